@@ -0,0 +1,433 @@
+use crate::html_renderer::{AuditReport, escape_html, render_static_page};
+use crate::sites::Site;
+use crate::types::FileEntry;
+use log::{debug, error, info};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Directory next to the dashboard that holds the archive pages
+const PAGES_DIR: &str = "archives";
+
+/// Files below this size count as empty, as in the integrity table
+const TINY_THRESHOLD: u64 = 1000;
+
+/// Ratio to the name's median above which a file is too large, as for
+/// directory anomalies
+const TOO_LARGE_RATIO: f64 = 1.2;
+
+/// Size of one file compared with the median of every file with its name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeClass {
+    Empty,
+    TooSmall,
+    TooLarge,
+    Normal,
+    /// Every file with this name is empty, so there is no median
+    NoBaseline,
+}
+
+impl SizeClass {
+    #[must_use]
+    pub fn classify(size: u64, median: Option<u64>, threshold: f64) -> Self {
+        if size < TINY_THRESHOLD {
+            return SizeClass::Empty;
+        }
+        match median {
+            Some(median) if median > 0 => {
+                let ratio = size as f64 / median as f64;
+                if ratio < threshold {
+                    SizeClass::TooSmall
+                } else if ratio > TOO_LARGE_RATIO {
+                    SizeClass::TooLarge
+                } else {
+                    SizeClass::Normal
+                }
+            }
+            _ => SizeClass::NoBaseline,
+        }
+    }
+
+    #[must_use]
+    pub fn label(self) -> &'static str {
+        match self {
+            SizeClass::Empty => "Empty",
+            SizeClass::TooSmall => "Too Small",
+            SizeClass::TooLarge => "Too Large",
+            SizeClass::Normal => "Normal",
+            SizeClass::NoBaseline => "No baseline",
+        }
+    }
+
+    fn css_class(self) -> &'static str {
+        match self {
+            SizeClass::Empty => "red",
+            SizeClass::TooSmall | SizeClass::TooLarge => "yellow",
+            SizeClass::Normal => "green",
+            SizeClass::NoBaseline => "",
+        }
+    }
+}
+
+/// Pages of one line, relative to the dashboard
+#[must_use]
+pub fn line_dir(site: Option<&str>, line_id: &str) -> String {
+    match site {
+        Some(site) => format!("{}/{}/Line_{}", PAGES_DIR, slug(site), slug(line_id)),
+        None => format!("{}/Line_{}", PAGES_DIR, slug(line_id)),
+    }
+}
+
+/// Link from the dashboard to a line's folder list in the index
+#[must_use]
+pub fn index_href(line_dir: &str) -> String {
+    let anchor = line_dir
+        .strip_prefix(&format!("{PAGES_DIR}/"))
+        .unwrap_or(line_dir);
+    format!("{PAGES_DIR}/index.html#{anchor}")
+}
+
+/// File name of the page of an archive folder (nested keys are flattened)
+#[must_use]
+pub fn page_name(folder: &str) -> String {
+    format!("{}.html", slug(folder))
+}
+
+/// `name` as a single path component that is safe in a URL. ASCII letters,
+/// digits, `-`, `_` and `.` are kept; every other byte (and a leading `.`)
+/// becomes `~` and two hex digits, so different names never share a page.
+fn slug(name: &str) -> String {
+    let mut slug = String::with_capacity(name.len());
+    for (i, byte) in name.bytes().enumerate() {
+        let keep =
+            byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_') || (byte == b'.' && i > 0);
+        if keep {
+            slug.push(char::from(byte));
+        } else {
+            slug.push_str(&format!("~{byte:02X}"));
+        }
+    }
+    slug
+}
+
+/// Write the index and one page per archive folder next to `output_file`,
+/// then point the reports at them so the dashboard links to the pages.
+///
+/// The pages are built in a temporary directory and swapped in, so pages of
+/// folders that no longer exist do not linger.
+pub fn write_archive_pages(
+    output_file: &str,
+    sites: &[Site],
+    site_reports: &mut [Vec<(String, AuditReport)>],
+) {
+    let root = Path::new(output_file)
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    let dashboard = Path::new(output_file)
+        .file_name()
+        .map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+
+    match write_pages(root, &dashboard, sites, site_reports) {
+        Ok(count) => {
+            info!(
+                "Archive pages written to: {}",
+                root.join(PAGES_DIR).display()
+            );
+            debug!("{} archive pages", count);
+            for (site, reports) in sites.iter().zip(site_reports.iter_mut()) {
+                for (line_id, report) in reports {
+                    report.archive_pages = Some(line_dir(site.name.as_deref(), line_id));
+                }
+            }
+        }
+        Err(e) => error!(
+            "Error writing archive pages to {}: {}",
+            root.join(PAGES_DIR).display(),
+            e
+        ),
+    }
+}
+
+fn write_pages(
+    root: &Path,
+    dashboard: &str,
+    sites: &[Site],
+    site_reports: &[Vec<(String, AuditReport)>],
+) -> io::Result<usize> {
+    let tmp_dir = root.join(format!("{PAGES_DIR}.tmp"));
+    if tmp_dir.exists() {
+        fs::remove_dir_all(&tmp_dir)?;
+    }
+    fs::create_dir_all(&tmp_dir)?;
+
+    let mut count = 0;
+    let mut index = String::new();
+    for (site, reports) in sites.iter().zip(site_reports) {
+        if let Some(name) = &site.name {
+            index.push_str(&format!(
+                "  <h1 class=\"site-title\">{}</h1>\n",
+                escape_html(name)
+            ));
+        }
+        for (line_id, report) in reports {
+            let line_dir = line_dir(site.name.as_deref(), line_id);
+            // Paths below are relative to the pages directory
+            let dir = line_dir
+                .strip_prefix(&format!("{PAGES_DIR}/"))
+                .unwrap_or(&line_dir);
+            fs::create_dir_all(tmp_dir.join(dir))?;
+            let up = "../".repeat(dir.split('/').count());
+
+            let folders = folder_files(&report.files);
+            let medians = name_medians(report);
+            index.push_str(&render_index_section(line_id, report, dir, &folders));
+            for (folder, files) in &folders {
+                let page = ArchivePage {
+                    line_id,
+                    folder,
+                    files,
+                    medians: &medians,
+                };
+                let html = render_archive_page(&page, report, &up, dashboard);
+                fs::write(tmp_dir.join(dir).join(page_name(folder)), html)?;
+                count += 1;
+            }
+        }
+    }
+    let index = format!(
+        "  <p><a href=\"../{}\">← Dashboard</a></p>\n{}",
+        escape_html(dashboard),
+        index
+    );
+    fs::write(tmp_dir.join("index.html"), render_static_page(&index))?;
+
+    let pages_dir = root.join(PAGES_DIR);
+    let old_dir = root.join(format!("{PAGES_DIR}.old"));
+    if old_dir.exists() {
+        fs::remove_dir_all(&old_dir)?;
+    }
+    if pages_dir.exists() {
+        fs::rename(&pages_dir, &old_dir)?;
+    }
+    fs::rename(&tmp_dir, &pages_dir)?;
+    if old_dir.exists() {
+        fs::remove_dir_all(&old_dir)?;
+    }
+    Ok(count)
+}
+
+/// Files of each archive folder, folders sorted by name
+fn folder_files(files: &[FileEntry]) -> BTreeMap<&str, Vec<&FileEntry>> {
+    let mut folders: BTreeMap<&str, Vec<&FileEntry>> = BTreeMap::new();
+    for file in files {
+        folders.entry(&file.parent_dir).or_default().push(file);
+    }
+    for files in folders.values_mut() {
+        files.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+    folders
+}
+
+/// Median size per file name, from the integrity table
+fn name_medians(report: &AuditReport) -> HashMap<&str, u64> {
+    report
+        .integrity_stats
+        .iter()
+        .flat_map(|stats| &stats.rows)
+        .filter(|row| row.valid_stats)
+        .map(|row| (row.name.as_str(), row.median_size))
+        .collect()
+}
+
+fn folder_anomaly<'a>(report: &'a AuditReport, folder: &str) -> Option<&'a str> {
+    report
+        .anomaly_report
+        .as_ref()?
+        .anomalies
+        .iter()
+        .find(|a| a.name == folder)
+        .map(|a| a.category.as_str())
+}
+
+fn render_index_section(
+    line_id: &str,
+    report: &AuditReport,
+    dir: &str,
+    folders: &BTreeMap<&str, Vec<&FileEntry>>,
+) -> String {
+    let mut html = String::new();
+    html.push_str(&format!(
+        "  <div class=\"section\" id=\"{}\"><h3 class=\"section-title\">Line {}</h3>",
+        escape_html(dir),
+        escape_html(line_id)
+    ));
+    if folders.is_empty() {
+        html.push_str("<p>No completed archive folders.</p></div>\n");
+        return html;
+    }
+
+    html.push_str(r#"<table class="data-table"><thead><tr>"#);
+    html.push_str("<th>Archive</th><th>Files</th><th>Size</th><th>Bad</th><th>Anomaly</th>");
+    html.push_str("</tr></thead><tbody>");
+    for (folder, files) in folders {
+        let bad = files.iter().filter(|f| !f.is_valid).count();
+        let size: u64 = files.iter().map(|f| f.size).sum();
+        let anomaly = folder_anomaly(report, folder);
+        html.push_str(&format!(
+            r#"<tr><td><a href="{}/{}">{}</a></td><td>{}</td><td>{}</td><td class="{}">{}</td><td class="{}">{}</td></tr>"#,
+            escape_html(dir),
+            escape_html(&page_name(folder)),
+            escape_html(folder),
+            files.len(),
+            human_bytes::human_bytes(size as f64),
+            if bad > 0 { "red" } else { "green" },
+            bad,
+            if anomaly.is_some() { "yellow" } else { "" },
+            anomaly.unwrap_or("-")
+        ));
+    }
+    html.push_str("</tbody></table></div>\n");
+    html
+}
+
+/// One archive folder and the medians its files are compared with
+struct ArchivePage<'a> {
+    line_id: &'a str,
+    folder: &'a str,
+    files: &'a [&'a FileEntry],
+    medians: &'a HashMap<&'a str, u64>,
+}
+
+/// `up` leads from the page back to the pages directory
+fn render_archive_page(
+    page: &ArchivePage,
+    report: &AuditReport,
+    up: &str,
+    dashboard: &str,
+) -> String {
+    let ArchivePage {
+        line_id,
+        folder,
+        files,
+        medians,
+    } = *page;
+    let mut html = String::new();
+    html.push_str(&format!(
+        "  <p><a href=\"{up}index.html\">← All archives</a> · <a href=\"{up}../{}\">Dashboard</a></p>\n",
+        escape_html(dashboard)
+    ));
+    html.push_str(&format!(
+        "  <h1>Line {} · {}</h1>\n",
+        escape_html(line_id),
+        escape_html(folder)
+    ));
+
+    let bad = files.iter().filter(|f| !f.is_valid).count();
+    let size: u64 = files.iter().map(|f| f.size).sum();
+    html.push_str(r#"  <div class="section">"#);
+    html.push_str(&format!(
+        "<p><strong>Files:</strong> {} ({}) · <strong>Bad:</strong> <span class=\"{}\">{}</span>",
+        files.len(),
+        human_bytes::human_bytes(size as f64),
+        if bad > 0 { "red" } else { "green" },
+        bad
+    ));
+    if let Some(category) = folder_anomaly(report, folder) {
+        html.push_str(&format!(
+            " · <strong>Folder Size:</strong> <span class=\"yellow\">{category}</span>"
+        ));
+    }
+    html.push_str("</p>");
+
+    html.push_str(r#"<table class="data-table"><thead><tr>"#);
+    html.push_str("<th>File</th><th>Format</th><th>Size</th><th>Name Median</th><th>Ratio</th><th>Valid</th><th>Modified</th><th>Class</th>");
+    html.push_str("</tr></thead><tbody>");
+    for file in files {
+        let median = medians.get(file.name.as_str()).copied();
+        let class = SizeClass::classify(file.size, median, report.anomaly_threshold);
+        let ratio = median.filter(|m| *m > 0).map_or_else(
+            || "-".to_string(),
+            |m| format!("{:.0}%", file.size as f64 * 100.0 / m as f64),
+        );
+        let validity = if file.is_valid {
+            r#"<span class="green">OK</span>"#.to_string()
+        } else {
+            format!(
+                r#"<span class="red">Bad: {}</span>"#,
+                escape_html(file.invalid_reason.as_deref().unwrap_or("Unknown error"))
+            )
+        };
+        html.push_str(&format!(
+            r#"<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td class="{}">{}</td></tr>"#,
+            escape_html(&file.name),
+            file.format,
+            human_bytes::human_bytes(file.size as f64),
+            median.map_or_else(|| "-".to_string(), |m| human_bytes::human_bytes(m as f64)),
+            ratio,
+            validity,
+            file.modified.format("%Y-%m-%d %H:%M"),
+            class.css_class(),
+            class.label()
+        ));
+    }
+    html.push_str("</tbody></table></div>\n");
+    render_static_page(&html)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_size_class() {
+        assert_eq!(SizeClass::classify(10, Some(5000), 0.5), SizeClass::Empty);
+        assert_eq!(
+            SizeClass::classify(2000, Some(5000), 0.5),
+            SizeClass::TooSmall
+        );
+        assert_eq!(
+            SizeClass::classify(5000, Some(5000), 0.5),
+            SizeClass::Normal
+        );
+        assert_eq!(
+            SizeClass::classify(6500, Some(5000), 0.5),
+            SizeClass::TooLarge
+        );
+        assert_eq!(SizeClass::classify(5000, None, 0.5), SizeClass::NoBaseline);
+    }
+
+    #[test]
+    fn test_page_paths() {
+        assert_eq!(line_dir(None, "B"), "archives/Line_B");
+        assert_eq!(
+            line_dir(Some("north site"), "B"),
+            "archives/north~20site/Line_B"
+        );
+        assert_eq!(
+            index_href("archives/north~20site/Line_B"),
+            "archives/index.html#north~20site/Line_B"
+        );
+        assert_eq!(page_name("BeamB/2025/03/14"), "BeamB~2F2025~2F03~2F14.html");
+        assert_eq!(
+            page_name("Archive_Beam_B_2025-03-14"),
+            "Archive_Beam_B_2025-03-14.html"
+        );
+        assert_eq!(line_dir(Some(".."), "B"), "archives/~2E./Line_B");
+    }
+
+    #[test]
+    fn test_distinct_names_get_distinct_pages() {
+        let names = [
+            "North 1",
+            "North_1",
+            "North~201",
+            "Nörth",
+            "BeamB/2025/03/14",
+            "BeamB_2025_03_14",
+        ];
+        let slugs: std::collections::HashSet<String> = names.iter().map(|n| slug(n)).collect();
+        assert_eq!(slugs.len(), names.len());
+    }
+}
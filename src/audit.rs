@@ -0,0 +1,357 @@
+use crate::{alerts, calendar, estimates, gap_analysis, history, html_renderer, inventory};
+use crate::{manifest, members, ranking, scanner, sites, stats, transfer, types};
+use chrono::Local;
+use log::debug;
+use std::collections::HashSet;
+use std::fs;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+
+/// Dashboard HTML for the reports of every site, in `sites` order
+pub fn render_site_reports(
+    sites: &[sites::Site],
+    site_reports: Vec<Vec<(String, html_renderer::AuditReport)>>,
+) -> String {
+    match sites {
+        [site] if site.name.is_none() => html_renderer::render_dashboard(&site_reports[0]),
+        _ => html_renderer::render_sites_dashboard(
+            &sites
+                .iter()
+                .map(|site| site.name.clone().unwrap_or_default())
+                .zip(site_reports)
+                .collect::<Vec<_>>(),
+        ),
+    }
+}
+
+/// Replace the dashboard atomically so the web server never serves half a page
+pub fn write_dashboard(output_file: &str, html: &str) -> std::io::Result<()> {
+    let tmp_file = format!("{output_file}.tmp");
+    fs::write(&tmp_file, html)?;
+    fs::rename(&tmp_file, output_file)
+}
+
+/// Held by a dashboard run, or by the daemon for as long as it runs
+pub const DASHBOARD_LOCK: &str = "/tmp/beam_audit_dashboard.lock";
+
+pub fn acquire_lock(lockfile: &str) -> Result<fs::File, String> {
+    use std::io::ErrorKind;
+
+    // Try to create lockfile exclusively (fails if exists)
+    match OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o644)
+        .open(lockfile)
+    {
+        Ok(mut file) => {
+            // Write PID to lockfile
+            let pid = std::process::id();
+            writeln!(file, "{pid}").ok();
+            Ok(file)
+        }
+        Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+            Err("Lockfile already exists (another instance running)".to_string())
+        }
+        Err(e) => Err(format!("Failed to create lockfile: {e}")),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn collect_audit_data(
+    line_id: &str,
+    site: &sites::Site,
+    alert_threshold: u64,
+    max_bad_per_archive: usize,
+    anomaly_threshold: f64,
+    scan_options: &scanner::ScanOptions,
+    source_inventory: Option<&[inventory::SourceFile]>,
+    history_retention_days: u32,
+) -> html_renderer::AuditReport {
+    let search_dir = format!("{}/Line {}", site.base_dir, line_id);
+
+    let sample = transfer::TransferSample::since_snapshot(
+        &search_dir,
+        &scan_options.dir_pattern,
+        &transfer::snapshot_file_path(&site.base_dir, line_id, transfer::RunKind::Dashboard),
+    );
+    let activity = sample.activity(line_id, &search_dir);
+    let scan = scan_line(
+        line_id,
+        &site.base_dir,
+        scan_options,
+        &activity.growing_dirs,
+        source_inventory,
+    );
+    let since_ts = update_transfer_state(line_id, site, activity.speed_bps, alert_threshold);
+
+    let mut report = build_audit_report(
+        line_id,
+        site,
+        &sample.after,
+        &activity,
+        &scan,
+        since_ts,
+        max_bad_per_archive,
+        anomaly_threshold,
+        scan_options,
+        source_inventory,
+    );
+    history::record(
+        &site.base_dir,
+        line_id,
+        transfer::RunKind::Dashboard,
+        history_retention_days,
+        &mut report,
+    );
+    report
+}
+
+/// Archive files of one line, with the manifest check that runs alongside them
+pub struct LineScan {
+    pub files: Vec<types::FileEntry>,
+    pub manifest_report: Option<manifest::ManifestReport>,
+}
+
+pub fn scan_line(
+    line_id: &str,
+    base_dir: &str,
+    scan_options: &scanner::ScanOptions,
+    growing_dirs: &HashSet<String>,
+    source_inventory: Option<&[inventory::SourceFile]>,
+) -> LineScan {
+    let search_dir = format!("{}/Line {}", base_dir, line_id);
+    let files = scanner::scan_files(&search_dir, &scan_options.for_line(base_dir, line_id));
+    let manifest_report = scan_options.manifests.then(|| {
+        // Which days are complete decides which get a first manifest
+        let inventory_report = source_inventory.map(|source| {
+            inventory::compare_inventory(
+                source,
+                &inventory::destination_sizes(&search_dir, line_id, &scan_options.dir_pattern),
+                growing_dirs,
+                line_id,
+                &scan_options.dir_pattern,
+            )
+        });
+        manifest::process_manifests(
+            &search_dir,
+            line_id,
+            scan_options,
+            growing_dirs,
+            inventory_report.as_ref(),
+            Some(&manifest::hash_cache_path(base_dir, line_id)),
+        )
+    });
+    LineScan {
+        files,
+        manifest_report,
+    }
+}
+
+/// Record the ACTIVE/IDLE state of one line, send alerts when it has
+/// persisted long enough, and return the timestamp the state started at
+pub fn update_transfer_state(
+    line_id: &str,
+    site: &sites::Site,
+    speed_bps: u64,
+    alert_threshold: u64,
+) -> String {
+    let base_dir = site.base_dir.as_str();
+    let state_file = format!("{}/.transfer_state_{}", base_dir, line_id);
+    let since_file = format!("{}/.transfer_since_{}", base_dir, line_id);
+
+    let current_state = if speed_bps > 0 { "ACTIVE" } else { "IDLE" };
+    let prev_state = fs::read_to_string(&state_file).unwrap_or_else(|_| "IDLE".to_string());
+    let prev_state = prev_state.trim();
+    debug!("Line {}: Read previous state: {}", line_id, prev_state);
+
+    if current_state != prev_state || !std::path::Path::new(&since_file).exists() {
+        let now_str = Local::now().format("%Y-%m-%d %H:%M").to_string();
+        debug!("Line {}: Writing since timestamp: {}", line_id, now_str);
+        fs::write(&since_file, &now_str).ok();
+    }
+    let since_ts = fs::read_to_string(&since_file).unwrap_or_default();
+    let since_ts = since_ts.trim().to_string();
+    debug!("Line {}: Read since timestamp: {}", line_id, since_ts);
+
+    // State change handling (email alerts) - use consolidated alert function
+    let speed_mib = speed_bps as f64 / 1_024.0 / 1_024.0;
+    alerts::check_and_send_alerts(
+        base_dir,
+        site.name.as_deref(),
+        line_id,
+        current_state,
+        prev_state,
+        speed_mib,
+        alert_threshold,
+    );
+
+    since_ts
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn build_audit_report(
+    line_id: &str,
+    site: &sites::Site,
+    sizes: &transfer::SizeWalk,
+    activity: &transfer::TransferActivity,
+    scan: &LineScan,
+    since_ts: String,
+    max_bad_per_archive: usize,
+    anomaly_threshold: f64,
+    scan_options: &scanner::ScanOptions,
+    source_inventory: Option<&[inventory::SourceFile]>,
+) -> html_renderer::AuditReport {
+    let base_dir = site.base_dir.as_str();
+    let (start_date, end_date) = (site.start_date, site.end_date);
+    let search_dir = format!("{}/Line {}", base_dir, line_id);
+    let tiny_threshold = 1000;
+    let files = &scan.files;
+    let speed_bps = activity.speed_bps;
+    let growing_dirs = &activity.growing_dirs;
+
+    // Create filtered file list for analysis (exclude files from growing directories)
+    let analysis_files: Vec<_> = files
+        .iter()
+        .filter(|f| !growing_dirs.contains(&f.parent_dir))
+        .cloned()
+        .collect();
+
+    debug!(
+        "Line {}: Total files: {}, Analysis files: {}",
+        line_id,
+        files.len(),
+        analysis_files.len()
+    );
+
+    let integrity_stats = stats::calculate_integrity_stats(&analysis_files, tiny_threshold);
+    let holidays = calendar::load_holidays(base_dir);
+    let gap_report = gap_analysis::find_gaps(files, line_id, &scan_options.dir_pattern, &holidays);
+    let inventory_report = source_inventory.map(|source| {
+        inventory::compare_inventory(
+            source,
+            &inventory::destination_sizes(&search_dir, line_id, &scan_options.dir_pattern),
+            growing_dirs,
+            line_id,
+            &scan_options.dir_pattern,
+        )
+    });
+    let estimates_report = estimates::calculate_estimates(
+        &search_dir,
+        &analysis_files,
+        files,
+        growing_dirs,
+        line_id,
+        &scan_options.dir_pattern,
+        sizes.total,
+        speed_bps,
+        start_date,
+        end_date,
+        inventory_report.as_ref(),
+    );
+    // Exclude growing directories from anomaly detection
+    let anomalies_report =
+        stats::calculate_anomalies(&sizes.stable_dirs(growing_dirs), anomaly_threshold);
+    let bad_files_report = stats::collect_bad_files(&analysis_files, line_id, max_bad_per_archive);
+    let member_report = members::calculate_member_sets(
+        &analysis_files,
+        line_id,
+        &scan_options.dir_pattern,
+        members::load_expected_members(base_dir, line_id),
+    );
+    let monthly_ranking = ranking::calculate_monthly_rankings(
+        &analysis_files,
+        Some(&gap_report),
+        anomalies_report.as_ref(),
+        member_report.as_ref(),
+        line_id,
+        &scan_options.dir_pattern,
+        start_date,
+        end_date,
+        tiny_threshold,
+    );
+
+    // Return AuditReport
+    html_renderer::AuditReport {
+        total_size: sizes.total,
+        total_files: files.len(),
+        speed_bps,
+        since_timestamp: since_ts,
+        recent_files: activity.recent_files.clone(),
+        recent_files_live: activity.live,
+        redundancy_check: None,
+        integrity_stats,
+        gap_report: Some(gap_report),
+        estimates_report,
+        anomaly_report: anomalies_report,
+        bad_files_report,
+        manifest_report: scan.manifest_report.clone(),
+        inventory_report,
+        member_report,
+        max_bad_per_archive,
+        history: Vec::new(),
+        files: analysis_files,
+        anomaly_threshold,
+        archive_pages: None,
+        monthly_ranking: Some(monthly_ranking),
+        holidays,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ArchiveFormat, FileEntry};
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_report_leaves_holidays_out_of_missing_days() {
+        // Mon 3 and Wed 5 March 2025; Tue 4 is listed in .holidays
+        let temp = tempfile::tempdir().unwrap();
+        let base_dir = temp.path().to_str().unwrap();
+        fs::create_dir_all(temp.path().join("Line B")).unwrap();
+        fs::write(temp.path().join(".holidays"), "2025-03-04\n").unwrap();
+        let date = |s| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+        let site = sites::Site::local(
+            base_dir,
+            vec!["B".to_string()],
+            date("2025-03-01"),
+            date("2025-03-31"),
+        );
+        let files = ["2025-03-03", "2025-03-05"]
+            .iter()
+            .map(|day| FileEntry {
+                name: "Cam1.zip".to_string(),
+                size: 5000,
+                format: ArchiveFormat::Zip,
+                is_valid: true,
+                invalid_reason: None,
+                modified: Local::now(),
+                parent_dir: format!("Archive_Beam_B_{day}"),
+            })
+            .collect();
+        let scan_options = scanner::ScanOptions::default();
+
+        let report = build_audit_report(
+            "B",
+            &site,
+            &transfer::SizeWalk::take(&format!("{base_dir}/Line B"), &scan_options.dir_pattern),
+            &transfer::TransferActivity::default(),
+            &LineScan {
+                files,
+                manifest_report: None,
+            },
+            String::new(),
+            3,
+            0.85,
+            &scan_options,
+            None,
+        );
+
+        assert_eq!(report.holidays, vec![date("2025-03-04")]);
+        assert!(report.gap_report.unwrap().missing_weekdays.is_empty());
+        let ranking = report.monthly_ranking.unwrap();
+        assert_eq!(ranking.months[0].missing_days, 0);
+    }
+}
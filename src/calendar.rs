@@ -0,0 +1,309 @@
+use crate::gap_analysis::{DayFolder, GapReport};
+use crate::stats::{AnomalyReport, BadFilesReport};
+use chrono::{Datelike, NaiveDate};
+use log::{debug, warn};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+
+/// Status of one day of the archive window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DayState {
+    Healthy,
+    BadFiles,
+    Anomaly,
+    Missing,
+    Weekend,
+    /// Weekday without an archive, listed in `.holidays`
+    Holiday,
+    /// Outside the oldest..newest archive range the gap analysis checks,
+    /// e.g. after the newest archive where the transfer has not reached it yet
+    NotYetDue,
+}
+
+impl DayState {
+    pub const ALL: [DayState; 7] = [
+        DayState::Healthy,
+        DayState::BadFiles,
+        DayState::Anomaly,
+        DayState::Missing,
+        DayState::Weekend,
+        DayState::Holiday,
+        DayState::NotYetDue,
+    ];
+
+    #[must_use]
+    pub fn css_class(self) -> &'static str {
+        match self {
+            DayState::Healthy => "cal-healthy",
+            DayState::BadFiles => "cal-bad",
+            DayState::Anomaly => "cal-anomaly",
+            DayState::Missing => "cal-missing",
+            DayState::Weekend => "cal-weekend",
+            DayState::Holiday => "cal-holiday",
+            DayState::NotYetDue => "cal-pending",
+        }
+    }
+
+    #[must_use]
+    pub fn label(self) -> &'static str {
+        match self {
+            DayState::Healthy => "Healthy",
+            DayState::BadFiles => "Bad archives",
+            DayState::Anomaly => "Size anomaly",
+            DayState::Missing => "Missing weekday",
+            DayState::Weekend => "Weekend / no archive expected",
+            DayState::Holiday => "Holiday",
+            DayState::NotYetDue => "Not yet copied",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarDay {
+    pub date: NaiveDate,
+    pub state: DayState,
+    pub folder: Option<DayFolder>,
+    pub bad_files: usize,
+    /// Anomaly category ("Too Small" or "Too Large")
+    pub anomaly: Option<String>,
+}
+
+impl CalendarDay {
+    /// Hover text: date, folder name and counts
+    #[must_use]
+    pub fn describe(&self) -> String {
+        let mut text = format!("{} ({})", self.date, self.date.format("%a"));
+        match &self.folder {
+            Some(folder) => {
+                text.push_str(&format!(" · {} · {} files", folder.name, folder.files));
+                if self.bad_files > 0 {
+                    text.push_str(&format!(" · {} bad", self.bad_files));
+                }
+                if let Some(category) = &self.anomaly {
+                    text.push_str(&format!(" · {category}"));
+                }
+            }
+            None => {
+                text.push_str(&format!(" · {}", self.state.label()));
+            }
+        }
+        text
+    }
+}
+
+/// Holidays of a site from `<base_dir>/.holidays`: one `YYYY-MM-DD` date
+/// per line, `#` starts a comment. Empty when the file is missing.
+#[must_use]
+pub fn load_holidays(base_dir: &str) -> Vec<NaiveDate> {
+    let path = format!("{}/.holidays", base_dir);
+    let Ok(content) = fs::read_to_string(&path) else {
+        return Vec::new();
+    };
+    let holidays: Vec<NaiveDate> = content
+        .lines()
+        .map(|l| l.split('#').next().unwrap_or("").trim())
+        .filter(|l| !l.is_empty())
+        .filter_map(|l| {
+            NaiveDate::parse_from_str(l, "%Y-%m-%d")
+                .inspect_err(|_| warn!("Ignoring invalid date '{}' in {}", l, path))
+                .ok()
+        })
+        .collect();
+    debug!("Loaded {} holidays from {}", holidays.len(), path);
+    holidays
+}
+
+/// One entry per day from `start` to `end`, classified from the gap,
+/// anomaly and bad-file reports. Only days the gap report checks (oldest to
+/// newest archive) can be missing; weekdays without an archive that are in
+/// `holidays` are holidays rather than missing.
+#[must_use]
+pub fn build_calendar(
+    start: NaiveDate,
+    end: NaiveDate,
+    gap: &GapReport,
+    anomalies: Option<&AnomalyReport>,
+    bad_files: Option<&BadFilesReport>,
+    holidays: &[NaiveDate],
+) -> Vec<CalendarDay> {
+    let holidays: HashSet<&NaiveDate> = holidays.iter().collect();
+    let anomaly_by_folder: HashMap<&str, &str> = anomalies
+        .map(|report| {
+            report
+                .anomalies
+                .iter()
+                .map(|a| (a.name.as_str(), a.category.as_str()))
+                .collect()
+        })
+        .unwrap_or_default();
+    let bad_by_folder: HashMap<&str, usize> = bad_files
+        .map(|report| {
+            report
+                .files_by_folder
+                .iter()
+                .map(|(folder, _, total)| (folder.as_str(), *total))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    start
+        .iter_days()
+        .take_while(|date| *date <= end)
+        .map(|date| {
+            let folder = gap.folders.get(&date).cloned();
+            let bad = folder
+                .as_ref()
+                .and_then(|f| bad_by_folder.get(f.name.as_str()).copied())
+                .unwrap_or(0);
+            let anomaly = folder
+                .as_ref()
+                .and_then(|f| anomaly_by_folder.get(f.name.as_str()))
+                .map(|category| (*category).to_string());
+            let state = if folder.is_some() {
+                if bad > 0 {
+                    DayState::BadFiles
+                } else if anomaly.is_some() {
+                    DayState::Anomaly
+                } else {
+                    DayState::Healthy
+                }
+            } else if gap.is_empty || date < gap.start_date || date > gap.end_date {
+                DayState::NotYetDue
+            } else if date.weekday().number_from_monday() > 5 {
+                DayState::Weekend
+            } else if holidays.contains(&date) {
+                DayState::Holiday
+            } else {
+                DayState::Missing
+            };
+            CalendarDay {
+                date,
+                state,
+                folder,
+                bad_files: bad,
+                anomaly,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gap_analysis::find_gaps;
+    use crate::naming::DirPattern;
+    use crate::stats::{Anomaly, BadFile};
+    use crate::types::{ArchiveFormat, FileEntry};
+    use chrono::Local;
+
+    fn entry(date: &str, valid: bool) -> FileEntry {
+        FileEntry {
+            name: "Cam1.zip".to_string(),
+            size: 100,
+            format: ArchiveFormat::Zip,
+            is_valid: valid,
+            invalid_reason: None,
+            modified: Local::now(),
+            parent_dir: format!("Archive_Beam_B_{date}"),
+        }
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn test_calendar_day_states() {
+        // Thu 13 .. Wed 19 March 2025; Mon 17 is missing, nothing before
+        // Thu 13 or after Tue 18 (listing those as holidays changes nothing)
+        let files = vec![
+            entry("2025-03-13", true),
+            entry("2025-03-14", true),
+            entry("2025-03-14", false),
+            entry("2025-03-18", true),
+        ];
+        let gap = find_gaps(&files, "B", &DirPattern::default(), &[]);
+        let anomalies = AnomalyReport {
+            median_daily_size: 100,
+            anomalies: vec![Anomaly {
+                name: "Archive_Beam_B_2025-03-18".to_string(),
+                size: 10,
+                category: "Too Small".to_string(),
+            }],
+        };
+        let bad_files = BadFilesReport {
+            total_count: 1,
+            files_by_folder: vec![(
+                "Archive_Beam_B_2025-03-14".to_string(),
+                vec![BadFile {
+                    relative_path: "Line B/Archive_Beam_B_2025-03-14/Cam1.zip".to_string(),
+                    format: ArchiveFormat::Zip,
+                    size: 100,
+                    reason: "Truncated".to_string(),
+                }],
+                1,
+            )],
+        };
+
+        let days = build_calendar(
+            date("2025-03-12"),
+            date("2025-03-19"),
+            &gap,
+            Some(&anomalies),
+            Some(&bad_files),
+            &[date("2025-03-12"), date("2025-03-19")],
+        );
+        let states: Vec<DayState> = days.iter().map(|d| d.state).collect();
+        assert_eq!(
+            states,
+            vec![
+                DayState::NotYetDue,
+                DayState::Healthy,
+                DayState::BadFiles,
+                DayState::Weekend,
+                DayState::Weekend,
+                DayState::Missing,
+                DayState::Anomaly,
+                DayState::NotYetDue,
+            ]
+        );
+        assert_eq!(
+            days[2].describe(),
+            "2025-03-14 (Fri) · Archive_Beam_B_2025-03-14 · 2 files · 1 bad"
+        );
+        assert_eq!(days[5].describe(), "2025-03-17 (Mon) · Missing weekday");
+        assert_eq!(days[0].describe(), "2025-03-12 (Wed) · Not yet copied");
+    }
+
+    #[test]
+    fn test_calendar_agrees_with_gap_report() {
+        // Archives Wed 12 and Fri 14 March 2025; Mon 10 and Tue 11 are
+        // before the first archive, Thu 13 a holiday
+        let files = vec![entry("2025-03-12", true), entry("2025-03-14", true)];
+        let holidays = [date("2025-03-13")];
+        let gap = find_gaps(&files, "B", &DirPattern::default(), &holidays);
+        assert!(gap.missing_weekdays.is_empty());
+
+        let days = build_calendar(
+            date("2025-03-10"),
+            date("2025-03-14"),
+            &gap,
+            None,
+            None,
+            &holidays,
+        );
+        let states: Vec<DayState> = days.iter().map(|d| d.state).collect();
+        assert_eq!(
+            states,
+            vec![
+                DayState::NotYetDue,
+                DayState::NotYetDue,
+                DayState::Healthy,
+                DayState::Holiday,
+                DayState::Healthy,
+            ]
+        );
+        assert!(!states.contains(&DayState::Missing));
+        assert_eq!(days[3].describe(), "2025-03-13 (Thu) · Holiday");
+    }
+}
@@ -0,0 +1,201 @@
+use chrono::{Duration, NaiveDateTime};
+
+/// Charts never draw more points than this; denser history is averaged
+const MAX_POINTS: usize = 200;
+
+const WIDTH: f64 = 320.0;
+const HEIGHT: f64 = 110.0;
+const LEFT: f64 = 50.0;
+const RIGHT: f64 = 310.0;
+const TOP: f64 = 10.0;
+const BOTTOM: f64 = 85.0;
+
+/// A line chart of `points` between `now - window` and `now`, as inline SVG.
+///
+/// `zero_based` anchors the value axis at zero (rates and counts); levels
+/// such as stored bytes use the observed range so changes stay visible.
+#[must_use]
+pub fn render_trend_chart(
+    title: &str,
+    points: &[(NaiveDateTime, f64)],
+    now: NaiveDateTime,
+    window: Duration,
+    zero_based: bool,
+    format_value: fn(f64) -> String,
+) -> String {
+    let start = now - window;
+    let points = downsample(
+        points
+            .iter()
+            .filter(|(t, _)| *t >= start && *t <= now)
+            .copied()
+            .collect(),
+        start,
+        window,
+    );
+
+    let mut svg = format!(
+        r#"<svg class="chart" viewBox="0 0 {WIDTH} {HEIGHT}" xmlns="http://www.w3.org/2000/svg" role="img">"#
+    );
+    let Some(last) = points.last() else {
+        svg.push_str(&format!("<title>{title}: no runs recorded</title>"));
+        svg.push_str(&format!(
+            r##"<text x="{}" y="{}" fill="#777" font-size="9" text-anchor="middle">{title}: no runs recorded</text></svg>"##,
+            WIDTH / 2.0,
+            HEIGHT / 2.0
+        ));
+        return svg;
+    };
+    svg.push_str(&format!(
+        "<title>{}: latest {}</title>",
+        title,
+        format_value(last.1)
+    ));
+
+    let max = points.iter().map(|p| p.1).fold(f64::MIN, f64::max);
+    let min = if zero_based {
+        0.0
+    } else {
+        points.iter().map(|p| p.1).fold(f64::MAX, f64::min)
+    };
+    // A flat series is drawn in the middle instead of dividing by zero
+    let (low, high) = if max > min {
+        (min, max)
+    } else {
+        (min - 1.0, max + 1.0)
+    };
+    let span_ms = window.num_milliseconds().max(1) as f64;
+    let x =
+        |t: NaiveDateTime| LEFT + (t - start).num_milliseconds() as f64 / span_ms * (RIGHT - LEFT);
+    let y = |v: f64| BOTTOM - (v - low) / (high - low) * (BOTTOM - TOP);
+
+    // Axes, value labels and time labels
+    svg.push_str(&format!(
+        r##"<line x1="{LEFT}" y1="{BOTTOM}" x2="{RIGHT}" y2="{BOTTOM}" stroke="#333"/><line x1="{LEFT}" y1="{mid}" x2="{RIGHT}" y2="{mid}" stroke="#222" stroke-dasharray="3,3"/>"##,
+        mid = (TOP + BOTTOM) / 2.0
+    ));
+    for (value, pos) in [(high, TOP), (low, BOTTOM)] {
+        svg.push_str(&format!(
+            r##"<text x="{}" y="{:.1}" fill="#777" font-size="8" text-anchor="end">{}</text>"##,
+            LEFT - 4.0,
+            pos + 3.0,
+            format_value(value.max(0.0))
+        ));
+    }
+    let time_format = if window > Duration::days(1) {
+        "%m-%d"
+    } else {
+        "%H:%M"
+    };
+    svg.push_str(&format!(
+        r##"<text x="{LEFT}" y="{label_y}" fill="#777" font-size="8">{}</text><text x="{RIGHT}" y="{label_y}" fill="#777" font-size="8" text-anchor="end">{}</text>"##,
+        start.format(time_format),
+        now.format(time_format),
+        label_y = BOTTOM + 12.0
+    ));
+    svg.push_str(&format!(
+        r##"<text x="{}" y="{}" fill="#d1d1d1" font-size="9" text-anchor="middle">{} · {}</text>"##,
+        (LEFT + RIGHT) / 2.0,
+        HEIGHT - 2.0,
+        title,
+        format_value(last.1)
+    ));
+
+    let coords: Vec<String> = points
+        .iter()
+        .map(|(t, v)| format!("{:.1},{:.1}", x(*t), y(*v)))
+        .collect();
+    if let [single] = coords.as_slice() {
+        let (cx, cy) = single.split_once(',').unwrap_or_default();
+        svg.push_str(&format!(
+            r##"<circle cx="{cx}" cy="{cy}" r="2" fill="#4CAF50"/>"##
+        ));
+    } else {
+        svg.push_str(&format!(
+            r##"<polyline points="{}" fill="none" stroke="#4CAF50" stroke-width="1.5"/>"##,
+            coords.join(" ")
+        ));
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+/// Average the points into at most `MAX_POINTS` equal time buckets
+fn downsample(
+    points: Vec<(NaiveDateTime, f64)>,
+    start: NaiveDateTime,
+    window: Duration,
+) -> Vec<(NaiveDateTime, f64)> {
+    if points.len() <= MAX_POINTS {
+        return points;
+    }
+    let bucket_ms = (window.num_milliseconds() / MAX_POINTS as i64).max(1);
+    let mut buckets: Vec<(NaiveDateTime, f64, usize)> = Vec::new();
+    for (t, v) in points {
+        let bucket = (t - start).num_milliseconds() / bucket_ms;
+        let bucket_time = start + Duration::milliseconds(bucket * bucket_ms);
+        match buckets.last_mut() {
+            Some((time, sum, count)) if *time == bucket_time => {
+                *sum += v;
+                *count += 1;
+            }
+            _ => buckets.push((bucket_time, v, 1)),
+        }
+    }
+    buckets
+        .into_iter()
+        .map(|(t, sum, count)| (t, sum / count as f64))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(minutes: i64) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str("2025-03-14 00:00:00", "%Y-%m-%d %H:%M:%S").unwrap()
+            + Duration::minutes(minutes)
+    }
+
+    fn plain(v: f64) -> String {
+        format!("{v:.0}")
+    }
+
+    #[test]
+    fn test_chart_plots_only_the_window() {
+        let points = vec![(at(0), 50.0), (at(600), 10.0), (at(1200), 30.0)];
+        let svg = render_trend_chart("Speed", &points, at(1440), Duration::hours(16), true, plain);
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.ends_with("</svg>"));
+        assert!(svg.contains("<title>Speed: latest 30</title>"));
+        // 00:00 is outside the 16h window, so the top of the axis is 30
+        assert!(svg.contains(">30</text>"));
+        assert!(!svg.contains(">50</text>"));
+        assert_eq!(svg.matches("<polyline").count(), 1);
+        assert!(!svg.contains("<script"));
+    }
+
+    #[test]
+    fn test_empty_and_single_point_charts() {
+        let svg = render_trend_chart("Free Space", &[], at(60), Duration::hours(1), false, plain);
+        assert!(svg.contains("no runs recorded"));
+
+        let svg = render_trend_chart(
+            "Free Space",
+            &[(at(30), 7.0)],
+            at(60),
+            Duration::hours(1),
+            false,
+            plain,
+        );
+        assert!(svg.contains("<circle"));
+    }
+
+    #[test]
+    fn test_downsample_averages_buckets() {
+        let points: Vec<_> = (0..1000).map(|m| (at(m), m as f64)).collect();
+        let sampled = downsample(points, at(0), Duration::minutes(1000));
+        assert_eq!(sampled.len(), MAX_POINTS);
+        assert_eq!(sampled[0], (at(0), 2.0));
+    }
+}
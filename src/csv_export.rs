@@ -0,0 +1,287 @@
+use crate::gap_analysis::GapReport;
+use crate::ranking::MonthlyRankingReport;
+use crate::stats::{self, AnomalyReport, IntegrityStats};
+use crate::types::FileEntry;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// One CSV file: a header line, then one line per row. Every file is
+/// written the same way (comma separated, `\n` line endings, fields quoted
+/// only when needed) so they open alike in any spreadsheet.
+struct CsvTable {
+    header: &'static [&'static str],
+    rows: Vec<Vec<String>>,
+}
+
+impl CsvTable {
+    fn new(header: &'static [&'static str]) -> Self {
+        Self {
+            header,
+            rows: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, row: Vec<String>) {
+        debug_assert_eq!(row.len(), self.header.len());
+        self.rows.push(row);
+    }
+
+    fn render(&self) -> String {
+        let mut out = String::new();
+        push_line(&mut out, self.header.iter().copied());
+        for row in &self.rows {
+            push_line(&mut out, row.iter().map(String::as_str));
+        }
+        out
+    }
+}
+
+fn push_line<'a>(out: &mut String, fields: impl Iterator<Item = &'a str>) {
+    let line: Vec<String> = fields.map(quote).collect();
+    out.push_str(&line.join(","));
+    out.push('\n');
+}
+
+/// RFC 4180 quoting: fields with a comma, quote or line break are wrapped
+/// in double quotes, inner quotes doubled
+fn quote(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn bad_files_table(files: &[FileEntry], line_id: &str) -> CsvTable {
+    let mut table = CsvTable::new(&["folder", "relative_path", "format", "size_bytes", "reason"]);
+    // Every bad file, not just the first `max_bad_per_archive` of each folder
+    if let Some(report) = stats::collect_bad_files(files, line_id, usize::MAX) {
+        for (folder, bad_files, _) in &report.files_by_folder {
+            for file in bad_files {
+                table.push(vec![
+                    folder.clone(),
+                    file.relative_path.clone(),
+                    file.format.to_string(),
+                    file.size.to_string(),
+                    file.reason.clone(),
+                ]);
+            }
+        }
+    }
+    table
+}
+
+fn missing_weekdays_table(gaps: &GapReport) -> CsvTable {
+    let mut table = CsvTable::new(&["date", "weekday"]);
+    for date in &gaps.missing_weekdays {
+        table.push(vec![
+            date.format("%Y-%m-%d").to_string(),
+            date.format("%A").to_string(),
+        ]);
+    }
+    table
+}
+
+fn anomalies_table(report: Option<&AnomalyReport>) -> CsvTable {
+    let mut table = CsvTable::new(&[
+        "folder",
+        "size_bytes",
+        "median_bytes",
+        "median_ratio",
+        "category",
+    ]);
+    if let Some(report) = report {
+        for anomaly in &report.anomalies {
+            let ratio = if report.median_daily_size > 0 {
+                anomaly.size as f64 / report.median_daily_size as f64
+            } else {
+                0.0
+            };
+            table.push(vec![
+                anomaly.name.clone(),
+                anomaly.size.to_string(),
+                report.median_daily_size.to_string(),
+                format!("{ratio:.3}"),
+                anomaly.category.clone(),
+            ]);
+        }
+    }
+    table
+}
+
+fn integrity_table(stats: Option<&IntegrityStats>) -> CsvTable {
+    let mut table = CsvTable::new(&[
+        "name",
+        "format",
+        "total",
+        "empty",
+        "bad",
+        "min_bytes",
+        "max_bytes",
+        "median_bytes",
+        "std_dev_bytes",
+        "valid_stats",
+    ]);
+    for row in stats.iter().flat_map(|s| &s.rows) {
+        table.push(vec![
+            row.name.clone(),
+            row.format.to_string(),
+            row.total.to_string(),
+            row.empty.to_string(),
+            row.bad.to_string(),
+            row.min_size.to_string(),
+            row.max_size.to_string(),
+            row.median_size.to_string(),
+            format!("{:.0}", row.std_dev),
+            row.valid_stats.to_string(),
+        ]);
+    }
+    table
+}
+
+fn ranking_table(report: &MonthlyRankingReport) -> CsvTable {
+    let mut table = CsvTable::new(&[
+        "rank",
+        "month",
+        "health_score",
+        "missing_days",
+        "anomalies",
+        "invalid_files",
+        "empty_files",
+        "missing_members",
+        "unexpected_members",
+        "expected_weekdays",
+        "actual_archives",
+        "complete",
+    ]);
+    for (rank, month) in report.months.iter().enumerate() {
+        table.push(vec![
+            (rank + 1).to_string(),
+            format!("{}-{:02}", month.year, month.month),
+            format!("{:.1}", month.health_score),
+            month.missing_days.to_string(),
+            month.anomaly_count.to_string(),
+            month.invalid_files.to_string(),
+            month.empty_files.to_string(),
+            month.missing_members.to_string(),
+            month.unexpected_members.to_string(),
+            month.expected_weekdays.to_string(),
+            month.actual_archives.to_string(),
+            month.is_complete.to_string(),
+        ]);
+    }
+    table
+}
+
+/// Write one CSV per report of a line into `dir` (created if missing):
+/// `line_<id>_bad_files.csv`, `_missing_weekdays.csv`, `_anomalies.csv`,
+/// `_integrity.csv` and `_monthly_ranking.csv`. Returns the written paths.
+pub fn export_line(
+    dir: &Path,
+    line_id: &str,
+    files: &[FileEntry],
+    integrity: Option<&IntegrityStats>,
+    gaps: &GapReport,
+    anomalies: Option<&AnomalyReport>,
+    ranking: &MonthlyRankingReport,
+) -> io::Result<Vec<PathBuf>> {
+    fs::create_dir_all(dir)?;
+    let tables = [
+        ("bad_files", bad_files_table(files, line_id)),
+        ("missing_weekdays", missing_weekdays_table(gaps)),
+        ("anomalies", anomalies_table(anomalies)),
+        ("integrity", integrity_table(integrity)),
+        ("monthly_ranking", ranking_table(ranking)),
+    ];
+
+    let mut written = Vec::new();
+    for (report, table) in tables {
+        let path = dir.join(format!("line_{line_id}_{report}.csv"));
+        fs::write(&path, table.render())?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::Anomaly;
+    use crate::types::ArchiveFormat;
+    use chrono::{Local, NaiveDate};
+
+    fn entry(folder: &str, name: &str, reason: Option<&str>) -> FileEntry {
+        FileEntry {
+            name: name.to_string(),
+            size: 100,
+            format: ArchiveFormat::Zip,
+            is_valid: reason.is_none(),
+            invalid_reason: reason.map(str::to_string),
+            modified: Local::now(),
+            parent_dir: folder.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("plain"), "plain");
+        assert_eq!(quote("a,b"), "\"a,b\"");
+        assert_eq!(quote("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(quote("two\nlines"), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn test_tables() {
+        // Four bad files in one folder: none dropped, unlike the report
+        let files: Vec<FileEntry> = (1..=4)
+            .map(|i| {
+                entry(
+                    "Archive_Beam_B_2025-03-14",
+                    &format!("Cam{i}.zip"),
+                    Some("CRC mismatch, member 2"),
+                )
+            })
+            .collect();
+        let csv = bad_files_table(&files, "B").render();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[0], "folder,relative_path,format,size_bytes,reason");
+        assert_eq!(
+            lines[1],
+            "Archive_Beam_B_2025-03-14,Line B/Archive_Beam_B_2025-03-14/Cam1.zip,ZIP,100,\"CRC mismatch, member 2\""
+        );
+
+        let gaps = GapReport {
+            start_date: NaiveDate::from_ymd_opt(2025, 3, 13).unwrap(),
+            end_date: NaiveDate::from_ymd_opt(2025, 3, 18).unwrap(),
+            missing_weekdays: vec![NaiveDate::from_ymd_opt(2025, 3, 17).unwrap()],
+            skipped_weekends: 2,
+            is_empty: false,
+            folders: Default::default(),
+        };
+        assert_eq!(
+            missing_weekdays_table(&gaps).render(),
+            "date,weekday\n2025-03-17,Monday\n"
+        );
+
+        let anomalies = AnomalyReport {
+            median_daily_size: 1000,
+            anomalies: vec![Anomaly {
+                name: "Archive_Beam_B_2025-03-18".to_string(),
+                size: 250,
+                category: "Too Small".to_string(),
+            }],
+        };
+        assert_eq!(
+            anomalies_table(Some(&anomalies)).render(),
+            "folder,size_bytes,median_bytes,median_ratio,category\n\
+             Archive_Beam_B_2025-03-18,250,1000,0.250,Too Small\n"
+        );
+        // No report still gives a header-only file
+        assert_eq!(
+            anomalies_table(None).render(),
+            "folder,size_bytes,median_bytes,median_ratio,category\n"
+        );
+    }
+}
@@ -0,0 +1,503 @@
+use crate::archive_pages;
+use crate::audit::{LineScan, acquire_lock, build_audit_report, render_site_reports, scan_line};
+use crate::audit::{update_transfer_state, write_dashboard};
+use crate::history;
+use crate::html_renderer::AuditReport;
+use crate::inventory::SourceFile;
+use crate::json_report;
+use crate::metrics;
+use crate::scanner::ScanOptions;
+use crate::server::{Server, Snapshot};
+use crate::sites::Site;
+use crate::transfer::{RunKind, SizeWalk, TransferActivity, TransferSample};
+use crate::watcher::TransferWatcher;
+use log::{debug, error, info, warn};
+use signal_hook::consts::{SIGINT, SIGTERM};
+use std::collections::HashSet;
+use std::fs;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often the shutdown flag and the schedules are checked
+const TICK: Duration = Duration::from_millis(500);
+
+/// How often the daemon does each kind of work
+#[derive(Debug, Clone, Copy)]
+pub struct Intervals {
+    /// Size walk of every line (speed, growing directories)
+    pub sample: Duration,
+    /// ACTIVE/IDLE state update and email alerts
+    pub alerts: Duration,
+    /// Dashboard rendered from the in-memory state
+    pub dashboard: Duration,
+    /// Archive validation, manifests and sidecars
+    pub scan: Duration,
+}
+
+/// Settings shared by every line, as for a one-off dashboard run
+pub struct DaemonOptions<'a> {
+    pub alert_threshold: u64,
+    pub max_bad_per_archive: usize,
+    pub anomaly_threshold: f64,
+    pub scan_options: &'a ScanOptions,
+    pub source_inventory: Option<&'a [SourceFile]>,
+    /// Also write the reports as JSON next to the dashboard
+    pub write_json: bool,
+    /// Prometheus textfile refreshed with the dashboard
+    pub metrics_file: Option<&'a str>,
+    /// HTTP server that gets every new dashboard (`serve`)
+    pub server: Option<&'a Server>,
+    /// See `history::append`
+    pub history_retention_days: u32,
+}
+
+/// A recurring job, first due at `next` and then every `interval`
+struct Schedule {
+    interval: Duration,
+    next: Instant,
+}
+
+impl Schedule {
+    fn new(interval: Duration, first: Instant) -> Self {
+        Self {
+            interval,
+            next: first,
+        }
+    }
+
+    /// Whether the job is due at `now`. Runs missed while the daemon was
+    /// busy with other work are skipped rather than run back to back.
+    fn due(&mut self, now: Instant) -> bool {
+        if now < self.next {
+            return false;
+        }
+        while self.next <= now {
+            self.next += self.interval;
+        }
+        true
+    }
+}
+
+/// What the daemon keeps in memory for one line between runs
+struct LineState<'a> {
+    site: &'a Site,
+    line_id: &'a str,
+    search_dir: String,
+    /// `None` when inotify is unavailable; activity then comes from
+    /// comparing two walks
+    watcher: Option<TransferWatcher>,
+    /// Latest directory sizes
+    walk: SizeWalk,
+    activity: TransferActivity,
+    scan: LineScan,
+    since_ts: String,
+    /// A rescan finished since the last history record; history gets one
+    /// record per rescan rather than one per dashboard
+    unrecorded_scan: bool,
+}
+
+impl<'a> LineState<'a> {
+    /// Activity since the previous sample, from inotify or a new walk
+    fn resample(&mut self, options: &DaemonOptions) {
+        if let Some(watcher) = &mut self.watcher {
+            self.activity = watcher.activity(self.line_id, &mut self.walk);
+            return;
+        }
+        let after = SizeWalk::take(&self.search_dir, &options.scan_options.dir_pattern);
+        let sample = TransferSample {
+            before: std::mem::replace(&mut self.walk, after.clone()),
+            after,
+        };
+        self.activity = sample.activity(self.line_id, &self.search_dir);
+    }
+
+    /// What a rescan of this line needs, as of now
+    fn scan_job(&self) -> ScanJob<'a> {
+        ScanJob {
+            site: self.site,
+            line_id: self.line_id,
+            search_dir: self.search_dir.clone(),
+            growing_dirs: self.activity.growing_dirs.clone(),
+            walk: self.watcher.is_some(),
+        }
+    }
+
+    fn apply_scan(&mut self, result: ScanResult) {
+        self.scan = result.scan;
+        self.unrecorded_scan = true;
+        if let Some(walk) = result.walk {
+            self.walk = walk;
+        }
+    }
+
+    fn update_state(&mut self, options: &DaemonOptions) {
+        self.since_ts = update_transfer_state(
+            self.line_id,
+            self.site,
+            self.activity.speed_bps,
+            options.alert_threshold,
+        );
+    }
+
+    fn report(&self, options: &DaemonOptions) -> AuditReport {
+        build_audit_report(
+            self.line_id,
+            self.site,
+            &self.walk,
+            &self.activity,
+            &self.scan,
+            self.since_ts.clone(),
+            options.max_bad_per_archive,
+            options.anomaly_threshold,
+            options.scan_options,
+            options.source_inventory,
+        )
+    }
+}
+
+/// A copy of what a rescan needs from one line, so the scan can run on
+/// its own thread while the daemon keeps sampling
+struct ScanJob<'a> {
+    site: &'a Site,
+    line_id: &'a str,
+    search_dir: String,
+    growing_dirs: HashSet<String>,
+    /// Also take a new size walk (the line has a watcher)
+    walk: bool,
+}
+
+/// A finished rescan of one line
+struct ScanResult {
+    scan: LineScan,
+    walk: Option<SizeWalk>,
+}
+
+impl ScanJob<'_> {
+    /// `None` when `scan_options` was cancelled before the scan finished
+    fn run(&self, options: &DaemonOptions, scan_options: &ScanOptions) -> Option<ScanResult> {
+        // Picks up deletions, which inotify sizes do not follow
+        let walk = self
+            .walk
+            .then(|| SizeWalk::take(&self.search_dir, &scan_options.dir_pattern));
+        let scan = scan_line(
+            self.line_id,
+            &self.site.base_dir,
+            scan_options,
+            &self.growing_dirs,
+            options.source_inventory,
+        );
+        (!scan_options.cancelled()).then_some(ScanResult { scan, walk })
+    }
+}
+
+/// Removes the dashboard lock when the daemon stops, including on panic
+struct LockGuard(&'static str);
+
+impl Drop for LockGuard {
+    fn drop(&mut self) {
+        debug!("Removing lock file");
+        fs::remove_file(self.0).ok();
+    }
+}
+
+/// Keep auditing every line of every site until SIGTERM or SIGINT,
+/// writing the dashboard to `output_file` and/or publishing it to
+/// `options.server`.
+///
+/// The dashboard lock is held for the whole run, so a leftover cron job
+/// exits instead of racing the daemon. Rescans run on a worker thread
+/// and are picked up when they finish, so sampling, alerts and
+/// dashboards keep their schedule. The first dashboard waits for the
+/// first scan. On shutdown a running rescan stops at the next file and
+/// its results are dropped; only a dashboard being written is finished.
+pub fn run(
+    output_file: Option<&str>,
+    sites: &[Site],
+    intervals: Intervals,
+    options: &DaemonOptions,
+    lockfile: &'static str,
+) -> Result<(), String> {
+    let shutdown = Arc::new(AtomicBool::new(false));
+    for signal in [SIGTERM, SIGINT] {
+        signal_hook::flag::register(signal, Arc::clone(&shutdown))
+            .map_err(|e| format!("Cannot install signal handler: {e}"))?;
+    }
+
+    let _lock = acquire_lock(lockfile).map_err(|e| format!("Cannot acquire {lockfile}: {e}"))?;
+    let _guard = LockGuard(lockfile);
+
+    info!(
+        "Daemon started: sampling every {}s, alerts every {}s, dashboard every {}s, scans every {}s",
+        intervals.sample.as_secs(),
+        intervals.alerts.as_secs(),
+        intervals.dashboard.as_secs(),
+        intervals.scan.as_secs()
+    );
+
+    let Some(mut lines) = start_lines(sites, intervals.sample, options, &shutdown) else {
+        info!("Shutdown requested during startup");
+        return Ok(());
+    };
+
+    let now = Instant::now();
+    let mut sample_schedule = Schedule::new(intervals.sample, now + intervals.sample);
+    let mut alert_schedule = Schedule::new(intervals.alerts, now);
+    let mut scan_schedule = Schedule::new(intervals.scan, now);
+    let mut dashboard_schedule = Schedule::new(intervals.dashboard, now);
+    // Rescans check the shutdown flag between files
+    let scan_options = ScanOptions {
+        cancel: Some(Arc::clone(&shutdown)),
+        ..options.scan_options.clone()
+    };
+
+    thread::scope(|s| {
+        let mut running_scan: Option<thread::ScopedJoinHandle<Vec<Option<ScanResult>>>> = None;
+        let mut scanned = false;
+
+        while !shutdown.load(Ordering::Relaxed) {
+            let now = Instant::now();
+            if sample_schedule.due(now) {
+                debug!("Sampling sizes of {} lines", lines.len());
+                for_each_line(&mut lines, |line| line.resample(options));
+            }
+            if alert_schedule.due(now) {
+                for line in &mut lines {
+                    line.update_state(options);
+                }
+            }
+            if let Some(scan) = running_scan.take_if(|scan| scan.is_finished()) {
+                match scan.join() {
+                    Ok(results) => {
+                        // A line whose scan failed keeps its previous results
+                        for (line, result) in lines.iter_mut().zip(results) {
+                            if let Some(result) = result {
+                                line.apply_scan(result);
+                            }
+                        }
+                        debug!("Rescan of {} lines finished", lines.len());
+                        scanned = true;
+                    }
+                    Err(_) => error!("Rescan failed, keeping the previous results"),
+                }
+            }
+            if scan_schedule.due(now) {
+                if running_scan.is_some() {
+                    warn!("Previous rescan still running, skipping this one");
+                } else {
+                    info!("Rescanning archives of {} lines", lines.len());
+                    let jobs: Vec<ScanJob> = lines.iter().map(LineState::scan_job).collect();
+                    let scan_options = &scan_options;
+                    running_scan = Some(s.spawn(move || scan_lines(&jobs, options, scan_options)));
+                }
+            }
+            if scanned && dashboard_schedule.due(now) {
+                write_site_dashboard(output_file, sites, &mut lines, options);
+            }
+            poll_watchers(&mut lines);
+            thread::sleep(TICK);
+        }
+
+        if running_scan.is_some() {
+            info!("Stopping the running rescan");
+        }
+    });
+
+    info!("Shutdown requested, daemon stopped");
+    Ok(())
+}
+
+/// Start watching and walk every line, then sample again after `gap`.
+/// `None` when shutdown was requested before the first sample was
+/// complete.
+fn start_lines<'a>(
+    sites: &'a [Site],
+    gap: Duration,
+    options: &DaemonOptions,
+    shutdown: &AtomicBool,
+) -> Option<Vec<LineState<'a>>> {
+    let pattern = &options.scan_options.dir_pattern;
+    let mut lines: Vec<LineState> = sites
+        .iter()
+        .flat_map(|site| {
+            site.lines
+                .iter()
+                .map(move |line_id| (site, line_id.as_str()))
+        })
+        .map(|(site, line_id)| {
+            let search_dir = format!("{}/Line {}", site.base_dir, line_id);
+            let watcher = TransferWatcher::new(&search_dir, pattern)
+                .map_err(|e| {
+                    warn!(
+                        "Line {}: Cannot watch {} ({}), sampling sizes by walking instead",
+                        line_id, search_dir, e
+                    );
+                })
+                .ok();
+            let walk = SizeWalk::take(&search_dir, pattern);
+            LineState {
+                site,
+                line_id,
+                search_dir,
+                watcher,
+                walk,
+                activity: TransferActivity::default(),
+                scan: LineScan {
+                    files: Vec::new(),
+                    manifest_report: None,
+                },
+                since_ts: String::new(),
+                unrecorded_scan: false,
+            }
+        })
+        .collect();
+
+    let deadline = Instant::now() + gap;
+    while Instant::now() < deadline {
+        if shutdown.load(Ordering::Relaxed) {
+            return None;
+        }
+        poll_watchers(&mut lines);
+        thread::sleep(TICK.min(deadline.saturating_duration_since(Instant::now())));
+    }
+    for_each_line(&mut lines, |line| line.resample(options));
+    Some(lines)
+}
+
+/// Drain the inotify queues so they do not overflow between samples
+fn poll_watchers(lines: &mut [LineState]) {
+    for watcher in lines.iter_mut().filter_map(|line| line.watcher.as_mut()) {
+        watcher.poll();
+    }
+}
+
+/// Rescan every line in parallel, results in `jobs` order. A line whose
+/// scan panicked or was cancelled gets `None`.
+fn scan_lines(
+    jobs: &[ScanJob],
+    options: &DaemonOptions,
+    scan_options: &ScanOptions,
+) -> Vec<Option<ScanResult>> {
+    thread::scope(|s| {
+        let handles: Vec<_> = jobs
+            .iter()
+            .map(|job| s.spawn(move || job.run(options, scan_options)))
+            .collect();
+        jobs.iter()
+            .zip(handles)
+            .map(|(job, h)| {
+                h.join().unwrap_or_else(|_| {
+                    error!(
+                        "Line {}: Rescan failed, keeping the previous results",
+                        job.line_id
+                    );
+                    None
+                })
+            })
+            .collect()
+    })
+}
+
+/// Run `f` on every line in parallel
+fn for_each_line(lines: &mut [LineState], f: impl Fn(&mut LineState) + Sync) {
+    let f = &f;
+    thread::scope(|s| {
+        for line in lines.iter_mut() {
+            s.spawn(move || f(line));
+        }
+    });
+}
+
+fn write_site_dashboard(
+    output_file: Option<&str>,
+    sites: &[Site],
+    lines: &mut [LineState],
+    options: &DaemonOptions,
+) {
+    let mut reports = thread::scope(|s| {
+        let handles: Vec<_> = lines
+            .iter_mut()
+            .map(|line| {
+                s.spawn(move || {
+                    let mut report = line.report(options);
+                    let (base_dir, line_id) = (&line.site.base_dir, line.line_id);
+                    if std::mem::take(&mut line.unrecorded_scan) {
+                        history::record(
+                            base_dir,
+                            line_id,
+                            RunKind::Dashboard,
+                            options.history_retention_days,
+                            &mut report,
+                        );
+                    } else {
+                        report.history = history::load_kind(base_dir, line_id, RunKind::Dashboard);
+                    }
+                    (line.line_id.to_string(), report)
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|h| h.join().unwrap())
+            .collect::<Vec<_>>()
+            .into_iter()
+    });
+
+    // Lines are kept in site order, so each site takes the next few reports
+    let mut site_reports: Vec<Vec<_>> = sites
+        .iter()
+        .map(|site| reports.by_ref().take(site.lines.len()).collect())
+        .collect();
+
+    if let Some(output_file) = output_file {
+        archive_pages::write_archive_pages(output_file, sites, &mut site_reports);
+        if options.write_json {
+            json_report::write_dashboard_json(output_file, sites, &site_reports);
+        }
+    }
+    if let Some(metrics_file) = options.metrics_file {
+        metrics::write_metrics(metrics_file, sites, &site_reports);
+    }
+    let snapshot = options.server.map(|_| Snapshot::new(sites, &site_reports));
+
+    let html = render_site_reports(sites, site_reports);
+    if let Some(output_file) = output_file {
+        match write_dashboard(output_file, &html) {
+            Ok(()) => debug!("Dashboard written to: {}", output_file),
+            Err(e) => error!("Error writing dashboard to {}: {}", output_file, e),
+        }
+    }
+    if let (Some(server), Some(mut snapshot)) = (options.server, snapshot) {
+        snapshot.set_dashboard(html);
+        server.publish(snapshot);
+        debug!("Dashboard published to the HTTP server");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schedule_due_every_interval() {
+        let start = Instant::now();
+        let mut schedule = Schedule::new(Duration::from_secs(60), start);
+
+        assert!(schedule.due(start));
+        assert!(!schedule.due(start + Duration::from_secs(59)));
+        assert!(schedule.due(start + Duration::from_secs(60)));
+        assert!(!schedule.due(start + Duration::from_secs(61)));
+    }
+
+    #[test]
+    fn test_schedule_skips_missed_runs() {
+        let start = Instant::now();
+        let mut schedule = Schedule::new(Duration::from_secs(60), start);
+
+        // A dashboard kept the daemon busy for several intervals
+        assert!(schedule.due(start + Duration::from_secs(200)));
+        assert!(!schedule.due(start + Duration::from_secs(201)));
+        assert!(schedule.due(start + Duration::from_secs(240)));
+    }
+}
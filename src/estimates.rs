@@ -1,3 +1,5 @@
+use crate::inventory::InventoryReport;
+use crate::naming::DirPattern;
 use crate::types::FileEntry;
 use chrono::{Datelike, NaiveDate};
 use colored::Colorize;
@@ -13,12 +15,52 @@ pub struct EstimatesReport {
     pub weekdays_completed: u32,
     pub total_weekdays: u32,
     pub estimated_data_left_tib: f64,
+    /// Bytes still to copy, exact with a source inventory
+    pub remaining_bytes: u64,
     pub free_space_tib: f64,
+    pub free_bytes: u64,
     pub disk_status_ok: bool,
     pub estimated_days_eta: Option<u64>,
     pub estimated_hours_eta: Option<u64>,
     pub start_date: NaiveDate,
     pub end_date: NaiveDate,
+    /// (copied, total) bytes when a source inventory was loaded
+    pub inventory_bytes: Option<(u64, u64)>,
+}
+
+impl EstimatesReport {
+    /// Fraction complete (0.0-1.0): real bytes when a source inventory is
+    /// loaded, completed weekdays otherwise
+    #[must_use]
+    pub fn progress_ratio(&self) -> f64 {
+        let (done, total) = match self.inventory_bytes {
+            Some((copied, total)) => (copied as f64, total as f64),
+            None => (
+                f64::from(self.weekdays_completed),
+                f64::from(self.total_weekdays),
+            ),
+        };
+        if total > 0.0 {
+            (done / total).min(1.0)
+        } else {
+            0.0
+        }
+    }
+
+    /// Percent complete, see `progress_ratio`
+    #[must_use]
+    pub fn progress_pct(&self) -> u8 {
+        (self.progress_ratio() * 100.0) as u8
+    }
+
+    #[must_use]
+    pub fn data_left_label(&self) -> &'static str {
+        if self.inventory_bytes.is_some() {
+            "Data Left:"
+        } else {
+            "Est. Data Left:"
+        }
+    }
 }
 
 #[must_use]
@@ -29,10 +71,12 @@ pub fn calculate_estimates(
     _all_files: &[FileEntry],
     growing_dirs: &HashSet<String>,
     line_id: &str,
+    pattern: &DirPattern,
     _size_t2: u64,
     speed_bps: u64,
     start_date: NaiveDate,
     end_date: NaiveDate,
+    inventory: Option<&InventoryReport>,
 ) -> Option<EstimatesReport> {
     // Determine active copying state and last completed date
     let is_active = speed_bps > 0 && !growing_dirs.is_empty();
@@ -42,13 +86,13 @@ pub fn calculate_estimates(
         growing_dirs
             .iter()
             .next()
-            .and_then(|dir| extract_date_from_dirname(dir, line_id))
+            .and_then(|dir| pattern.parse_date(dir, line_id))
     } else {
         None
     };
 
     // What's the last completed date (from existing folders)
-    let last_completed = get_last_completed_date(files, line_id);
+    let last_completed = get_last_completed_date(files, line_id, pattern);
 
     // For progress calculations, use last_completed or start_date
     let progress_reference_date = last_completed.unwrap_or(start_date);
@@ -125,7 +169,13 @@ pub fn calculate_estimates(
     //     return None; // Transfer complete
     // }
 
-    let total_remaining_bytes = (weekdays_remaining as u64) * median_bytes_per_day;
+    // Exact byte counts from the source inventory replace the median heuristic
+    let total_remaining_bytes = if let Some(inv) = inventory {
+        weekdays_remaining = inv.incomplete_days + inv.missing_days;
+        inv.remaining_bytes()
+    } else {
+        (weekdays_remaining as u64) * median_bytes_per_day
+    };
     let estimated_data_left_tib =
         total_remaining_bytes as f64 / 1_024.0 / 1_024.0 / 1_024.0 / 1_024.0;
 
@@ -133,14 +183,14 @@ pub fn calculate_estimates(
     let free_space_tib = avail_bytes as f64 / 1_024.0 / 1_024.0 / 1_024.0 / 1_024.0;
     let disk_status_ok = avail_bytes > total_remaining_bytes;
 
-    let (estimated_days_eta, estimated_hours_eta) = if speed_bps > 0 {
-        let seconds_left = total_remaining_bytes / speed_bps;
-        let hours_left = seconds_left / 3600;
-        let days_eta = hours_left / 24;
-        (Some(days_eta), Some(hours_left))
-    } else {
-        (None, None)
-    };
+    let (estimated_days_eta, estimated_hours_eta) =
+        if let Some(seconds_left) = total_remaining_bytes.checked_div(speed_bps) {
+            let hours_left = seconds_left / 3600;
+            let days_eta = hours_left / 24;
+            (Some(days_eta), Some(hours_left))
+        } else {
+            (None, None)
+        };
 
     Some(EstimatesReport {
         currently_copying,
@@ -149,12 +199,15 @@ pub fn calculate_estimates(
         weekdays_completed,
         total_weekdays,
         estimated_data_left_tib,
+        remaining_bytes: total_remaining_bytes,
         free_space_tib,
+        free_bytes: avail_bytes,
         disk_status_ok,
         estimated_days_eta,
         estimated_hours_eta,
         start_date,
         end_date,
+        inventory_bytes: inventory.map(|inv| (inv.copied_bytes, inv.total_bytes)),
     })
 }
 
@@ -162,12 +215,7 @@ pub fn print_estimates(report: &Option<EstimatesReport>) {
     println!("\n{}", "=== Transfer Estimates ===".cyan());
 
     if let Some(r) = report {
-        // Calculate progress based on completed weekdays vs total weekdays
-        let progress_pct = if r.total_weekdays > 0 {
-            ((r.weekdays_completed as f64 / r.total_weekdays as f64 * 100.0).min(100.0)) as u8
-        } else {
-            0
-        };
+        let progress_pct = r.progress_pct();
 
         // Render progress bar (20 blocks total)
         let filled_blocks = (progress_pct as usize * 20) / 100;
@@ -211,8 +259,10 @@ pub fn print_estimates(report: &Option<EstimatesReport>) {
             r.weekdays_remaining, date_range
         );
         println!(
-            "Est. Data Left:    {:.1} TiB (Free: {:.1} TiB)",
-            r.estimated_data_left_tib, r.free_space_tib
+            "{:<18} {:.1} TiB (Free: {:.1} TiB)",
+            r.data_left_label(),
+            r.estimated_data_left_tib,
+            r.free_space_tib
         );
 
         if r.disk_status_ok {
@@ -278,40 +328,22 @@ fn extract_date_from_ps1(line: &str) -> Option<NaiveDate> {
     }
 }
 
-fn get_folder_dates(files: &[FileEntry], line_id: &str) -> Vec<NaiveDate> {
-    let prefix = format!("Archive_Beam_{line_id}_");
+fn get_folder_dates(files: &[FileEntry], line_id: &str, pattern: &DirPattern) -> Vec<NaiveDate> {
     let mut dates: Vec<NaiveDate> = files
         .iter()
-        .filter_map(|f| {
-            if f.parent_dir.starts_with(&prefix) {
-                let date_str = &f.parent_dir[prefix.len()..];
-                if date_str.len() >= 10 {
-                    NaiveDate::parse_from_str(&date_str[0..10], "%Y-%m-%d").ok()
-                } else {
-                    None
-                }
-            } else {
-                None
-            }
-        })
+        .filter_map(|f| pattern.parse_date(&f.parent_dir, line_id))
         .collect();
     dates.sort();
     dates.dedup();
     dates
 }
 
-fn extract_date_from_dirname(dirname: &str, line_id: &str) -> Option<NaiveDate> {
-    let prefix = format!("Archive_Beam_{}_", line_id);
-    if let Some(date_str) = dirname.strip_prefix(&prefix)
-        && date_str.len() >= 10
-    {
-        return NaiveDate::parse_from_str(&date_str[0..10], "%Y-%m-%d").ok();
-    }
-    None
-}
-
-fn get_last_completed_date(files: &[FileEntry], line_id: &str) -> Option<NaiveDate> {
-    let dates = get_folder_dates(files, line_id);
+fn get_last_completed_date(
+    files: &[FileEntry],
+    line_id: &str,
+    pattern: &DirPattern,
+) -> Option<NaiveDate> {
+    let dates = get_folder_dates(files, line_id, pattern);
     dates.last().copied()
 }
 
@@ -342,6 +374,7 @@ fn get_available_bytes(path: &str) -> u64 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::ArchiveFormat;
     use std::io::Write;
 
     #[test]
@@ -377,42 +410,13 @@ mod tests {
         fs::remove_file(file_path).unwrap();
     }
 
-    #[test]
-    fn test_extract_date_from_dirname() {
-        // Test valid date extraction
-        assert_eq!(
-            extract_date_from_dirname("Archive_Beam_B_2024-08-01", "B"),
-            Some(NaiveDate::parse_from_str("2024-08-01", "%Y-%m-%d").unwrap())
-        );
-
-        assert_eq!(
-            extract_date_from_dirname("Archive_Beam_A_2024-12-25", "A"),
-            Some(NaiveDate::parse_from_str("2024-12-25", "%Y-%m-%d").unwrap())
-        );
-
-        // Test invalid formats
-        assert_eq!(
-            extract_date_from_dirname("Archive_Beam_B_invalid", "B"),
-            None
-        );
-        assert_eq!(
-            extract_date_from_dirname("SomeOtherDir_2024-08-01", "B"),
-            None
-        );
-
-        // Test wrong line ID
-        assert_eq!(
-            extract_date_from_dirname("Archive_Beam_A_2024-08-01", "B"),
-            None
-        );
-    }
-
     #[test]
     fn test_get_last_completed_date() {
         let files = vec![
             FileEntry {
                 name: "file1.zip".to_string(),
                 size: 100,
+                format: ArchiveFormat::Zip,
                 is_valid: true,
                 invalid_reason: None,
                 modified: chrono::Local::now(),
@@ -421,6 +425,7 @@ mod tests {
             FileEntry {
                 name: "file2.zip".to_string(),
                 size: 100,
+                format: ArchiveFormat::Zip,
                 is_valid: true,
                 invalid_reason: None,
                 modified: chrono::Local::now(),
@@ -429,6 +434,7 @@ mod tests {
             FileEntry {
                 name: "file3.zip".to_string(),
                 size: 100,
+                format: ArchiveFormat::Zip,
                 is_valid: true,
                 invalid_reason: None,
                 modified: chrono::Local::now(),
@@ -437,7 +443,7 @@ mod tests {
         ];
 
         // Should return the latest date from all directories
-        let result = get_last_completed_date(&files, "B");
+        let result = get_last_completed_date(&files, "B", &DirPattern::default());
         assert_eq!(
             result,
             Some(NaiveDate::parse_from_str("2024-08-05", "%Y-%m-%d").unwrap())
@@ -454,4 +460,38 @@ mod tests {
 
         fs::remove_file(file_path).unwrap();
     }
+
+    #[test]
+    fn test_inventory_bytes_replace_median_heuristic() {
+        let inventory = InventoryReport {
+            days: Vec::new(),
+            total_bytes: 4 * 1024_u64.pow(4),
+            copied_bytes: 1024_u64.pow(4),
+            complete_days: 10,
+            incomplete_days: 1,
+            missing_days: 2,
+        };
+        let start = NaiveDate::from_ymd_opt(2024, 7, 29).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 12, 31).unwrap();
+
+        let report = calculate_estimates(
+            ".",
+            &[],
+            &[],
+            &HashSet::new(),
+            "B",
+            &DirPattern::default(),
+            0,
+            0,
+            start,
+            end,
+            Some(&inventory),
+        )
+        .unwrap();
+
+        assert_eq!(report.progress_pct(), 25);
+        assert_eq!(report.weekdays_remaining, 3);
+        assert!((report.estimated_data_left_tib - 3.0).abs() < 1e-9);
+        assert_eq!(report.data_left_label(), "Data Left:");
+    }
 }
@@ -1,7 +1,8 @@
+use crate::naming::DirPattern;
 use crate::types::FileEntry;
 use chrono::{Datelike, NaiveDate};
 use colored::Colorize;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 
 #[derive(Debug, PartialEq)]
 pub struct GapReport {
@@ -10,10 +11,23 @@ pub struct GapReport {
     pub missing_weekdays: Vec<NaiveDate>,
     pub skipped_weekends: u32,
     pub is_empty: bool,
+    /// Archive folder found for each date
+    pub folders: BTreeMap<NaiveDate, DayFolder>,
 }
 
-pub fn analyze_gaps(files: &[FileEntry], line_id: &str) {
-    let report = find_gaps(files, line_id);
+#[derive(Debug, Clone, PartialEq)]
+pub struct DayFolder {
+    pub name: String,
+    pub files: usize,
+}
+
+pub fn analyze_gaps(
+    files: &[FileEntry],
+    line_id: &str,
+    pattern: &DirPattern,
+    holidays: &[NaiveDate],
+) {
+    let report = find_gaps(files, line_id, pattern, holidays);
 
     if report.is_empty {
         println!("No dated folders found for gap analysis.");
@@ -43,27 +57,28 @@ pub fn analyze_gaps(files: &[FileEntry], line_id: &str) {
     }
 }
 
+/// Weekdays between the first and last archive without an archive folder.
+/// Dates in `holidays` (see `calendar::load_holidays`) are not missing.
 #[must_use]
-pub fn find_gaps(files: &[FileEntry], line_id: &str) -> GapReport {
-    let prefix = format!("Archive_Beam_{line_id}_");
-    let mut dates: Vec<NaiveDate> = files
-        .iter()
-        .filter_map(|f| {
-            if f.parent_dir.starts_with(&prefix) {
-                let date_str = &f.parent_dir[prefix.len()..];
-                if date_str.len() >= 10 {
-                    NaiveDate::parse_from_str(&date_str[0..10], "%Y-%m-%d").ok()
-                } else {
-                    None
-                }
-            } else {
-                None
-            }
-        })
-        .collect();
-
-    dates.sort();
-    dates.dedup();
+pub fn find_gaps(
+    files: &[FileEntry],
+    line_id: &str,
+    pattern: &DirPattern,
+    holidays: &[NaiveDate],
+) -> GapReport {
+    let mut folders: BTreeMap<NaiveDate, DayFolder> = BTreeMap::new();
+    for file in files {
+        if let Some(date) = pattern.parse_date(&file.parent_dir, line_id) {
+            folders
+                .entry(date)
+                .or_insert_with(|| DayFolder {
+                    name: file.parent_dir.clone(),
+                    files: 0,
+                })
+                .files += 1;
+        }
+    }
+    let dates: Vec<NaiveDate> = folders.keys().copied().collect();
 
     if dates.is_empty() {
         return GapReport {
@@ -72,12 +87,14 @@ pub fn find_gaps(files: &[FileEntry], line_id: &str) -> GapReport {
             missing_weekdays: vec![],
             skipped_weekends: 0,
             is_empty: true,
+            folders,
         };
     }
 
     let start = *dates.first().unwrap();
     let end = *dates.last().unwrap();
     let existing_set: HashSet<NaiveDate> = dates.iter().copied().collect();
+    let holidays: HashSet<&NaiveDate> = holidays.iter().collect();
 
     let mut missing_weekdays = Vec::new();
     let mut skipped_weekends = 0;
@@ -86,10 +103,10 @@ pub fn find_gaps(files: &[FileEntry], line_id: &str) -> GapReport {
     while curr < end {
         if !existing_set.contains(&curr) {
             let dow = curr.weekday().number_from_monday();
-            if dow <= 5 {
-                missing_weekdays.push(curr);
-            } else {
+            if dow > 5 {
                 skipped_weekends += 1;
+            } else if !holidays.contains(&curr) {
+                missing_weekdays.push(curr);
             }
         }
         match curr.succ_opt() {
@@ -104,12 +121,14 @@ pub fn find_gaps(files: &[FileEntry], line_id: &str) -> GapReport {
         missing_weekdays,
         skipped_weekends,
         is_empty: false,
+        folders,
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::ArchiveFormat;
     use chrono::{Local, Utc};
 
     // Helper to create dummy entry
@@ -117,6 +136,7 @@ mod tests {
         FileEntry {
             name: "dummy.zip".to_string(),
             size: 0,
+            format: ArchiveFormat::Zip,
             is_valid: true,
             invalid_reason: None,
             modified: Utc::now().with_timezone(&Local),
@@ -133,7 +153,7 @@ mod tests {
             make_entry("2024-07-31", "B"),
         ];
 
-        let report = find_gaps(&files, "B");
+        let report = find_gaps(&files, "B", &DirPattern::default(), &[]);
         assert_eq!(report.missing_weekdays.len(), 0);
         assert_eq!(report.skipped_weekends, 0);
         assert_eq!(
@@ -154,7 +174,7 @@ mod tests {
             make_entry("2024-07-31", "B"), // Wed
         ];
 
-        let report = find_gaps(&files, "B");
+        let report = find_gaps(&files, "B", &DirPattern::default(), &[]);
         assert_eq!(report.missing_weekdays.len(), 1);
         assert_eq!(
             report.missing_weekdays[0],
@@ -170,8 +190,21 @@ mod tests {
             make_entry("2024-08-05", "B"), // Mon
         ];
 
-        let report = find_gaps(&files, "B");
+        let report = find_gaps(&files, "B", &DirPattern::default(), &[]);
         assert_eq!(report.missing_weekdays.len(), 0);
         assert_eq!(report.skipped_weekends, 2); // Sat, Sun
     }
+
+    #[test]
+    fn test_find_gaps_skip_holiday() {
+        // Mon 29th, Thu 1st; Tue 30th is a holiday, Wed 31st is missing
+        let files = vec![make_entry("2024-07-29", "B"), make_entry("2024-08-01", "B")];
+        let holiday = NaiveDate::from_ymd_opt(2024, 7, 30).unwrap();
+
+        let report = find_gaps(&files, "B", &DirPattern::default(), &[holiday]);
+        assert_eq!(
+            report.missing_weekdays,
+            vec![NaiveDate::from_ymd_opt(2024, 7, 31).unwrap()]
+        );
+    }
 }
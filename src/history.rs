@@ -0,0 +1,506 @@
+use crate::estimates::EstimatesReport;
+use crate::gap_analysis::GapReport;
+use crate::html_renderer::AuditReport;
+use crate::stats::BadFilesReport;
+use crate::transfer::RunKind;
+use chrono::{Duration, Local, NaiveDateTime};
+use colored::Colorize;
+use comfy_table::{Cell, Color, Table};
+use log::{debug, error};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+const HEADER: &str =
+    "timestamp,total_bytes,speed_bps,free_bytes,progress_pct,bad_files,missing_days,kind";
+/// How far past the retention period the oldest record may get before the
+/// file is rewritten, so a rewrite happens about once a day and not every run
+const PRUNE_SLACK_DAYS: i64 = 1;
+
+/// One audit run of one line
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryRecord {
+    /// Local time of the run
+    pub timestamp: NaiveDateTime,
+    pub total_bytes: u64,
+    pub speed_bps: u64,
+    pub free_bytes: u64,
+    pub progress_pct: u8,
+    pub bad_files: usize,
+    pub missing_days: usize,
+    /// Single-line and dashboard runs measure speed over their own interval
+    /// (see `RunKind`), so their records are kept apart
+    pub kind: RunKind,
+}
+
+impl HistoryRecord {
+    #[must_use]
+    pub fn new(
+        kind: RunKind,
+        timestamp: NaiveDateTime,
+        total_bytes: u64,
+        speed_bps: u64,
+        estimates: Option<&EstimatesReport>,
+        bad_files: Option<&BadFilesReport>,
+        gaps: Option<&GapReport>,
+    ) -> Self {
+        Self {
+            timestamp,
+            total_bytes,
+            speed_bps,
+            free_bytes: estimates.map_or(0, |e| e.free_bytes),
+            progress_pct: estimates.map_or(0, EstimatesReport::progress_pct),
+            bad_files: bad_files.map_or(0, |b| b.total_count),
+            missing_days: gaps.map_or(0, |g| g.missing_weekdays.len()),
+            kind,
+        }
+    }
+
+    #[must_use]
+    pub fn from_report(kind: RunKind, timestamp: NaiveDateTime, report: &AuditReport) -> Self {
+        Self::new(
+            kind,
+            timestamp,
+            report.total_size,
+            report.speed_bps,
+            report.estimates_report.as_ref(),
+            report.bad_files_report.as_ref(),
+            report.gap_report.as_ref(),
+        )
+    }
+
+    fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{}",
+            self.timestamp.format(TIMESTAMP_FORMAT),
+            self.total_bytes,
+            self.speed_bps,
+            self.free_bytes,
+            self.progress_pct,
+            self.bad_files,
+            self.missing_days,
+            self.kind.as_str()
+        )
+    }
+
+    fn parse(line: &str) -> Option<Self> {
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let [timestamp, total, speed, free, progress, bad, missing, kind] = fields.as_slice()
+        else {
+            return None;
+        };
+        Some(Self {
+            timestamp: NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT).ok()?,
+            total_bytes: total.parse().ok()?,
+            speed_bps: speed.parse().ok()?,
+            free_bytes: free.parse().ok()?,
+            progress_pct: progress.parse().ok()?,
+            bad_files: bad.parse().ok()?,
+            missing_days: missing.parse().ok()?,
+            kind: RunKind::parse(kind)?,
+        })
+    }
+}
+
+#[must_use]
+pub fn history_file_path(base_dir: &str, line_id: &str) -> String {
+    format!("{}/.history_{}", base_dir, line_id)
+}
+
+/// Append one run to `<base_dir>/.history_<line>`, a CSV with a header line.
+/// With a `retention_days` other than 0, runs more than that many days older
+/// than `record` are pruned (`--history-retention-days`).
+pub fn append(base_dir: &str, line_id: &str, record: &HistoryRecord, retention_days: u32) {
+    let history_file = history_file_path(base_dir, line_id);
+    let is_new = fs::metadata(&history_file).map_or(true, |m| m.len() == 0);
+    let mut entry = String::new();
+    if is_new {
+        entry.push_str(HEADER);
+        entry.push('\n');
+    }
+    entry.push_str(&record.to_csv());
+    entry.push('\n');
+
+    debug!("Line {}: Appending to history: {}", line_id, history_file);
+    if let Ok(mut file) = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&history_file)
+    {
+        let _ = file.write_all(entry.as_bytes());
+    } else {
+        error!(
+            "Line {}: Failed to write to history {}",
+            line_id, history_file
+        );
+    }
+    if retention_days > 0 {
+        prune(&history_file, line_id, record.timestamp, retention_days);
+    }
+}
+
+/// Rewrite the history without the records from before `now - retention_days`
+/// once the oldest one is `PRUNE_SLACK_DAYS` past that. Only the first
+/// record is read otherwise. Written atomically (temp file + rename).
+fn prune(history_file: &str, line_id: &str, now: NaiveDateTime, retention_days: u32) {
+    let Some(cutoff) = now.checked_sub_signed(Duration::days(i64::from(retention_days))) else {
+        return;
+    };
+    let Ok(file) = File::open(history_file) else {
+        return;
+    };
+    let oldest = BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter(|l| !l.starts_with("timestamp"))
+        .find_map(|l| HistoryRecord::parse(&l));
+    if oldest.is_none_or(|r| r.timestamp >= cutoff - Duration::days(PRUNE_SLACK_DAYS)) {
+        return;
+    }
+
+    let Ok(content) = fs::read_to_string(history_file) else {
+        return;
+    };
+    let mut kept = format!("{HEADER}\n");
+    for line in content.lines() {
+        if HistoryRecord::parse(line).is_some_and(|r| r.timestamp >= cutoff) {
+            kept.push_str(line);
+            kept.push('\n');
+        }
+    }
+
+    let tmp_file = format!("{}.tmp", history_file);
+    if let Err(e) = fs::write(&tmp_file, kept).and_then(|()| fs::rename(&tmp_file, history_file)) {
+        error!(
+            "Line {}: Failed to prune history {}: {}",
+            line_id, history_file, e
+        );
+        fs::remove_file(&tmp_file).ok();
+    } else {
+        debug!(
+            "Line {}: Pruned history records older than {} days",
+            line_id, retention_days
+        );
+    }
+}
+
+/// Append the run behind `report` and load the history of runs of the
+/// same `kind` into it for the charts
+pub fn record(
+    base_dir: &str,
+    line_id: &str,
+    kind: RunKind,
+    retention_days: u32,
+    report: &mut AuditReport,
+) {
+    append(
+        base_dir,
+        line_id,
+        &HistoryRecord::from_report(kind, Local::now().naive_local(), report),
+        retention_days,
+    );
+    report.history = load_kind(base_dir, line_id, kind);
+}
+
+/// Every record of one line, oldest first; unreadable lines are skipped
+#[must_use]
+pub fn load(base_dir: &str, line_id: &str) -> Vec<HistoryRecord> {
+    let history_file = history_file_path(base_dir, line_id);
+    let Ok(content) = fs::read_to_string(&history_file) else {
+        debug!("Line {}: No history at {}", line_id, history_file);
+        return Vec::new();
+    };
+    let mut records: Vec<HistoryRecord> = content
+        .lines()
+        .filter(|l| !l.starts_with("timestamp"))
+        .filter_map(HistoryRecord::parse)
+        .collect();
+    records.sort_by_key(|r| r.timestamp);
+    records
+}
+
+/// The records of one line written by runs of `kind`, oldest first
+#[must_use]
+pub fn load_kind(base_dir: &str, line_id: &str, kind: RunKind) -> Vec<HistoryRecord> {
+    let mut records = load(base_dir, line_id);
+    records.retain(|r| r.kind == kind);
+    records
+}
+
+/// Records at or after `now - window`, or all of them when the window
+/// reaches back past the earliest representable time
+#[must_use]
+pub fn within(records: &[HistoryRecord], now: NaiveDateTime, window: Duration) -> &[HistoryRecord] {
+    let Some(cutoff) = now.checked_sub_signed(window) else {
+        return records;
+    };
+    let start = records.partition_point(|r| r.timestamp < cutoff);
+    &records[start..]
+}
+
+/// Parse a window like `90m`, `24h`, `7d` or `2w`
+pub fn parse_window(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let split = value.char_indices().last().map_or(0, |(i, _)| i);
+    let (amount, unit) = value.split_at(split);
+    let amount: i64 = amount
+        .parse()
+        .ok()
+        .filter(|a| *a > 0)
+        .ok_or_else(|| format!("invalid window '{value}' (e.g. 90m, 24h, 7d, 2w)"))?;
+    let window = match unit {
+        "m" => Duration::try_minutes(amount),
+        "h" => Duration::try_hours(amount),
+        "d" => Duration::try_days(amount),
+        "w" => Duration::try_weeks(amount),
+        _ => None,
+    };
+    window.ok_or_else(|| format!("invalid window '{value}' (e.g. 90m, 24h, 7d, 2w)"))
+}
+
+#[derive(Debug, PartialEq)]
+pub struct HistorySummary {
+    pub runs: usize,
+    pub first: NaiveDateTime,
+    pub last: NaiveDateTime,
+    pub bytes_added: u64,
+    pub average_speed_bps: u64,
+    pub peak_speed_bps: u64,
+    /// Share of runs that saw a transfer, in percent
+    pub active_pct: f64,
+    pub free_bytes: (u64, u64),
+    pub progress_pct: (u8, u8),
+    pub bad_files: (usize, usize),
+    pub missing_days: (usize, usize),
+}
+
+/// Summary of a window of records (first and last values are shown as pairs)
+#[must_use]
+pub fn summarize(records: &[HistoryRecord]) -> Option<HistorySummary> {
+    let (first, last) = (records.first()?, records.last()?);
+    let active = records.iter().filter(|r| r.speed_bps > 0).count();
+    Some(HistorySummary {
+        runs: records.len(),
+        first: first.timestamp,
+        last: last.timestamp,
+        bytes_added: last.total_bytes.saturating_sub(first.total_bytes),
+        average_speed_bps: records.iter().map(|r| r.speed_bps).sum::<u64>() / records.len() as u64,
+        peak_speed_bps: records.iter().map(|r| r.speed_bps).max().unwrap_or(0),
+        active_pct: active as f64 * 100.0 / records.len() as f64,
+        free_bytes: (first.free_bytes, last.free_bytes),
+        progress_pct: (first.progress_pct, last.progress_pct),
+        bad_files: (first.bad_files, last.bad_files),
+        missing_days: (first.missing_days, last.missing_days),
+    })
+}
+
+fn mib_per_sec(speed_bps: u64) -> String {
+    format!("{:.1} MiB/s", speed_bps as f64 / 1_024.0 / 1_024.0)
+}
+
+/// Print single-line and dashboard runs as separate tables and summaries,
+/// since their speeds are averaged over different intervals
+pub fn print_history_by_kind(records: &[HistoryRecord]) {
+    let kinds: Vec<RunKind> = [RunKind::Dashboard, RunKind::Line]
+        .into_iter()
+        .filter(|kind| records.iter().any(|r| r.kind == *kind))
+        .collect();
+    if kinds.len() < 2 {
+        print_history(records);
+        return;
+    }
+    for kind in kinds {
+        let of_kind: Vec<HistoryRecord> =
+            records.iter().filter(|r| r.kind == kind).cloned().collect();
+        println!("\n{}", format!("--- {} runs ---", kind.as_str()).cyan());
+        print_history(&of_kind);
+    }
+}
+
+pub fn print_history(records: &[HistoryRecord]) {
+    let Some(summary) = summarize(records) else {
+        println!("No history recorded in this window.");
+        return;
+    };
+
+    let mut table = Table::new();
+    table.load_preset(comfy_table::presets::UTF8_HORIZONTAL_ONLY);
+    table.set_header(vec![
+        "Time", "Total", "Speed", "Free", "Progress", "Bad", "Missing",
+    ]);
+    for r in records {
+        table.add_row(vec![
+            Cell::new(r.timestamp.format("%Y-%m-%d %H:%M")),
+            Cell::new(human_bytes::human_bytes(r.total_bytes as f64)),
+            Cell::new(mib_per_sec(r.speed_bps)).fg(if r.speed_bps > 0 {
+                Color::Green
+            } else {
+                Color::Yellow
+            }),
+            Cell::new(human_bytes::human_bytes(r.free_bytes as f64)),
+            Cell::new(format!("{}%", r.progress_pct)),
+            Cell::new(r.bad_files).fg(if r.bad_files > 0 {
+                Color::Red
+            } else {
+                Color::White
+            }),
+            Cell::new(r.missing_days).fg(if r.missing_days > 0 {
+                Color::Yellow
+            } else {
+                Color::White
+            }),
+        ]);
+    }
+    println!("{table}");
+
+    println!(
+        "\nRuns:          {} ({} to {})",
+        summary.runs,
+        summary.first.format("%Y-%m-%d %H:%M"),
+        summary.last.format("%Y-%m-%d %H:%M")
+    );
+    println!(
+        "Data Added:    {}",
+        human_bytes::human_bytes(summary.bytes_added as f64).green()
+    );
+    println!(
+        "Speed:         {} average, {} peak, active in {:.0}% of runs",
+        mib_per_sec(summary.average_speed_bps),
+        mib_per_sec(summary.peak_speed_bps),
+        summary.active_pct
+    );
+    println!(
+        "Free Space:    {} -> {}",
+        human_bytes::human_bytes(summary.free_bytes.0 as f64),
+        human_bytes::human_bytes(summary.free_bytes.1 as f64)
+    );
+    println!(
+        "Progress:      {}% -> {}%",
+        summary.progress_pct.0, summary.progress_pct.1
+    );
+    let change = |(from, to): (usize, usize)| {
+        let text = format!("{from} -> {to}");
+        if to > from { text.red() } else { text.normal() }
+    };
+    println!("Bad Files:     {}", change(summary.bad_files));
+    println!("Missing Days:  {}", change(summary.missing_days));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(time: &str, total: u64, speed: u64, bad: usize) -> HistoryRecord {
+        HistoryRecord {
+            timestamp: NaiveDateTime::parse_from_str(time, TIMESTAMP_FORMAT).unwrap(),
+            total_bytes: total,
+            speed_bps: speed,
+            free_bytes: 5_000_000,
+            progress_pct: 40,
+            bad_files: bad,
+            missing_days: 3,
+            kind: RunKind::Line,
+        }
+    }
+
+    #[test]
+    fn test_append_and_load_history() {
+        let temp = tempfile::tempdir().unwrap();
+        let base_dir = temp.path().to_str().unwrap();
+        let first = record("2025-03-14 10:00:00", 1000, 0, 0);
+        let second = record("2025-03-14 10:05:00", 4000, 10, 2);
+
+        append(base_dir, "B", &first, 0);
+        append(base_dir, "B", &second, 0);
+
+        let content = fs::read_to_string(history_file_path(base_dir, "B")).unwrap();
+        assert_eq!(
+            content,
+            format!(
+                "{HEADER}\n\
+                 2025-03-14 10:00:00,1000,0,5000000,40,0,3,line\n\
+                 2025-03-14 10:05:00,4000,10,5000000,40,2,3,line\n"
+            )
+        );
+        assert_eq!(load(base_dir, "B"), vec![first, second]);
+        assert!(load(base_dir, "A").is_empty());
+    }
+
+    #[test]
+    fn test_append_prunes_expired_records() {
+        let temp = tempfile::tempdir().unwrap();
+        let base_dir = temp.path().to_str().unwrap();
+        let expired = record("2024-12-01 10:00:00", 500, 0, 0);
+        let kept = record("2025-01-10 10:00:00", 1000, 0, 0);
+        let latest = record("2025-03-14 10:00:00", 4000, 10, 2);
+
+        append(base_dir, "B", &expired, 90);
+        append(base_dir, "B", &kept, 90);
+        assert_eq!(load(base_dir, "B"), vec![expired.clone(), kept.clone()]);
+
+        append(base_dir, "B", &latest, 90);
+        let content = fs::read_to_string(history_file_path(base_dir, "B")).unwrap();
+        assert!(content.starts_with(HEADER));
+        assert_eq!(load(base_dir, "B"), vec![kept.clone(), latest.clone()]);
+
+        // 0 keeps everything
+        append(base_dir, "A", &expired, 0);
+        append(base_dir, "A", &latest, 0);
+        assert_eq!(load(base_dir, "A"), vec![expired, latest]);
+    }
+
+    #[test]
+    fn test_load_kind_keeps_runs_apart() {
+        let temp = tempfile::tempdir().unwrap();
+        let base_dir = temp.path().to_str().unwrap();
+        let line = record("2025-03-14 10:00:00", 1000, 0, 0);
+        let dashboard = HistoryRecord {
+            kind: RunKind::Dashboard,
+            ..record("2025-03-14 10:01:00", 1000, 50, 0)
+        };
+        append(base_dir, "B", &line, 0);
+        append(base_dir, "B", &dashboard, 0);
+
+        assert_eq!(load_kind(base_dir, "B", RunKind::Line), vec![line]);
+        assert_eq!(
+            load_kind(base_dir, "B", RunKind::Dashboard),
+            vec![dashboard]
+        );
+    }
+
+    #[test]
+    fn test_window_and_summary() {
+        let records = vec![
+            record("2025-03-13 09:00:00", 0, 0, 0),
+            record("2025-03-14 10:00:00", 1000, 0, 0),
+            record("2025-03-14 10:05:00", 4000, 30, 1),
+            record("2025-03-14 10:10:00", 9000, 60, 2),
+        ];
+        let now = NaiveDateTime::parse_from_str("2025-03-14 12:00:00", TIMESTAMP_FORMAT).unwrap();
+        let window = within(&records, now, parse_window("24h").unwrap());
+        assert_eq!(window.len(), 3);
+        let everything = within(&records, now, parse_window("300000000d").unwrap());
+        assert_eq!(everything.len(), 4);
+
+        let summary = summarize(window).unwrap();
+        assert_eq!(summary.runs, 3);
+        assert_eq!(summary.bytes_added, 8000);
+        assert_eq!(summary.average_speed_bps, 30);
+        assert_eq!(summary.peak_speed_bps, 60);
+        assert!((summary.active_pct - 66.7).abs() < 0.1);
+        assert_eq!(summary.bad_files, (0, 2));
+        assert!(summarize(&[]).is_none());
+    }
+
+    #[test]
+    fn test_parse_window() {
+        assert_eq!(parse_window("90m"), Ok(Duration::minutes(90)));
+        assert_eq!(parse_window("7d"), Ok(Duration::days(7)));
+        assert_eq!(parse_window("2w"), Ok(Duration::weeks(2)));
+        assert!(parse_window("7").is_err());
+        assert!(parse_window("0h").is_err());
+        assert!(parse_window("d").is_err());
+        assert!(parse_window("").is_err());
+        assert!(parse_window("99999999999999d").is_err());
+    }
+}
@@ -1,25 +1,106 @@
+use crate::archive_pages;
+use crate::calendar::{self, DayState};
+use crate::charts;
 use crate::estimates::EstimatesReport;
 use crate::gap_analysis::GapReport;
+use crate::history::HistoryRecord;
+use crate::inventory::{DayStatus, InventoryReport};
+use crate::manifest::{ManifestIssueKind, ManifestReport};
+use crate::members::{CanonicalSource, MemberSetReport};
+use crate::ranking::{self, CombinedRankingReport, MonthlyMetrics, MonthlyRankingReport};
 use crate::stats::{AnomalyReport, BadFilesReport, IntegrityStats};
-use chrono::Local;
+use crate::types::{FileEntry, FileWrite};
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveDateTime};
 
 pub struct AuditReport {
     pub total_size: u64,
     pub total_files: usize,
     pub speed_bps: u64,
     pub since_timestamp: String,
-    pub recent_files: Vec<String>,
+    pub recent_files: Vec<FileWrite>,
+    /// `recent_files` are being copied right now rather than written in the last 5 minutes
+    pub recent_files_live: bool,
     pub redundancy_check: Option<String>,
     pub integrity_stats: Option<IntegrityStats>,
     pub gap_report: Option<GapReport>,
     pub estimates_report: Option<EstimatesReport>,
     pub anomaly_report: Option<AnomalyReport>,
     pub bad_files_report: Option<BadFilesReport>,
+    pub manifest_report: Option<ManifestReport>,
+    pub inventory_report: Option<InventoryReport>,
+    pub member_report: Option<MemberSetReport>,
     pub max_bad_per_archive: usize,
+    /// Recorded runs of this line, oldest first, for the trend charts
+    pub history: Vec<HistoryRecord>,
+    /// Files of the completed archive folders, for the per-folder pages
+    pub files: Vec<FileEntry>,
+    pub anomaly_threshold: f64,
+    /// Directory of this line's archive pages, relative to the dashboard,
+    /// once they have been written
+    pub archive_pages: Option<String>,
+    pub monthly_ranking: Option<MonthlyRankingReport>,
+    /// Dates listed in the site's `.holidays`, for the calendar
+    pub holidays: Vec<NaiveDate>,
 }
 
+/// Render one column per line, in the given order
 #[must_use]
-pub fn render_dashboard(line_a_report: &AuditReport, line_b_report: &AuditReport) -> String {
+pub fn render_dashboard(line_reports: &[(String, AuditReport)]) -> String {
+    render_page(&render_line_columns(line_reports), "")
+}
+
+/// Render a section per site, each with one column per line
+#[must_use]
+pub fn render_sites_dashboard(site_reports: &[(String, Vec<(String, AuditReport)>)]) -> String {
+    let mut content = String::new();
+    for (site, line_reports) in site_reports {
+        content.push_str(&format!(
+            "  <h1 class=\"site-title\">{}</h1>\n",
+            escape_html(site)
+        ));
+        content.push_str(&render_line_columns(line_reports));
+    }
+    render_page(&content, r#" class="multi-site""#)
+}
+
+fn render_line_columns(line_reports: &[(String, AuditReport)]) -> String {
+    let mut html = String::new();
+    html.push_str("  <div class=\"container\">\n");
+
+    for (line_id, report) in line_reports {
+        html.push_str("    <div class=\"column\">\n");
+        html.push_str(&format!("      <h2>Line {}</h2>\n", escape_html(line_id)));
+        html.push_str("      <div class=\"column-content\">\n");
+        html.push_str(&render_full_report(report));
+        html.push_str("      </div>\n");
+        html.push_str("    </div>\n");
+    }
+
+    html.push_str("  </div>\n");
+
+    let rankings: Vec<MonthlyRankingReport> = line_reports
+        .iter()
+        .filter_map(|(_, report)| report.monthly_ranking.clone())
+        .collect();
+    if rankings.len() > 1 {
+        let combined = ranking::combine_rankings(&rankings);
+        html.push_str(r#"  <details class="combined-ranking"><summary>Combined Ranking</summary>"#);
+        html.push_str(&render_combined_ranking(&combined));
+        html.push_str("  </details>\n");
+    }
+    html
+}
+
+/// A page of the static site around the dashboard (archive index and folders)
+#[must_use]
+pub fn render_static_page(content: &str) -> String {
+    render_page(
+        &format!("  <div class=\"page-content\">\n{content}  </div>\n"),
+        r#" class="static-page""#,
+    )
+}
+
+fn render_page(content: &str, body_attributes: &str) -> String {
     let mut html = String::new();
 
     html.push_str("<!DOCTYPE html>\n<html>\n<head>\n");
@@ -28,32 +109,14 @@ pub fn render_dashboard(line_a_report: &AuditReport, line_b_report: &AuditReport
     html.push_str("  <meta http-equiv=\"refresh\" content=\"300\">\n");
     html.push_str("  <link rel=\"icon\" href=\"data:image/svg+xml,<svg xmlns=%22http://www.w3.org/2000/svg%22 viewBox=%220 0 100 100%22><text y=%22.9em%22 font-size=%2290%22>⇄</text></svg>\">\n");
     html.push_str(&render_styles());
-    html.push_str("</head>\n<body>\n");
+    html.push_str(&format!("</head>\n<body{}>\n", body_attributes));
 
     html.push_str(&format!(
         "  <h3>Last Sync: {}</h3>\n",
         Local::now().format("%Y-%m-%d %H:%M")
     ));
 
-    html.push_str("  <div class=\"container\">\n");
-
-    // Line B
-    html.push_str("    <div class=\"column\">\n");
-    html.push_str("      <h2>Line B</h2>\n");
-    html.push_str("      <div class=\"column-content\">\n");
-    html.push_str(&render_full_report(line_b_report));
-    html.push_str("      </div>\n");
-    html.push_str("    </div>\n");
-
-    // Line A
-    html.push_str("    <div class=\"column\">\n");
-    html.push_str("      <h2>Line A</h2>\n");
-    html.push_str("      <div class=\"column-content\">\n");
-    html.push_str(&render_full_report(line_a_report));
-    html.push_str("      </div>\n");
-    html.push_str("    </div>\n");
-
-    html.push_str("  </div>\n");
+    html.push_str(content);
 
     // Footer with Ferris
     html.push_str("  <div class=\"footer\">\n");
@@ -71,7 +134,7 @@ fn render_styles() -> String {
     body { background-color: #0c0c0c; color: #d1d1d1; font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Roboto, Helvetica, Arial, sans-serif; margin: 0; height: 100vh; overflow: hidden; display: flex; flex-direction: column; }
     h1 { text-align: center; margin: 15px 0 5px 0; color: #4CAF50; font-size: 2.2em; letter-spacing: 1px; }
     h3 { text-align: center; font-size: 0.9em; color: #777; margin: 0 0 15px 0; font-weight: normal; text-transform: uppercase; letter-spacing: 2px; }
-    .container { display: flex; gap: 20px; justify-content: center; align-items: stretch; flex: 1; min-height: 0; overflow: hidden; padding: 0 20px 15px 20px; }
+    .container { display: flex; gap: 20px; justify-content: center; align-items: stretch; flex: 1; min-height: 0; overflow-x: auto; overflow-y: hidden; padding: 0 20px 15px 20px; }
     .column { flex: 1; min-width: 300px; max-width: 1000px; border: 1px solid #333; padding: 0; background-color: #161616; border-radius: 12px; box-shadow: 0 10px 30px rgba(0,0,0,0.5); display: flex; flex-direction: column; min-height: 0; }
     h2 { text-align: center; color: #fff; border-bottom: 1px solid #333; padding: 20px 25px 15px 25px; margin: 0; font-size: 1.5em; letter-spacing: 1px; background-color: #161616; flex-shrink: 0; }
     .column-content { flex: 1; min-height: 0; overflow-y: auto; overflow-x: hidden; padding: 25px; }
@@ -81,12 +144,26 @@ fn render_styles() -> String {
     .yellow { color: #FFD700; font-weight: 500; }
     .red { color: #f44336; }
     .bold { font-weight: bold; }
+    .cyan { color: #00BCD4; }
     .data-table { width: 100%; border-collapse: collapse; margin: 10px 0; font-size: 0.9em; min-width: 600px; }
     .data-table th { background-color: #1a1a1a; padding: 10px; text-align: left; border-bottom: 2px solid #333; }
     .data-table td { padding: 8px; border-bottom: 1px solid #222; }
     .data-table tr.summary { border-top: 3px solid #4CAF50; background-color: #1a1a1a; font-weight: bold; }
     .anomaly-table { width: 100%; border-collapse: collapse; }
     .anomaly-table td { padding: 5px 10px; }
+    .chart-grid { display: grid; grid-template-columns: repeat(auto-fill, minmax(260px, 1fr)); gap: 10px; }
+    .calendar { display: grid; grid-template-rows: repeat(7, 12px); grid-auto-flow: column; grid-auto-columns: 12px; gap: 2px; margin: 10px 0; }
+    .cal-cell { border-radius: 2px; }
+    .cal-legend { display: flex; flex-wrap: wrap; gap: 4px 12px; font-size: 0.8em; color: #999; }
+    .cal-legend .cal-cell { display: inline-block; width: 10px; height: 10px; margin-right: 4px; vertical-align: middle; }
+    .cal-healthy { background-color: #4CAF50; }
+    .cal-bad { background-color: #f44336; }
+    .cal-anomaly { background-color: #FFD700; }
+    .cal-missing { background-color: #7a1f1f; border: 1px solid #f44336; box-sizing: border-box; }
+    .cal-weekend { background-color: #2a2a2a; }
+    .cal-holiday { background-color: #2a2a2a; border: 1px solid #5c8ab8; box-sizing: border-box; }
+    .cal-pending { background-color: #161616; border: 1px dashed #444; box-sizing: border-box; }
+    .chart { width: 100%; height: auto; background-color: #1a1a1a; border-radius: 6px; }
     .recent-files { list-style: none; padding-left: 0; font-size: 0.85em; }
     .recent-files li { padding: 2px 0; word-break: break-all; }
     .redundancy { margin: 10px 0; padding: 10px; background-color: #1a1a1a; border-left: 3px solid #FFC107; }
@@ -94,6 +171,15 @@ fn render_styles() -> String {
     progress::-webkit-progress-bar { background-color: #2a2a2a; border-radius: 4px; }
     progress::-webkit-progress-value { background: linear-gradient(90deg, #4CAF50, #8BC34A); border-radius: 4px; }
     progress::-moz-progress-bar { background: linear-gradient(90deg, #4CAF50, #8BC34A); border-radius: 4px; }
+    body.multi-site { height: auto; overflow-y: auto; }
+    body.multi-site .container { flex: none; height: 85vh; }
+    body.static-page { height: auto; overflow-y: auto; }
+    .page-content { max-width: 1400px; width: 100%; margin: 0 auto; padding: 0 20px; box-sizing: border-box; }
+    a { color: #8BC34A; }
+    .combined-ranking { flex-shrink: 0; margin: 0 20px 15px 20px; border: 1px solid #333; border-radius: 12px; background-color: #161616; padding: 10px 25px; }
+    .combined-ranking[open] { max-height: 45vh; overflow-y: auto; }
+    .combined-ranking summary { cursor: pointer; color: #4CAF50; font-weight: bold; }
+    .site-title { border-top: 1px solid #333; padding-top: 15px; }
     .footer { display: flex; align-items: center; justify-content: center; gap: 8px; padding: 8px 20px; color: #666; font-size: 0.9em; flex-shrink: 0; background-color: #0c0c0c; }
     .footer svg { opacity: 0.8; transition: opacity 0.3s; vertical-align: middle; }
     .footer svg:hover { opacity: 1; }
@@ -130,35 +216,267 @@ pub fn render_full_report(report: &AuditReport) -> String {
     // Transfer Estimates
     html.push_str(&render_estimates_section(report));
 
+    // Trends from the run history
+    html.push_str(&render_trends_section(report, Local::now().naive_local()));
+
+    // Source Inventory
+    html.push_str(&render_inventory_section(report));
+
     // File Integrity & Heuristics
     html.push_str(&render_integrity_section(report));
 
     // Gap Analysis
     html.push_str(&render_gap_section(report));
 
+    // Daily Status Calendar
+    html.push_str(&render_calendar_section(report));
+
     // Directory Size Anomalies
     html.push_str(&render_anomalies_section(report));
 
-    // Bad ZIP Files
+    // Daily Member Set
+    html.push_str(&render_member_section(report));
+
+    // Bad Archive Files
     html.push_str(&render_bad_files_section(
         report,
         report.max_bad_per_archive,
     ));
 
+    // Manifest Verification
+    html.push_str(&render_manifest_section(report));
+
+    // Monthly Performance Ranking
+    if let Some(ranking) = &report.monthly_ranking {
+        html.push_str(&render_monthly_ranking(ranking));
+    }
+
+    html
+}
+
+/// CSS class of a health score, with the terminal's thresholds
+fn score_class(score: f64) -> &'static str {
+    if score >= ranking::GOOD_SCORE {
+        "green"
+    } else if score >= ranking::FAIR_SCORE {
+        "yellow"
+    } else {
+        "red"
+    }
+}
+
+/// A count cell, highlighted with `class` when non-zero
+fn count_cell(count: usize, class: &str) -> String {
+    if count > 0 {
+        format!(r#"<td class="{class}">{count}</td>"#)
+    } else {
+        format!("<td>{count}</td>")
+    }
+}
+
+fn month_label(year: i32, month: u32) -> String {
+    format!("{year}-{month:02}")
+}
+
+fn render_score_line(label: &str, year: i32, month: u32, score: f64) -> String {
+    format!(
+        r#"<p><strong>{}:</strong> {} (Score: <span class="{}">{:.1}%</span>)</p>"#,
+        label,
+        month_label(year, month),
+        score_class(score),
+        score
+    )
+}
+
+/// Monthly health scores of one line, best month first
+#[must_use]
+pub fn render_monthly_ranking(report: &MonthlyRankingReport) -> String {
+    let mut html = String::new();
+    html.push_str(
+        r#"<div class="section"><h3 class="section-title">Monthly Performance Ranking</h3>"#,
+    );
+
+    if report.months.is_empty() {
+        html.push_str("<p>No monthly data available for ranking.</p></div>\n");
+        return html;
+    }
+
+    if let Some(best) = &report.best_month {
+        html.push_str(&render_score_line(
+            "Best Month",
+            best.year,
+            best.month,
+            best.health_score,
+        ));
+    }
+    if let Some(worst) = &report.worst_month {
+        html.push_str(&render_score_line(
+            "Worst Month",
+            worst.year,
+            worst.month,
+            worst.health_score,
+        ));
+    }
+    html.push_str(&format!(
+        r#"<p><strong>Average Score:</strong> <span class="{}">{:.1}%</span></p>"#,
+        score_class(report.average_score),
+        report.average_score
+    ));
+
+    html.push_str(r#"<table class="data-table"><thead><tr>"#);
+    html.push_str("<th>Month</th><th>Score</th><th>Missing</th><th>Anomalies</th><th>Invalid</th><th>Empty</th><th>Members</th><th>Archives</th><th>Status</th>");
+    html.push_str("</tr></thead><tbody>");
+    for m in &report.months {
+        html.push_str(&render_month_row(m));
+    }
+    html.push_str("</tbody></table></div>\n");
+    html
+}
+
+fn render_month_row(m: &MonthlyMetrics) -> String {
+    let status = if m.is_complete {
+        "<td>Complete</td>"
+    } else {
+        r#"<td class="cyan">Partial</td>"#
+    };
+    format!(
+        r#"<tr><td>{}</td><td class="{} bold">{:.1}%</td>{}{}{}{}{}<td>{}/{}</td>{}</tr>"#,
+        month_label(m.year, m.month),
+        score_class(m.health_score),
+        m.health_score,
+        count_cell(m.missing_days, "yellow"),
+        count_cell(m.anomaly_count, "yellow"),
+        count_cell(m.invalid_files, "red"),
+        count_cell(m.empty_files, "yellow"),
+        count_cell(m.missing_members + m.unexpected_members, "yellow"),
+        m.actual_archives,
+        m.expected_weekdays,
+        status
+    )
+}
+
+/// Monthly health scores of every line side by side, best month first
+#[must_use]
+pub fn render_combined_ranking(report: &CombinedRankingReport) -> String {
+    let mut html = String::new();
+    html.push_str(
+        r#"<div class="section"><h3 class="section-title">Combined Monthly Ranking</h3>"#,
+    );
+
+    if report.months.is_empty() {
+        html.push_str("<p>No monthly data available for combined ranking.</p></div>\n");
+        return html;
+    }
+
+    for (line_id, average) in report.line_ids.iter().zip(&report.line_averages) {
+        html.push_str(&format!(
+            r#"<p><strong>Line {} Average:</strong> <span class="{}">{:.1}%</span></p>"#,
+            escape_html(line_id),
+            score_class(*average),
+            average
+        ));
+    }
+    html.push_str(&format!(
+        r#"<p><strong>Combined Average:</strong> <span class="{}">{:.1}%</span></p>"#,
+        score_class(report.combined_average),
+        report.combined_average
+    ));
+    if let Some(best) = &report.best_month {
+        html.push_str(&render_score_line(
+            "Best Month (Combined)",
+            best.year,
+            best.month,
+            best.combined_score,
+        ));
+    }
+    if let Some(worst) = &report.worst_month {
+        html.push_str(&render_score_line(
+            "Worst Month (Combined)",
+            worst.year,
+            worst.month,
+            worst.combined_score,
+        ));
+    }
+
+    html.push_str(r#"<table class="data-table"><thead><tr><th>Month</th><th>Combined</th>"#);
+    for suffix in ["", " Invalid", " Missing"] {
+        for line_id in &report.line_ids {
+            let label = if suffix.is_empty() {
+                format!("Line {line_id}")
+            } else {
+                format!("{line_id}{suffix}")
+            };
+            html.push_str(&format!("<th>{}</th>", escape_html(&label)));
+        }
+    }
+    html.push_str("</tr></thead><tbody>");
+
+    for m in &report.months {
+        html.push_str(&format!(
+            r#"<tr><td>{}</td><td class="{} bold">{:.1}%</td>"#,
+            month_label(m.year, m.month),
+            score_class(m.combined_score),
+            m.combined_score
+        ));
+        for i in 0..m.line_metrics.len() {
+            html.push_str(&match m.line_score(i) {
+                Some(score) => format!(r#"<td class="{}">{:.1}%</td>"#, score_class(score), score),
+                None => "<td>-</td>".to_string(),
+            });
+        }
+        for metrics in &m.line_metrics {
+            html.push_str(&metrics.as_ref().map_or_else(
+                || "<td>-</td>".to_string(),
+                |metrics| count_cell(metrics.invalid_files, "red"),
+            ));
+        }
+        for metrics in &m.line_metrics {
+            html.push_str(&metrics.as_ref().map_or_else(
+                || "<td>-</td>".to_string(),
+                |metrics| count_cell(metrics.missing_days, "yellow"),
+            ));
+        }
+        html.push_str("</tr>");
+    }
+    html.push_str("</tbody></table></div>\n");
     html
 }
 
 fn render_archive_status(report: &AuditReport) -> String {
+    let pages_link = report
+        .archive_pages
+        .as_ref()
+        .map(|dir| {
+            format!(
+                r#" <a href="{}">All archives</a>"#,
+                escape_html(&archive_pages::index_href(dir))
+            )
+        })
+        .unwrap_or_default();
     format!(
         r#"<div class="section">
-<p><strong>Archive Status:</strong> <span class="green">{}</span> across <span class="green">{}</span> files.</p>
+<p><strong>Archive Status:</strong> <span class="green">{}</span> across <span class="green">{}</span> files.{}</p>
 </div>
 "#,
         human_bytes::human_bytes(report.total_size as f64),
-        report.total_files
+        report.total_files,
+        pages_link
     )
 }
 
+/// `folder`, linked to its archive page once the pages have been written
+fn archive_link(report: &AuditReport, folder: &str) -> String {
+    match &report.archive_pages {
+        Some(dir) => format!(
+            r#"<a href="{}/{}">{}</a>"#,
+            escape_html(dir),
+            escape_html(&archive_pages::page_name(folder)),
+            escape_html(folder)
+        ),
+        None => escape_html(folder),
+    }
+}
+
 fn render_transfer_status(report: &AuditReport) -> String {
     let mut html = String::new();
     html.push_str(
@@ -187,10 +505,17 @@ fn render_transfer_status(report: &AuditReport) -> String {
 
     // Recent Files
     if !report.recent_files.is_empty() {
-        html.push_str(r#"<p class="green"><strong>Active/Recent File Writes (last 5m):</strong></p><ul class="recent-files">"#);
+        let title = if report.recent_files_live {
+            "Files Being Copied:"
+        } else {
+            "Active/Recent File Writes (last 5m):"
+        };
+        html.push_str(&format!(
+            r#"<p class="green"><strong>{title}</strong></p><ul class="recent-files">"#
+        ));
         for (i, file) in report.recent_files.iter().enumerate() {
             if i < 3 {
-                html.push_str(&format!(r"<li>{}</li>", escape_html(file)));
+                html.push_str(&format!(r"<li>{}</li>", escape_html(&file.to_string())));
             }
         }
         if report.recent_files.len() > 3 {
@@ -214,7 +539,7 @@ fn render_integrity_section(report: &AuditReport) -> String {
 
     if let Some(stats) = &report.integrity_stats {
         html.push_str(r#"<table class="data-table"><thead><tr>"#);
-        html.push_str("<th>Filename</th><th>Total</th><th>Empty</th><th>Bad</th>");
+        html.push_str("<th>Filename</th><th>Format</th><th>Total</th><th>Empty</th><th>Bad</th>");
         html.push_str("<th>Min</th><th>Median</th><th>StdDev</th><th>Max</th>");
         html.push_str("</tr></thead><tbody>");
 
@@ -243,8 +568,9 @@ fn render_integrity_section(report: &AuditReport) -> String {
             };
 
             html.push_str(&format!(
-                r"<tr><td>{}</td><td>{}</td><td{}>{}</td><td{}>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                r"<tr><td>{}</td><td>{}</td><td>{}</td><td{}>{}</td><td{}>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
                 escape_html(&row.name),
+                row.format,
                 row.total,
                 empty_class, row.empty,
                 bad_class, row.bad,
@@ -265,7 +591,7 @@ fn render_integrity_section(report: &AuditReport) -> String {
         };
 
         html.push_str(&format!(
-            r#"<tr class="summary"><td class="bold">TOTALS / SUMMARY</td><td class="bold">{}</td><td{}>{}</td><td{}>{}{}</td><td class="bold">{}</td><td class="bold">{}</td><td class="bold">{}</td><td class="bold">{}</td></tr>"#,
+            r#"<tr class="summary"><td class="bold">TOTALS / SUMMARY</td><td></td><td class="bold">{}</td><td{}>{}</td><td{}>{}{}</td><td class="bold">{}</td><td class="bold">{}</td><td class="bold">{}</td><td class="bold">{}</td></tr>"#,
             stats.grand_total,
             empty_class, stats.grand_empty,
             bad_class,
@@ -278,11 +604,11 @@ fn render_integrity_section(report: &AuditReport) -> String {
         ));
 
         // Repeat header at the bottom
-        html.push_str("<tr class=\"header-repeat\"><th>Filename</th><th>Total</th><th>Empty</th><th>Bad</th><th>Min</th><th>Median</th><th>StdDev</th><th>Max</th></tr>");
+        html.push_str("<tr class=\"header-repeat\"><th>Filename</th><th>Format</th><th>Total</th><th>Empty</th><th>Bad</th><th>Min</th><th>Median</th><th>StdDev</th><th>Max</th></tr>");
 
         html.push_str("</tbody></table>");
     } else {
-        html.push_str("<p>No zip files found.</p>");
+        html.push_str("<p>No archive files found.</p>");
     }
 
     html.push_str("</div>\n");
@@ -324,17 +650,122 @@ fn render_gap_section(report: &AuditReport) -> String {
     html
 }
 
+fn render_calendar_section(report: &AuditReport) -> String {
+    let Some(gap) = &report.gap_report else {
+        return String::new();
+    };
+    // The configured window when known, else the span of the archives found
+    let (start, end) = match &report.estimates_report {
+        Some(est) => (est.start_date, est.end_date),
+        None if !gap.is_empty => (gap.start_date, gap.end_date),
+        None => return String::new(),
+    };
+
+    let mut html = String::new();
+    html.push_str(r#"<div class="section"><h3 class="section-title">Daily Status</h3>"#);
+    html.push_str(r#"<div class="calendar">"#);
+    // Rows are Monday to Sunday, so the first column starts on the start's weekday
+    for _ in 0..start.weekday().num_days_from_monday() {
+        html.push_str("<span></span>");
+    }
+    for day in calendar::build_calendar(
+        start,
+        end,
+        gap,
+        report.anomaly_report.as_ref(),
+        report.bad_files_report.as_ref(),
+        &report.holidays,
+    ) {
+        html.push_str(&format!(
+            r#"<span class="cal-cell {}" title="{}"></span>"#,
+            day.state.css_class(),
+            escape_html(&day.describe())
+        ));
+    }
+    html.push_str("</div>");
+
+    html.push_str(r#"<div class="cal-legend">"#);
+    for state in DayState::ALL {
+        html.push_str(&format!(
+            r#"<span><span class="cal-cell {}"></span>{}</span>"#,
+            state.css_class(),
+            state.label()
+        ));
+    }
+    html.push_str("</div>");
+
+    html.push_str("</div>\n");
+    html
+}
+
+fn render_trends_section(report: &AuditReport, now: NaiveDateTime) -> String {
+    let mut html = String::new();
+    html.push_str(r#"<div class="section"><h3 class="section-title">Trends</h3>"#);
+
+    let series = |value: fn(&HistoryRecord) -> f64| -> Vec<(NaiveDateTime, f64)> {
+        report
+            .history
+            .iter()
+            .map(|r| (r.timestamp, value(r)))
+            .collect()
+    };
+    let speed = series(|r| r.speed_bps as f64 / 1_024.0 / 1_024.0);
+    let mib_per_sec = |v: f64| format!("{v:.1} MiB/s");
+    let bytes = |v: f64| human_bytes::human_bytes(v);
+    let count = |v: f64| format!("{v:.0}");
+
+    let chart = |title, points: &[(NaiveDateTime, f64)], window, zero_based, format| {
+        charts::render_trend_chart(title, points, now, window, zero_based, format)
+    };
+
+    html.push_str(r#"<div class="chart-grid">"#);
+    html.push_str(&chart(
+        "Speed, 24h",
+        &speed,
+        Duration::hours(24),
+        true,
+        mib_per_sec,
+    ));
+    html.push_str(&chart(
+        "Speed, 7d",
+        &speed,
+        Duration::days(7),
+        true,
+        mib_per_sec,
+    ));
+    html.push_str(&chart(
+        "Archived Data, 30d",
+        &series(|r| r.total_bytes as f64),
+        Duration::days(30),
+        false,
+        bytes,
+    ));
+    html.push_str(&chart(
+        "Free Space, 30d",
+        &series(|r| r.free_bytes as f64),
+        Duration::days(30),
+        false,
+        bytes,
+    ));
+    html.push_str(&chart(
+        "Bad Files, 30d",
+        &series(|r| r.bad_files as f64),
+        Duration::days(30),
+        true,
+        count,
+    ));
+    html.push_str("</div>");
+
+    html.push_str("</div>\n");
+    html
+}
+
 fn render_estimates_section(report: &AuditReport) -> String {
     let mut html = String::new();
     html.push_str(r#"<div class="section"><h3 class="section-title">Transfer Estimates</h3>"#);
 
     if let Some(est) = &report.estimates_report {
-        // Calculate progress percentage based on completed weekdays
-        let progress_pct = if est.total_weekdays > 0 {
-            ((est.weekdays_completed as f64 / est.total_weekdays as f64 * 100.0).min(100.0)) as u8
-        } else {
-            0
-        };
+        let progress_pct = est.progress_pct();
 
         // Render HTML5 progress bar
         html.push_str(&format!(
@@ -375,8 +806,10 @@ fn render_estimates_section(report: &AuditReport) -> String {
             est.weekdays_remaining, date_range
         ));
         html.push_str(&format!(
-            r"<p><strong>Est. Data Left:</strong> {:.1} TiB (Free: {:.1} TiB)</p>",
-            est.estimated_data_left_tib, est.free_space_tib
+            r"<p><strong>{}</strong> {:.1} TiB (Free: {:.1} TiB)</p>",
+            est.data_left_label(),
+            est.estimated_data_left_tib,
+            est.free_space_tib
         ));
 
         if est.disk_status_ok {
@@ -422,7 +855,7 @@ fn render_anomalies_section(report: &AuditReport) -> String {
                 };
                 html.push_str(&format!(
                     r#"<tr><td>⚠️ {}</td><td class="{}">{}</td><td>({})</td></tr>"#,
-                    escape_html(&a.name),
+                    archive_link(report, &a.name),
                     color_class,
                     human_bytes::human_bytes(a.size as f64),
                     a.category
@@ -438,15 +871,59 @@ fn render_anomalies_section(report: &AuditReport) -> String {
     html
 }
 
+fn render_member_section(report: &AuditReport) -> String {
+    let mut html = String::new();
+    html.push_str(r#"<div class="section"><h3 class="section-title">Daily Member Set</h3>"#);
+
+    if let Some(r) = &report.member_report {
+        let source = match r.source {
+            CanonicalSource::Majority => "majority of folders",
+            CanonicalSource::Config => "config",
+        };
+        html.push_str(&format!(
+            r"<p><strong>Expected Members:</strong> {} names (from {}), {} folders checked</p>",
+            r.canonical.len(),
+            source,
+            r.folders_checked
+        ));
+
+        if r.folders.is_empty() {
+            html.push_str("<p>All folders hold the expected set of archives.</p>");
+        } else {
+            html.push_str(&format!(
+                r#"<p><strong>{} folders deviate:</strong> <span class="red">{} missing</span>, <span class="yellow">{} unexpected</span></p>"#,
+                r.folders.len(),
+                r.missing_count(),
+                r.unexpected_count()
+            ));
+            html.push_str(r#"<table class="data-table"><thead><tr><th>Archive</th><th>Missing</th><th>Unexpected</th></tr></thead><tbody>"#);
+            for folder in &r.folders {
+                html.push_str(&format!(
+                    r#"<tr><td>{}</td><td class="red">{}</td><td class="yellow">{}</td></tr>"#,
+                    escape_html(&folder.dir_name),
+                    escape_html(&folder.missing.join(", ")),
+                    escape_html(&folder.unexpected.join(", "))
+                ));
+            }
+            html.push_str("</tbody></table>");
+        }
+    } else {
+        html.push_str("<p>No completed directories.</p>");
+    }
+
+    html.push_str("</div>\n");
+    html
+}
+
 fn render_bad_files_section(report: &AuditReport, threshold: usize) -> String {
     let mut html = String::new();
 
     if let Some(bad_report) = &report.bad_files_report {
-        html.push_str(r#"<div class="section"><h3 class="section-title">Bad ZIP Files</h3>"#);
+        html.push_str(r#"<div class="section"><h3 class="section-title">Bad Archive Files</h3>"#);
 
         let archive_count = bad_report.files_by_folder.len();
         html.push_str(&format!(
-            r#"<p>Found {} bad ZIP files across {} archives (showing archives with >{} bad files):</p>"#,
+            r#"<p>Found {} bad archive files across {} archives (showing archives with >{} bad files):</p>"#,
             bad_report.total_count, archive_count, threshold
         ));
 
@@ -470,17 +947,23 @@ fn render_bad_files_section(report: &AuditReport, threshold: usize) -> String {
 
             html.push_str(&format!(
                 r#"<h4 style="color: #ffd700; margin: 15px 0 10px 0;">{}</h4>"#,
-                escape_html(&folder_header)
+                escape_html(&folder_header).replacen(
+                    &escape_html(folder),
+                    &archive_link(report, folder),
+                    1
+                )
             ));
 
             for file in files {
                 html.push_str(&format!(
                     r#"<div style="margin: 10px 0 10px 20px;">
 <p style="margin: 3px 0;"><strong>⚠️</strong> {}</p>
+<p style="margin: 3px 0 3px 30px; font-size: 0.9em;">Format: {}</p>
 <p style="margin: 3px 0 3px 30px; font-size: 0.9em;">Size: {}</p>
 <p style="margin: 3px 0 3px 30px; font-size: 0.9em; color: #ff6b6b;">Reason: {}</p>
 </div>"#,
                     escape_html(&file.relative_path),
+                    file.format,
                     human_bytes::human_bytes(file.size as f64),
                     escape_html(&file.reason)
                 ));
@@ -502,7 +985,138 @@ fn render_bad_files_section(report: &AuditReport, threshold: usize) -> String {
     html
 }
 
-fn escape_html(s: &str) -> String {
+fn render_inventory_section(report: &AuditReport) -> String {
+    let Some(inventory) = &report.inventory_report else {
+        return String::new();
+    };
+
+    let mut html = String::new();
+    html.push_str(r#"<div class="section"><h3 class="section-title">Source Inventory</h3>"#);
+    html.push_str(&format!(
+        r#"<p><strong>Days:</strong> <span class="green">{}</span> complete, <span class="yellow">{}</span> incomplete, <span class="red">{}</span> missing</p>
+<p><strong>Copied:</strong> {} of {}</p>"#,
+        inventory.complete_days,
+        inventory.incomplete_days,
+        inventory.missing_days,
+        human_bytes::human_bytes(inventory.copied_bytes as f64),
+        human_bytes::human_bytes(inventory.total_bytes as f64)
+    ));
+
+    let open_days: Vec<_> = inventory
+        .days
+        .iter()
+        .filter(|d| d.status != DayStatus::Complete)
+        .collect();
+    if !open_days.is_empty() {
+        html.push_str(r#"<table class="data-table"><thead><tr>"#);
+        html.push_str("<th>Archive</th><th>Status</th><th>Files</th><th>Copied</th><th>Problems</th>");
+        html.push_str("</tr></thead><tbody>");
+
+        for day in open_days {
+            let (class, status) = match day.status {
+                DayStatus::Missing => ("red", "MISSING"),
+                _ if day.copying => ("green", "COPYING"),
+                _ => ("yellow", "INCOMPLETE"),
+            };
+
+            let mut problems: Vec<String> = day
+                .size_mismatches
+                .iter()
+                .take(report.max_bad_per_archive)
+                .map(|(rel, expected, actual)| {
+                    format!(
+                        "{} (size {} of {})",
+                        escape_html(rel),
+                        human_bytes::human_bytes(*actual as f64),
+                        human_bytes::human_bytes(*expected as f64)
+                    )
+                })
+                .collect();
+            if day.status == DayStatus::Incomplete {
+                problems.extend(
+                    day.missing_files
+                        .iter()
+                        .take(report.max_bad_per_archive)
+                        .map(|rel| format!("{} (missing)", escape_html(rel))),
+                );
+            }
+            let hidden = day.size_mismatches.len() + day.missing_files.len() - problems.len();
+            if day.status == DayStatus::Incomplete && hidden > 0 {
+                problems.push(format!("... {} more", hidden));
+            }
+
+            html.push_str(&format!(
+                r#"<tr><td>{}</td><td class="{}">{}</td><td>{}/{}</td><td>{} of {}</td><td>{}</td></tr>"#,
+                escape_html(&day.dir_name),
+                class,
+                status,
+                day.complete_files,
+                day.expected_files,
+                human_bytes::human_bytes(day.copied_bytes as f64),
+                human_bytes::human_bytes(day.expected_bytes as f64),
+                problems.join("<br>")
+            ));
+        }
+
+        html.push_str("</tbody></table>");
+    }
+
+    html.push_str("</div>\n");
+    html
+}
+
+fn render_manifest_section(report: &AuditReport) -> String {
+    let Some(manifest_report) = &report.manifest_report else {
+        return String::new();
+    };
+
+    let mut html = String::new();
+    html.push_str(r#"<div class="section"><h3 class="section-title">Manifest Verification</h3>"#);
+    html.push_str(&format!(
+        r#"<p><strong>Manifests:</strong> <span class="green">{}</span> verified, {} created, {} skipped (still growing), {} not yet complete</p>"#,
+        manifest_report.verified,
+        manifest_report.created.len(),
+        manifest_report.skipped_growing,
+        manifest_report.skipped_incomplete
+    ));
+
+    if manifest_report.issues_by_dir.is_empty() {
+        html.push_str("<p>No content changes since manifests were written.</p>");
+    } else {
+        html.push_str(&format!(
+            r#"<p>Found <span class="red">{}</span> issues across {} archives:</p>"#,
+            manifest_report.issue_count(),
+            manifest_report.issues_by_dir.len()
+        ));
+        for (dir, issues) in &manifest_report.issues_by_dir {
+            html.push_str(&format!(
+                r#"<h4 style="color: #ffd700; margin: 15px 0 10px 0;">{} ({} issues)</h4><table class="anomaly-table">"#,
+                escape_html(dir),
+                issues.len()
+            ));
+            for issue in issues {
+                let class = if issue.kind == ManifestIssueKind::Extra {
+                    "yellow"
+                } else {
+                    "red"
+                };
+                html.push_str(&format!(
+                    r#"<tr><td>⚠️</td><td class="{}">{}</td><td>{}</td></tr>"#,
+                    class,
+                    issue.kind,
+                    escape_html(&issue.relative_path)
+                ));
+            }
+            html.push_str("</table>");
+        }
+    }
+
+    html.push_str("</div>\n");
+    html
+}
+
+#[must_use]
+pub fn escape_html(s: &str) -> String {
     s.replace('&', "&amp;")
         .replace('<', "&lt;")
         .replace('>', "&gt;")
@@ -0,0 +1,390 @@
+use crate::naming::DirPattern;
+use colored::Colorize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use walkdir::WalkDir;
+
+/// One file listed in the source-side inventory
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceFile {
+    pub path: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DayStatus {
+    Complete,
+    Incomplete,
+    Missing,
+}
+
+#[derive(Debug)]
+pub struct DayCompletion {
+    pub dir_name: String,
+    pub status: DayStatus,
+    /// Directory is still growing (being copied right now)
+    pub copying: bool,
+    pub expected_files: usize,
+    pub complete_files: usize,
+    pub missing_files: Vec<String>,
+    /// (relative path, source size, destination size)
+    pub size_mismatches: Vec<(String, u64, u64)>,
+    pub expected_bytes: u64,
+    pub copied_bytes: u64,
+}
+
+#[derive(Debug)]
+pub struct InventoryReport {
+    pub days: Vec<DayCompletion>,
+    pub total_bytes: u64,
+    pub copied_bytes: u64,
+    pub complete_days: usize,
+    pub incomplete_days: usize,
+    pub missing_days: usize,
+}
+
+impl InventoryReport {
+    #[must_use]
+    pub fn remaining_bytes(&self) -> u64 {
+        self.total_bytes.saturating_sub(self.copied_bytes)
+    }
+}
+
+/// Load a source inventory CSV as exported by
+/// `Get-ChildItem -Recurse -File | Select-Object FullName, Length | Export-Csv`.
+///
+/// Columns are found by header name (`path`/`FullName`, `size`/`Length`).
+/// Completeness is judged by size only: any other column, such as a
+/// `LastWriteTime` from a wider export, is ignored.
+pub fn load_inventory(csv_path: &str) -> Result<Vec<SourceFile>, String> {
+    let content = fs::read_to_string(csv_path)
+        .map_err(|e| format!("Cannot read inventory {}: {}", csv_path, e))?;
+    parse_inventory(&content).map_err(|e| format!("Invalid inventory {}: {}", csv_path, e))
+}
+
+fn parse_inventory(content: &str) -> Result<Vec<SourceFile>, String> {
+    // Export-Csv without -NoTypeInformation starts with a `#TYPE` line
+    let mut lines = content
+        .lines()
+        .map(|l| l.trim_start_matches('\u{feff}'))
+        .filter(|l| !l.trim().is_empty() && !l.starts_with('#'));
+
+    let header = split_csv_line(lines.next().ok_or("empty file")?);
+    let column = |names: &[&str]| {
+        header
+            .iter()
+            .position(|h| names.iter().any(|n| h.eq_ignore_ascii_case(n)))
+    };
+    let path_col = column(&["path", "fullname"]).ok_or("missing path column")?;
+    let size_col = column(&["size", "length"]).ok_or("missing size column")?;
+
+    lines
+        .enumerate()
+        .map(|(i, line)| {
+            let fields = split_csv_line(line);
+            let field = |col: usize| {
+                fields
+                    .get(col)
+                    .ok_or_else(|| format!("row {}: too few columns", i + 2))
+            };
+            let size = field(size_col)?
+                .trim()
+                .parse()
+                .map_err(|_| format!("row {}: invalid size", i + 2))?;
+            Ok(SourceFile {
+                path: field(path_col)?.clone(),
+                size,
+            })
+        })
+        .collect()
+}
+
+/// Split one CSV line, honouring double quotes and `""` escapes
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                current.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    fields.push(current);
+    fields
+}
+
+/// Split a source or destination path into (archive directory, path below
+/// it). Windows and Unix separators are both accepted.
+fn archive_key(path: &str, line_id: &str, pattern: &DirPattern) -> Option<(String, String)> {
+    let parts: Vec<&str> = path.split(['/', '\\']).filter(|p| !p.is_empty()).collect();
+    let (dir, below) = pattern.find_in_path(&parts, line_id)?;
+    if below >= parts.len() {
+        return None;
+    }
+    Some((dir, parts[below..].join("/")))
+}
+
+/// Sizes of every file below `search_dir`, keyed like the inventory
+#[must_use]
+pub fn destination_sizes(
+    search_dir: &str,
+    line_id: &str,
+    pattern: &DirPattern,
+) -> HashMap<(String, String), u64> {
+    WalkDir::new(search_dir)
+        .into_iter()
+        .filter_map(std::result::Result::ok)
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| {
+            let rel = e.path().strip_prefix(Path::new(search_dir)).ok()?;
+            let key = archive_key(&rel.to_string_lossy(), line_id, pattern)?;
+            Some((key, e.metadata().ok()?.len()))
+        })
+        .collect()
+}
+
+/// Diff the source inventory against the destination, per archive day
+#[must_use]
+pub fn compare_inventory(
+    source: &[SourceFile],
+    destination: &HashMap<(String, String), u64>,
+    growing_dirs: &HashSet<String>,
+    line_id: &str,
+    pattern: &DirPattern,
+) -> InventoryReport {
+    let mut by_dir: BTreeMap<String, Vec<(String, u64)>> = BTreeMap::new();
+    for file in source {
+        if let Some((dir, rel)) = archive_key(&file.path, line_id, pattern) {
+            by_dir.entry(dir).or_default().push((rel, file.size));
+        }
+    }
+
+    let days: Vec<DayCompletion> = by_dir
+        .into_iter()
+        .map(|(dir_name, mut files)| {
+            files.sort();
+            let mut day = DayCompletion {
+                copying: growing_dirs.contains(&dir_name),
+                status: DayStatus::Complete,
+                expected_files: files.len(),
+                complete_files: 0,
+                missing_files: Vec::new(),
+                size_mismatches: Vec::new(),
+                expected_bytes: 0,
+                copied_bytes: 0,
+                dir_name,
+            };
+
+            for (rel, size) in files {
+                day.expected_bytes += size;
+                match destination.get(&(day.dir_name.clone(), rel.clone())) {
+                    None => day.missing_files.push(rel),
+                    Some(&actual) if actual == size => {
+                        day.complete_files += 1;
+                        day.copied_bytes += size;
+                    }
+                    Some(&actual) => {
+                        // Partially copied bytes still count towards progress
+                        day.copied_bytes += actual.min(size);
+                        day.size_mismatches.push((rel, size, actual));
+                    }
+                }
+            }
+
+            day.status = if day.complete_files == day.expected_files {
+                DayStatus::Complete
+            } else if day.missing_files.len() == day.expected_files {
+                DayStatus::Missing
+            } else {
+                DayStatus::Incomplete
+            };
+            day
+        })
+        .collect();
+
+    let count = |status| days.iter().filter(|d| d.status == status).count();
+    InventoryReport {
+        total_bytes: days.iter().map(|d| d.expected_bytes).sum(),
+        copied_bytes: days.iter().map(|d| d.copied_bytes).sum(),
+        complete_days: count(DayStatus::Complete),
+        incomplete_days: count(DayStatus::Incomplete),
+        missing_days: count(DayStatus::Missing),
+        days,
+    }
+}
+
+pub fn print_inventory_report(report: &InventoryReport, max_files_per_day: usize) {
+    println!(
+        "Source Inventory: {} days complete, {} incomplete, {} missing",
+        report.complete_days.to_string().green(),
+        report.incomplete_days.to_string().yellow(),
+        report.missing_days.to_string().red()
+    );
+    println!(
+        "Copied:           {} of {}",
+        human_bytes::human_bytes(report.copied_bytes as f64),
+        human_bytes::human_bytes(report.total_bytes as f64)
+    );
+
+    for day in report
+        .days
+        .iter()
+        .filter(|d| d.status != DayStatus::Complete)
+    {
+        let status = match day.status {
+            DayStatus::Missing => "MISSING".red(),
+            _ if day.copying => "COPYING".green(),
+            _ => "INCOMPLETE".yellow(),
+        };
+        println!(
+            "\n{} {} ({}/{} files, {} of {})",
+            day.dir_name.yellow(),
+            status,
+            day.complete_files,
+            day.expected_files,
+            human_bytes::human_bytes(day.copied_bytes as f64),
+            human_bytes::human_bytes(day.expected_bytes as f64)
+        );
+        if day.status == DayStatus::Missing {
+            continue;
+        }
+
+        for (rel, expected, actual) in day.size_mismatches.iter().take(max_files_per_day) {
+            println!(
+                "  {} {} (size {} of {})",
+                "⚠️".yellow(),
+                rel,
+                human_bytes::human_bytes(*actual as f64),
+                human_bytes::human_bytes(*expected as f64)
+            );
+        }
+        for rel in day.missing_files.iter().take(max_files_per_day) {
+            println!("  {} {} (missing)", "⚠️".yellow(), rel);
+        }
+        let shown = day.size_mismatches.len().min(max_files_per_day)
+            + day.missing_files.len().min(max_files_per_day);
+        let total = day.size_mismatches.len() + day.missing_files.len();
+        if total > shown {
+            println!("  ... {} more files in this archive", total - shown);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(path: &str, size: u64) -> SourceFile {
+        SourceFile {
+            path: path.to_string(),
+            size,
+        }
+    }
+
+    #[test]
+    fn test_parse_export_csv() {
+        let csv = "#TYPE Selected.System.IO.FileInfo\n\
+            \"FullName\",\"Length\",\"LastWriteTime\"\n\
+            \"D:\\Beam\\Archive_Beam_B_2024-08-01\\Device, 1.zip\",\"1024\",\"01/08/2024 18:00:00\"\n\
+            \"D:\\Beam\\Archive_Beam_B_2024-08-01\\say \"\"hi\"\".zip\",\"2048\",\"01/08/2024 18:00:00\"\n";
+        let files = parse_inventory(csv).unwrap();
+        assert_eq!(
+            files,
+            vec![
+                source("D:\\Beam\\Archive_Beam_B_2024-08-01\\Device, 1.zip", 1024),
+                source("D:\\Beam\\Archive_Beam_B_2024-08-01\\say \"hi\".zip", 2048),
+            ]
+        );
+
+        assert!(parse_inventory("path,mtime\nx,1\n").is_err());
+        assert!(parse_inventory("path,size\nx,abc\n").is_err());
+    }
+
+    #[test]
+    fn test_compare_inventory_statuses() {
+        let inventory = vec![
+            source("D:\\Beam\\Archive_Beam_B_2024-08-01\\a.zip", 100),
+            source("D:\\Beam\\Archive_Beam_B_2024-08-01\\b.zip", 200),
+            source("D:\\Beam\\Archive_Beam_B_2024-08-02\\a.zip", 100),
+            source("D:\\Beam\\Archive_Beam_B_2024-08-02\\sub\\b.zip", 200),
+            source("D:\\Beam\\Archive_Beam_B_2024-08-05\\a.zip", 300),
+            source("D:\\Beam\\Archive_Beam_A_2024-08-01\\a.zip", 999),
+        ];
+        let destination: HashMap<(String, String), u64> = [
+            (("Archive_Beam_B_2024-08-01", "a.zip"), 100),
+            (("Archive_Beam_B_2024-08-01", "b.zip"), 200),
+            (("Archive_Beam_B_2024-08-02", "sub/b.zip"), 50),
+        ]
+        .into_iter()
+        .map(|((d, f), s)| ((d.to_string(), f.to_string()), s))
+        .collect();
+
+        let report = compare_inventory(
+            &inventory,
+            &destination,
+            &HashSet::new(),
+            "B",
+            &DirPattern::default(),
+        );
+        let statuses: Vec<_> = report
+            .days
+            .iter()
+            .map(|d| (d.dir_name.as_str(), d.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("Archive_Beam_B_2024-08-01", DayStatus::Complete),
+                ("Archive_Beam_B_2024-08-02", DayStatus::Incomplete),
+                ("Archive_Beam_B_2024-08-05", DayStatus::Missing),
+            ]
+        );
+
+        let day2 = &report.days[1];
+        assert_eq!(day2.missing_files, vec!["a.zip"]);
+        assert_eq!(
+            day2.size_mismatches,
+            vec![("sub/b.zip".to_string(), 200, 50)]
+        );
+
+        assert_eq!(report.total_bytes, 900);
+        assert_eq!(report.copied_bytes, 350);
+        assert_eq!(report.remaining_bytes(), 550);
+        assert_eq!(
+            (
+                report.complete_days,
+                report.incomplete_days,
+                report.missing_days
+            ),
+            (1, 1, 1)
+        );
+    }
+
+    #[test]
+    fn test_destination_sizes_keys_match_inventory() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join("Archive_Beam_B_2024-08-01/sub");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("b.zip"), [0u8; 10]).unwrap();
+        fs::write(temp.path().join("stray.txt"), "x").unwrap();
+
+        let sizes = destination_sizes(temp.path().to_str().unwrap(), "B", &DirPattern::default());
+        assert_eq!(sizes.len(), 1);
+        assert_eq!(
+            sizes.get(&(
+                "Archive_Beam_B_2024-08-01".to_string(),
+                "sub/b.zip".to_string()
+            )),
+            Some(&10)
+        );
+    }
+}
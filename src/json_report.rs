@@ -0,0 +1,948 @@
+use crate::audit::write_dashboard;
+use crate::estimates::EstimatesReport;
+use crate::gap_analysis::GapReport;
+use crate::html_renderer::AuditReport;
+use crate::inventory::{DayStatus, InventoryReport};
+use crate::manifest::{ManifestIssueKind, ManifestReport};
+use crate::members::{CanonicalSource, MemberSetReport};
+use crate::ranking::{
+    self, CombinedMonthlyMetrics, CombinedRankingReport, MonthlyMetrics, MonthlyRankingReport,
+};
+use crate::sites::Site;
+use crate::stats::{self, AnomalyReport, IntegrityStats};
+use crate::types::ArchiveFormat;
+use chrono::{Local, NaiveDate};
+use log::{error, info};
+use serde::Serialize;
+use std::path::Path;
+
+/// Version of the JSON documents. Bumped when a field is renamed, removed
+/// or changes meaning; fields may be added without a bump.
+///
+/// The documents are built from the structs below rather than from the
+/// report types themselves, so the schema only changes when they do.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Every line of every site, as shown on the dashboard
+#[derive(Serialize)]
+struct AuditDocument<'a> {
+    schema_version: u32,
+    kind: &'static str,
+    generated_at: String,
+    sites: Vec<SiteDocument<'a>>,
+}
+
+#[derive(Serialize)]
+struct SiteDocument<'a> {
+    /// `null` for the site given by `--base-dir`
+    name: Option<&'a str>,
+    base_dir: &'a str,
+    lines: Vec<LineDocument<'a>>,
+    /// Present when more than one line has a monthly ranking
+    combined_ranking: Option<CombinedRankingJson>,
+}
+
+#[derive(Serialize)]
+struct LineDocument<'a> {
+    line_id: &'a str,
+    total_size: u64,
+    total_files: usize,
+    speed_bps: u64,
+    since_timestamp: &'a str,
+    recent_files: Vec<FileWriteJson<'a>>,
+    /// `recent_files` are being copied right now rather than written in
+    /// the last 5 minutes
+    recent_files_live: bool,
+    integrity_stats: Option<IntegrityJson<'a>>,
+    gap_report: Option<GapJson<'a>>,
+    estimates_report: Option<EstimatesJson>,
+    anomaly_threshold: f64,
+    anomaly_report: Option<AnomalyJson<'a>>,
+    /// Every bad file, not only the first few per folder shown in HTML
+    bad_files_report: BadFilesJson,
+    manifest_report: Option<ManifestJson<'a>>,
+    inventory_report: Option<InventoryJson<'a>>,
+    member_report: Option<MemberSetJson<'a>>,
+    monthly_ranking: Option<MonthlyRankingJson<'a>>,
+}
+
+#[derive(Serialize)]
+struct FileWriteJson<'a> {
+    path: &'a str,
+    size: u64,
+    modified: String,
+}
+
+#[derive(Serialize)]
+struct IntegrityJson<'a> {
+    rows: Vec<IntegrityRowJson<'a>>,
+    grand_total: usize,
+    grand_empty: usize,
+    grand_bad: usize,
+    grand_min: u64,
+    grand_max: u64,
+    grand_median: u64,
+    grand_std_dev: f64,
+}
+
+#[derive(Serialize)]
+struct IntegrityRowJson<'a> {
+    name: &'a str,
+    format: &'static str,
+    total: usize,
+    empty: usize,
+    bad: usize,
+    min_size: u64,
+    max_size: u64,
+    median_size: u64,
+    std_dev: f64,
+    valid_stats: bool,
+}
+
+#[derive(Serialize)]
+struct GapJson<'a> {
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    missing_weekdays: &'a [NaiveDate],
+    skipped_weekends: u32,
+    is_empty: bool,
+    /// Archive folder found for each date, by date
+    folders: Vec<DayFolderJson<'a>>,
+}
+
+#[derive(Serialize)]
+struct DayFolderJson<'a> {
+    date: NaiveDate,
+    name: &'a str,
+    files: usize,
+}
+
+#[derive(Serialize)]
+struct EstimatesJson {
+    currently_copying: Option<NaiveDate>,
+    last_completed: Option<NaiveDate>,
+    weekdays_remaining: usize,
+    weekdays_completed: u32,
+    total_weekdays: u32,
+    progress_ratio: f64,
+    remaining_bytes: u64,
+    free_bytes: u64,
+    disk_status_ok: bool,
+    estimated_hours_eta: Option<u64>,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    /// Present when a source inventory was loaded
+    inventory_copied_bytes: Option<u64>,
+    inventory_total_bytes: Option<u64>,
+}
+
+#[derive(Serialize)]
+struct AnomalyJson<'a> {
+    median_daily_size: u64,
+    anomalies: Vec<AnomalyEntryJson<'a>>,
+}
+
+#[derive(Serialize)]
+struct AnomalyEntryJson<'a> {
+    name: &'a str,
+    size: u64,
+    /// `too_small` or `too_large`
+    category: &'static str,
+}
+
+#[derive(Serialize)]
+struct BadFilesJson {
+    total_count: usize,
+    folders: Vec<BadFolderJson>,
+}
+
+#[derive(Serialize)]
+struct BadFolderJson {
+    name: String,
+    files: Vec<BadFileJson>,
+}
+
+#[derive(Serialize)]
+struct BadFileJson {
+    relative_path: String,
+    format: &'static str,
+    size: u64,
+    reason: String,
+}
+
+#[derive(Serialize)]
+struct ManifestJson<'a> {
+    created: &'a [String],
+    verified: usize,
+    skipped_growing: usize,
+    skipped_incomplete: usize,
+    /// Directories with problems, sorted by name
+    dirs_with_issues: Vec<ManifestDirJson<'a>>,
+}
+
+#[derive(Serialize)]
+struct ManifestDirJson<'a> {
+    name: &'a str,
+    issues: Vec<ManifestIssueJson<'a>>,
+}
+
+#[derive(Serialize)]
+struct ManifestIssueJson<'a> {
+    relative_path: &'a str,
+    /// `changed`, `missing`, `extra` or `unreadable`
+    kind: &'static str,
+}
+
+#[derive(Serialize)]
+struct InventoryJson<'a> {
+    days: Vec<DayCompletionJson<'a>>,
+    total_bytes: u64,
+    copied_bytes: u64,
+    complete_days: usize,
+    incomplete_days: usize,
+    missing_days: usize,
+}
+
+#[derive(Serialize)]
+struct DayCompletionJson<'a> {
+    dir_name: &'a str,
+    /// `complete`, `incomplete` or `missing`
+    status: &'static str,
+    copying: bool,
+    expected_files: usize,
+    complete_files: usize,
+    missing_files: &'a [String],
+    size_mismatches: Vec<SizeMismatchJson<'a>>,
+    expected_bytes: u64,
+    copied_bytes: u64,
+}
+
+#[derive(Serialize)]
+struct SizeMismatchJson<'a> {
+    relative_path: &'a str,
+    source_size: u64,
+    destination_size: u64,
+}
+
+#[derive(Serialize)]
+struct MemberSetJson<'a> {
+    canonical: &'a [String],
+    /// `majority` or `config`
+    source: &'static str,
+    folders_checked: usize,
+    /// Only folders that deviate, sorted by name
+    folders: Vec<FolderMembersJson<'a>>,
+}
+
+#[derive(Serialize)]
+struct FolderMembersJson<'a> {
+    dir_name: &'a str,
+    missing: &'a [String],
+    unexpected: &'a [String],
+}
+
+#[derive(Serialize)]
+struct MonthlyRankingJson<'a> {
+    line_id: &'a str,
+    /// Best first
+    months: Vec<MonthJson>,
+    best_month: Option<MonthJson>,
+    worst_month: Option<MonthJson>,
+    average_score: f64,
+}
+
+#[derive(Serialize)]
+struct MonthJson {
+    year: i32,
+    month: u32,
+    health_score: f64,
+    missing_days: usize,
+    anomaly_count: usize,
+    invalid_files: usize,
+    empty_files: usize,
+    missing_members: usize,
+    unexpected_members: usize,
+    expected_weekdays: usize,
+    actual_archives: usize,
+    is_complete: bool,
+}
+
+#[derive(Serialize)]
+struct CombinedRankingJson {
+    /// Best first
+    months: Vec<CombinedMonthJson>,
+    best_month: Option<CombinedMonthJson>,
+    worst_month: Option<CombinedMonthJson>,
+    lines: Vec<LineAverageJson>,
+    combined_average: f64,
+}
+
+#[derive(Serialize)]
+struct CombinedMonthJson {
+    year: i32,
+    month: u32,
+    combined_score: f64,
+    /// Lines with data for this month
+    lines: Vec<LineMonthJson>,
+}
+
+#[derive(Serialize)]
+struct LineMonthJson {
+    line_id: String,
+    #[serde(flatten)]
+    month: MonthJson,
+}
+
+#[derive(Serialize)]
+struct LineAverageJson {
+    line_id: String,
+    average_score: f64,
+}
+
+/// Monthly rankings only (`--rank-months-combined`)
+#[derive(Serialize)]
+struct RankingDocument<'a> {
+    schema_version: u32,
+    kind: &'static str,
+    generated_at: String,
+    combined_ranking: CombinedRankingJson,
+    line_rankings: Vec<MonthlyRankingJson<'a>>,
+}
+
+/// One part of a line's report, as served by `serve` under
+/// `/api/sites/<site>/lines/<line>/...`
+#[derive(Serialize)]
+struct LinePartDocument<'a, T: Serialize> {
+    schema_version: u32,
+    kind: &'static str,
+    generated_at: String,
+    /// `null` for the site given by `--base-dir`
+    site: Option<&'a str>,
+    line_id: &'a str,
+    /// `null` when the audit did not produce this part
+    data: Option<T>,
+}
+
+fn format_id(format: ArchiveFormat) -> &'static str {
+    match format {
+        ArchiveFormat::Zip => "zip",
+        ArchiveFormat::Tar => "tar",
+        ArchiveFormat::TarGz => "tar.gz",
+        ArchiveFormat::SevenZip => "7z",
+    }
+}
+
+impl<'a> LineDocument<'a> {
+    fn new(line_id: &'a str, report: &'a AuditReport) -> Self {
+        Self {
+            line_id,
+            total_size: report.total_size,
+            total_files: report.total_files,
+            speed_bps: report.speed_bps,
+            since_timestamp: &report.since_timestamp,
+            recent_files: report
+                .recent_files
+                .iter()
+                .map(|file| FileWriteJson {
+                    path: &file.path,
+                    size: file.size,
+                    modified: file.modified.to_rfc3339(),
+                })
+                .collect(),
+            recent_files_live: report.recent_files_live,
+            integrity_stats: report.integrity_stats.as_ref().map(IntegrityJson::new),
+            gap_report: report.gap_report.as_ref().map(GapJson::new),
+            estimates_report: report.estimates_report.as_ref().map(EstimatesJson::new),
+            anomaly_threshold: report.anomaly_threshold,
+            anomaly_report: report.anomaly_report.as_ref().map(AnomalyJson::new),
+            bad_files_report: BadFilesJson::new(line_id, report),
+            manifest_report: report.manifest_report.as_ref().map(ManifestJson::new),
+            inventory_report: report.inventory_report.as_ref().map(InventoryJson::new),
+            member_report: report.member_report.as_ref().map(MemberSetJson::new),
+            monthly_ranking: report.monthly_ranking.as_ref().map(MonthlyRankingJson::new),
+        }
+    }
+}
+
+impl<'a> IntegrityJson<'a> {
+    fn new(stats: &'a IntegrityStats) -> Self {
+        Self {
+            rows: stats
+                .rows
+                .iter()
+                .map(|row| IntegrityRowJson {
+                    name: &row.name,
+                    format: format_id(row.format),
+                    total: row.total,
+                    empty: row.empty,
+                    bad: row.bad,
+                    min_size: row.min_size,
+                    max_size: row.max_size,
+                    median_size: row.median_size,
+                    std_dev: row.std_dev,
+                    valid_stats: row.valid_stats,
+                })
+                .collect(),
+            grand_total: stats.grand_total,
+            grand_empty: stats.grand_empty,
+            grand_bad: stats.grand_bad,
+            grand_min: stats.grand_min,
+            grand_max: stats.grand_max,
+            grand_median: stats.grand_median,
+            grand_std_dev: stats.grand_std_dev,
+        }
+    }
+}
+
+impl<'a> GapJson<'a> {
+    fn new(gaps: &'a GapReport) -> Self {
+        Self {
+            start_date: gaps.start_date,
+            end_date: gaps.end_date,
+            missing_weekdays: &gaps.missing_weekdays,
+            skipped_weekends: gaps.skipped_weekends,
+            is_empty: gaps.is_empty,
+            folders: gaps
+                .folders
+                .iter()
+                .map(|(date, folder)| DayFolderJson {
+                    date: *date,
+                    name: &folder.name,
+                    files: folder.files,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl EstimatesJson {
+    fn new(estimates: &EstimatesReport) -> Self {
+        Self {
+            currently_copying: estimates.currently_copying,
+            last_completed: estimates.last_completed,
+            weekdays_remaining: estimates.weekdays_remaining,
+            weekdays_completed: estimates.weekdays_completed,
+            total_weekdays: estimates.total_weekdays,
+            progress_ratio: estimates.progress_ratio(),
+            remaining_bytes: estimates.remaining_bytes,
+            free_bytes: estimates.free_bytes,
+            disk_status_ok: estimates.disk_status_ok,
+            estimated_hours_eta: estimates.estimated_hours_eta,
+            start_date: estimates.start_date,
+            end_date: estimates.end_date,
+            inventory_copied_bytes: estimates.inventory_bytes.map(|(copied, _)| copied),
+            inventory_total_bytes: estimates.inventory_bytes.map(|(_, total)| total),
+        }
+    }
+}
+
+impl<'a> AnomalyJson<'a> {
+    fn new(report: &'a AnomalyReport) -> Self {
+        Self {
+            median_daily_size: report.median_daily_size,
+            anomalies: report
+                .anomalies
+                .iter()
+                .map(|anomaly| AnomalyEntryJson {
+                    name: &anomaly.name,
+                    size: anomaly.size,
+                    category: if anomaly.category == "Too Small" {
+                        "too_small"
+                    } else {
+                        "too_large"
+                    },
+                })
+                .collect(),
+        }
+    }
+}
+
+impl BadFilesJson {
+    /// Every bad file of the line's completed folders
+    fn new(line_id: &str, report: &AuditReport) -> Self {
+        let Some(bad_files) = stats::collect_bad_files(&report.files, line_id, usize::MAX) else {
+            return Self {
+                total_count: 0,
+                folders: Vec::new(),
+            };
+        };
+        Self {
+            total_count: bad_files.total_count,
+            folders: bad_files
+                .files_by_folder
+                .into_iter()
+                .map(|(name, files, _)| BadFolderJson {
+                    name,
+                    files: files
+                        .into_iter()
+                        .map(|file| BadFileJson {
+                            relative_path: file.relative_path,
+                            format: format_id(file.format),
+                            size: file.size,
+                            reason: file.reason,
+                        })
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl<'a> ManifestJson<'a> {
+    fn new(report: &'a ManifestReport) -> Self {
+        Self {
+            created: &report.created,
+            verified: report.verified,
+            skipped_growing: report.skipped_growing,
+            skipped_incomplete: report.skipped_incomplete,
+            dirs_with_issues: report
+                .issues_by_dir
+                .iter()
+                .map(|(name, issues)| ManifestDirJson {
+                    name,
+                    issues: issues
+                        .iter()
+                        .map(|issue| ManifestIssueJson {
+                            relative_path: &issue.relative_path,
+                            kind: match issue.kind {
+                                ManifestIssueKind::Changed => "changed",
+                                ManifestIssueKind::Missing => "missing",
+                                ManifestIssueKind::Extra => "extra",
+                                ManifestIssueKind::Unreadable => "unreadable",
+                            },
+                        })
+                        .collect(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl<'a> InventoryJson<'a> {
+    fn new(report: &'a InventoryReport) -> Self {
+        Self {
+            days: report
+                .days
+                .iter()
+                .map(|day| DayCompletionJson {
+                    dir_name: &day.dir_name,
+                    status: match day.status {
+                        DayStatus::Complete => "complete",
+                        DayStatus::Incomplete => "incomplete",
+                        DayStatus::Missing => "missing",
+                    },
+                    copying: day.copying,
+                    expected_files: day.expected_files,
+                    complete_files: day.complete_files,
+                    missing_files: &day.missing_files,
+                    size_mismatches: day
+                        .size_mismatches
+                        .iter()
+                        .map(|(path, source, destination)| SizeMismatchJson {
+                            relative_path: path,
+                            source_size: *source,
+                            destination_size: *destination,
+                        })
+                        .collect(),
+                    expected_bytes: day.expected_bytes,
+                    copied_bytes: day.copied_bytes,
+                })
+                .collect(),
+            total_bytes: report.total_bytes,
+            copied_bytes: report.copied_bytes,
+            complete_days: report.complete_days,
+            incomplete_days: report.incomplete_days,
+            missing_days: report.missing_days,
+        }
+    }
+}
+
+impl<'a> MemberSetJson<'a> {
+    fn new(report: &'a MemberSetReport) -> Self {
+        Self {
+            canonical: &report.canonical,
+            source: match report.source {
+                CanonicalSource::Majority => "majority",
+                CanonicalSource::Config => "config",
+            },
+            folders_checked: report.folders_checked,
+            folders: report
+                .folders
+                .iter()
+                .map(|folder| FolderMembersJson {
+                    dir_name: &folder.dir_name,
+                    missing: &folder.missing,
+                    unexpected: &folder.unexpected,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl MonthJson {
+    fn new(month: &MonthlyMetrics) -> Self {
+        Self {
+            year: month.year,
+            month: month.month,
+            health_score: month.health_score,
+            missing_days: month.missing_days,
+            anomaly_count: month.anomaly_count,
+            invalid_files: month.invalid_files,
+            empty_files: month.empty_files,
+            missing_members: month.missing_members,
+            unexpected_members: month.unexpected_members,
+            expected_weekdays: month.expected_weekdays,
+            actual_archives: month.actual_archives,
+            is_complete: month.is_complete,
+        }
+    }
+}
+
+impl<'a> MonthlyRankingJson<'a> {
+    fn new(report: &'a MonthlyRankingReport) -> Self {
+        Self {
+            line_id: &report.line_id,
+            months: report.months.iter().map(MonthJson::new).collect(),
+            best_month: report.best_month.as_ref().map(MonthJson::new),
+            worst_month: report.worst_month.as_ref().map(MonthJson::new),
+            average_score: report.average_score,
+        }
+    }
+}
+
+impl CombinedMonthJson {
+    fn new(month: &CombinedMonthlyMetrics, line_ids: &[String]) -> Self {
+        Self {
+            year: month.year,
+            month: month.month,
+            combined_score: month.combined_score,
+            lines: line_ids
+                .iter()
+                .zip(&month.line_metrics)
+                .filter_map(|(line_id, metrics)| {
+                    metrics.as_ref().map(|metrics| LineMonthJson {
+                        line_id: line_id.clone(),
+                        month: MonthJson::new(metrics),
+                    })
+                })
+                .collect(),
+        }
+    }
+}
+
+impl CombinedRankingJson {
+    fn new(report: &CombinedRankingReport) -> Self {
+        let month = |month| CombinedMonthJson::new(month, &report.line_ids);
+        Self {
+            months: report.months.iter().map(month).collect(),
+            best_month: report.best_month.as_ref().map(month),
+            worst_month: report.worst_month.as_ref().map(month),
+            lines: report
+                .line_ids
+                .iter()
+                .zip(&report.line_averages)
+                .map(|(line_id, average)| LineAverageJson {
+                    line_id: line_id.clone(),
+                    average_score: *average,
+                })
+                .collect(),
+            combined_average: report.combined_average,
+        }
+    }
+}
+
+fn generated_at() -> String {
+    Local::now().to_rfc3339()
+}
+
+fn site_document<'a>(
+    name: Option<&'a str>,
+    base_dir: &'a str,
+    line_reports: impl Iterator<Item = (&'a str, &'a AuditReport)>,
+) -> SiteDocument<'a> {
+    let mut rankings: Vec<MonthlyRankingReport> = Vec::new();
+    let lines = line_reports
+        .map(|(line_id, report)| {
+            rankings.extend(report.monthly_ranking.clone());
+            LineDocument::new(line_id, report)
+        })
+        .collect();
+    SiteDocument {
+        name,
+        base_dir,
+        lines,
+        combined_ranking: (rankings.len() > 1)
+            .then(|| CombinedRankingJson::new(&ranking::combine_rankings(&rankings))),
+    }
+}
+
+fn to_json<T: Serialize>(document: &T) -> String {
+    serde_json::to_string_pretty(document).expect("audit reports serialize to JSON")
+}
+
+/// JSON document for the reports of every site, in `sites` order
+#[must_use]
+pub fn render_sites(sites: &[Site], site_reports: &[Vec<(String, AuditReport)>]) -> String {
+    to_json(&AuditDocument {
+        schema_version: SCHEMA_VERSION,
+        kind: "audit",
+        generated_at: generated_at(),
+        sites: sites
+            .iter()
+            .zip(site_reports)
+            .map(|(site, reports)| {
+                site_document(
+                    site.name.as_deref(),
+                    &site.base_dir,
+                    reports
+                        .iter()
+                        .map(|(line_id, report)| (line_id.as_str(), report)),
+                )
+            })
+            .collect(),
+    })
+}
+
+/// JSON document for a single line of a site (`None` for the one given by
+/// `--base-dir`, as when auditing from the command line)
+#[must_use]
+pub fn render_line(
+    site: Option<&str>,
+    base_dir: &str,
+    line_id: &str,
+    report: &AuditReport,
+) -> String {
+    to_json(&AuditDocument {
+        schema_version: SCHEMA_VERSION,
+        kind: "audit",
+        generated_at: generated_at(),
+        sites: vec![site_document(
+            site,
+            base_dir,
+            std::iter::once((line_id, report)),
+        )],
+    })
+}
+
+fn render_line_part<T: Serialize>(
+    kind: &'static str,
+    site: Option<&str>,
+    line_id: &str,
+    data: Option<T>,
+) -> String {
+    to_json(&LinePartDocument {
+        schema_version: SCHEMA_VERSION,
+        kind,
+        generated_at: generated_at(),
+        site,
+        line_id,
+        data,
+    })
+}
+
+/// Every bad file of one line, `kind` "bad_files"
+#[must_use]
+pub fn render_line_bad_files(site: Option<&str>, line_id: &str, report: &AuditReport) -> String {
+    render_line_part(
+        "bad_files",
+        site,
+        line_id,
+        Some(BadFilesJson::new(line_id, report)),
+    )
+}
+
+/// Missing weekdays and the folders found for one line, `kind` "gaps"
+#[must_use]
+pub fn render_line_gaps(site: Option<&str>, line_id: &str, report: &AuditReport) -> String {
+    render_line_part(
+        "gaps",
+        site,
+        line_id,
+        report.gap_report.as_ref().map(GapJson::new),
+    )
+}
+
+/// Monthly ranking of one line, `kind` "line_ranking"
+#[must_use]
+pub fn render_line_ranking(site: Option<&str>, line_id: &str, report: &AuditReport) -> String {
+    render_line_part(
+        "line_ranking",
+        site,
+        line_id,
+        report.monthly_ranking.as_ref().map(MonthlyRankingJson::new),
+    )
+}
+
+#[must_use]
+pub fn render_rankings(
+    combined: &CombinedRankingReport,
+    line_rankings: &[MonthlyRankingReport],
+) -> String {
+    to_json(&RankingDocument {
+        schema_version: SCHEMA_VERSION,
+        kind: "ranking",
+        generated_at: generated_at(),
+        combined_ranking: CombinedRankingJson::new(combined),
+        line_rankings: line_rankings.iter().map(MonthlyRankingJson::new).collect(),
+    })
+}
+
+/// JSON file written next to the dashboard: `index.html` → `index.json`
+#[must_use]
+pub fn dashboard_json_path(output_file: &str) -> String {
+    Path::new(output_file)
+        .with_extension("json")
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Write the JSON document for the dashboard's reports next to it
+pub fn write_dashboard_json(
+    output_file: &str,
+    sites: &[Site],
+    site_reports: &[Vec<(String, AuditReport)>],
+) {
+    let json_file = dashboard_json_path(output_file);
+    match write_dashboard(&json_file, &render_sites(sites, site_reports)) {
+        Ok(()) => info!("Dashboard JSON written to: {}", json_file),
+        Err(e) => error!("Error writing dashboard JSON to {}: {}", json_file, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inventory::DayCompletion;
+    use crate::types::{FileEntry, FileWrite};
+    use chrono::TimeZone;
+    use serde_json::Value;
+    use std::path::Path;
+
+    fn report() -> AuditReport {
+        AuditReport {
+            total_size: 4096,
+            total_files: 2,
+            speed_bps: 0,
+            since_timestamp: "2025-03-14 10:00".to_string(),
+            recent_files: Vec::new(),
+            recent_files_live: false,
+            redundancy_check: None,
+            integrity_stats: None,
+            gap_report: Some(GapReport {
+                start_date: NaiveDate::from_ymd_opt(2025, 3, 13).unwrap(),
+                end_date: NaiveDate::from_ymd_opt(2025, 3, 17).unwrap(),
+                missing_weekdays: vec![NaiveDate::from_ymd_opt(2025, 3, 14).unwrap()],
+                skipped_weekends: 2,
+                is_empty: false,
+                folders: Default::default(),
+            }),
+            estimates_report: None,
+            anomaly_report: None,
+            bad_files_report: None,
+            manifest_report: None,
+            inventory_report: None,
+            member_report: None,
+            max_bad_per_archive: 5,
+            history: Vec::new(),
+            files: Vec::new(),
+            anomaly_threshold: 0.5,
+            archive_pages: None,
+            monthly_ranking: None,
+            holidays: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_line_document_shape() {
+        let json: Value =
+            serde_json::from_str(&render_line(None, "/data", "B", &report())).unwrap();
+
+        assert_eq!(json["schema_version"], SCHEMA_VERSION);
+        assert_eq!(json["kind"], "audit");
+        let site = &json["sites"][0];
+        assert_eq!(site["name"], Value::Null);
+        assert_eq!(site["base_dir"], "/data");
+        assert_eq!(site["combined_ranking"], Value::Null);
+        let line = &site["lines"][0];
+        assert_eq!(line["line_id"], "B");
+        assert_eq!(line["total_size"], 4096);
+        assert_eq!(line["gap_report"]["missing_weekdays"][0], "2025-03-14");
+        assert_eq!(line["bad_files_report"]["total_count"], 0);
+        // Internal fields stay out of the document
+        assert!(line.get("files").is_none());
+        assert!(line.get("history").is_none());
+    }
+
+    #[test]
+    fn test_named_fields_and_full_bad_file_list() {
+        let modified = Local.with_ymd_and_hms(2025, 3, 14, 10, 30, 0).unwrap();
+        let mut report = report();
+        report.max_bad_per_archive = 2;
+        report.files = (1..=4)
+            .map(|i| FileEntry {
+                name: format!("Cam{i}.zip"),
+                size: 100,
+                format: ArchiveFormat::Zip,
+                is_valid: false,
+                invalid_reason: Some("CRC mismatch".to_string()),
+                modified,
+                parent_dir: "Archive_Beam_B_2025-03-14".to_string(),
+            })
+            .collect();
+        report.recent_files = vec![FileWrite::new(
+            Path::new("/data/Line B/Archive_Beam_B_2025-03-14/Cam5.zip"),
+            2048,
+            modified,
+        )];
+        report.inventory_report = Some(InventoryReport {
+            days: vec![DayCompletion {
+                dir_name: "Archive_Beam_B_2025-03-14".to_string(),
+                status: DayStatus::Incomplete,
+                copying: false,
+                expected_files: 5,
+                complete_files: 4,
+                missing_files: Vec::new(),
+                size_mismatches: vec![("Cam5.zip".to_string(), 4096, 2048)],
+                expected_bytes: 4496,
+                copied_bytes: 2448,
+            }],
+            total_bytes: 4496,
+            copied_bytes: 2448,
+            complete_days: 0,
+            incomplete_days: 1,
+            missing_days: 0,
+        });
+
+        let json: Value = serde_json::from_str(&render_line(None, "/data", "B", &report)).unwrap();
+        let line = &json["sites"][0]["lines"][0];
+
+        // Not truncated to max_bad_per_archive
+        let bad_files = &line["bad_files_report"];
+        assert_eq!(bad_files["total_count"], 4);
+        let folder = &bad_files["folders"][0];
+        assert_eq!(folder["name"], "Archive_Beam_B_2025-03-14");
+        assert_eq!(folder["files"].as_array().unwrap().len(), 4);
+        assert_eq!(folder["files"][0]["format"], "zip");
+        assert_eq!(folder["files"][0]["reason"], "CRC mismatch");
+
+        let recent = &line["recent_files"][0];
+        assert_eq!(recent["path"], "Line B/Archive_Beam_B_2025-03-14/Cam5.zip");
+        assert_eq!(recent["size"], 2048);
+        assert_eq!(recent["modified"], modified.to_rfc3339());
+
+        let day = &line["inventory_report"]["days"][0];
+        assert_eq!(day["status"], "incomplete");
+        assert_eq!(day["size_mismatches"][0]["relative_path"], "Cam5.zip");
+        assert_eq!(day["size_mismatches"][0]["source_size"], 4096);
+        assert_eq!(day["size_mismatches"][0]["destination_size"], 2048);
+    }
+
+    #[test]
+    fn test_dashboard_json_path() {
+        assert_eq!(
+            dashboard_json_path("/var/www/html/index.html"),
+            "/var/www/html/index.json"
+        );
+        assert_eq!(dashboard_json_path("dashboard"), "dashboard.json");
+    }
+}
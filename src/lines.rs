@@ -0,0 +1,87 @@
+use log::debug;
+use std::fs;
+
+/// Lines to audit: `<base_dir>/.lines` when present (one line ID per line,
+/// `#` starts a comment, order is kept for the dashboard), otherwise every
+/// `Line <X>` folder under `base_dir`, sorted by ID
+#[must_use]
+pub fn resolve_lines(base_dir: &str) -> Vec<String> {
+    load_configured_lines(base_dir).unwrap_or_else(|| discover_lines(base_dir))
+}
+
+fn load_configured_lines(base_dir: &str) -> Option<Vec<String>> {
+    let config_path = format!("{}/.lines", base_dir);
+    let content = fs::read_to_string(&config_path).ok()?;
+    let mut lines: Vec<String> = Vec::new();
+    for line_id in content
+        .lines()
+        .map(|l| l.split('#').next().unwrap_or("").trim())
+        .filter(|l| !l.is_empty())
+    {
+        if !lines.iter().any(|l| l == line_id) {
+            lines.push(line_id.to_string());
+        }
+    }
+    debug!("Loaded {} line IDs from {}", lines.len(), config_path);
+    (!lines.is_empty()).then_some(lines)
+}
+
+fn discover_lines(base_dir: &str) -> Vec<String> {
+    let mut lines: Vec<String> = fs::read_dir(base_dir)
+        .into_iter()
+        .flatten()
+        .filter_map(std::result::Result::ok)
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            let line_id = name.strip_prefix("Line ")?.trim();
+            (!line_id.is_empty()).then(|| line_id.to_string())
+        })
+        .collect();
+    lines.sort();
+    debug!("Discovered lines under {}: {:?}", base_dir, lines);
+    lines
+}
+
+/// Canonical spelling of `line_id` among `lines`, ignoring case
+#[must_use]
+pub fn find_line<'a>(lines: &'a [String], line_id: &str) -> Option<&'a str> {
+    lines
+        .iter()
+        .find(|l| l.eq_ignore_ascii_case(line_id))
+        .map(String::as_str)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_discover_lines_from_folders() {
+        let temp = tempfile::tempdir().unwrap();
+        for dir in ["Line D", "Line A", "Line C", "Other", "Line B"] {
+            fs::create_dir(temp.path().join(dir)).unwrap();
+        }
+        fs::write(temp.path().join("Line E"), "not a folder").unwrap();
+
+        let base_dir = temp.path().to_str().unwrap();
+        assert_eq!(resolve_lines(base_dir), vec!["A", "B", "C", "D"]);
+        assert_eq!(resolve_lines("/nonexistent/base"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_configured_lines_override_discovery() {
+        let temp = tempfile::tempdir().unwrap();
+        fs::create_dir(temp.path().join("Line A")).unwrap();
+        fs::write(
+            temp.path().join(".lines"),
+            "# dashboard order\nB\nA\nC  # commissioning\nB\n",
+        )
+        .unwrap();
+
+        let lines = resolve_lines(temp.path().to_str().unwrap());
+        assert_eq!(lines, vec!["B", "A", "C"]);
+        assert_eq!(find_line(&lines, "c"), Some("C"));
+        assert_eq!(find_line(&lines, "D"), None);
+    }
+}
@@ -1,43 +1,85 @@
+mod alerts;
+mod archive_pages;
+mod audit;
+mod calendar;
+mod charts;
+mod csv_export;
+mod daemon;
 mod email;
 mod estimates;
 mod gap_analysis;
+mod history;
 mod html_renderer;
+mod inventory;
+mod json_report;
+mod lines;
+mod manifest;
+mod members;
+mod metrics;
+mod naming;
 mod ranking;
+mod scan_cache;
 mod scanner;
+mod server;
+mod sidecar;
+mod sites;
 mod stats;
 mod system_io;
+mod transfer;
 mod types;
+mod validators;
+mod watcher;
 
 use chrono::{Local, NaiveDate};
 use clap::Parser;
 use colored::Colorize;
-use log::{debug, error, info, warn};
-use std::collections::HashMap;
-use std::collections::HashSet;
+use log::{debug, error, info};
 use std::fs;
-use std::fs::OpenOptions;
-use std::io::Write;
-use std::os::unix::fs::OpenOptionsExt;
 use std::thread;
 use std::time::Duration;
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
+#[command(group(
+    clap::ArgGroup::new("daemon_schedule")
+        .args(["sample_interval", "alert_interval", "scan_interval"])
+        .multiple(true)
+        .requires("daemon")
+))]
 struct Args {
-    /// Line ID (A or B)
+    /// Line ID (a `Line <ID>` folder under the base dir, or listed in `.lines`)
     #[arg(default_value = "B")]
     line_id: String,
 
-    /// Output HTML instead of terminal colors
+    /// Output HTML instead of terminal colors (same as --format html)
     #[arg(long, short = 'H')]
     html: bool,
 
-    /// Generate full dashboard HTML for both lines (writes to file)
+    /// Output format. With --dashboard, json also writes the report as JSON
+    /// next to the HTML (index.html -> index.json)
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text, conflicts_with = "html")]
+    format: OutputFormat,
+
+    /// Generate full dashboard HTML for all lines (writes to file)
     #[arg(long, short = 'd', value_name = "FILE")]
     dashboard: Option<String>,
 
+    /// Also write per-line Prometheus gauges to FILE (a `.prom` file in the
+    /// node_exporter textfile collector directory)
+    #[arg(long, value_name = "FILE")]
+    metrics: Option<String>,
+
+    /// Drop run history records older than DAYS from `.history_<line>` (0 keeps everything)
+    #[arg(long, value_name = "DAYS", default_value_t = 0)]
+    history_retention_days: u32,
+
     /// Base directory containing Line A/B folders
-    #[arg(long, short = 'b', default_value = "/data/storage/samba_share_cluster")]
+    #[arg(
+        long,
+        short = 'b',
+        default_value = "/data/storage/samba_share_cluster",
+        global = true
+    )]
     base_dir: String,
 
     /// Test email configuration by sending a test email
@@ -72,9 +114,181 @@ struct Args {
     #[arg(long)]
     rank_months: bool,
 
-    /// Generate combined monthly ranking for both lines (ignores line_id)
+    /// Generate combined monthly ranking for all lines (ignores line_id)
     #[arg(long)]
     rank_months_combined: bool,
+
+    /// Write the line's bad files, missing weekdays, anomalies, integrity rows
+    /// and monthly ranking as CSV files into DIR
+    #[arg(
+        long,
+        value_name = "DIR",
+        conflicts_with_all = ["dashboard", "rank_months_combined"]
+    )]
+    export_csv: Option<String>,
+
+    /// Decompress every ZIP member and verify its CRC32 (slow, reads all data)
+    #[arg(long)]
+    deep_verify: bool,
+
+    /// Ignore the scan cache and re-validate every ZIP file
+    #[arg(long)]
+    rescan_all: bool,
+
+    /// Worker threads for ZIP validation, per line [default: CPU count, max 8]
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u16).range(1..))]
+    scan_threads: Option<u16>,
+
+    /// Write SHA-256 manifests for complete archive directories and re-verify existing ones
+    #[arg(long)]
+    manifest: bool,
+
+    /// Verify archives against `.sha256`/`.md5` sidecars or per-day checksum lists
+    #[arg(long)]
+    verify_sidecars: bool,
+
+    /// Source inventory CSV (path, size) for exact per-day completion accounting
+    #[arg(long, value_name = "CSV")]
+    inventory: Option<String>,
+
+    /// Sites config auditing several base dirs into one dashboard (with --dashboard or serve)
+    #[arg(long, value_name = "FILE", conflicts_with = "inventory")]
+    sites: Option<String>,
+
+    /// Archive directory naming pattern: {line}, {date} or {yyyy}/{mm}/{dd}, `/` for nested levels
+    #[arg(long, value_name = "TEMPLATE", default_value = naming::DEFAULT_DIR_PATTERN)]
+    dir_pattern: naming::DirPattern,
+
+    /// Keep running: sample sizes continuously and refresh the dashboard until SIGTERM/SIGINT (requires --dashboard)
+    #[arg(long, requires = "dashboard")]
+    daemon: bool,
+
+    #[command(flatten)]
+    intervals: DaemonIntervals,
+
+    /// Daemon: seconds between dashboard refreshes
+    #[arg(
+        long,
+        value_name = "SECS",
+        default_value_t = 300,
+        requires = "daemon",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    dashboard_interval: u64,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// Schedules shared by `--daemon` and `serve`
+#[derive(clap::Args, Debug, Clone, Copy)]
+struct DaemonIntervals {
+    /// Daemon: seconds between size samples (transfer speed, growing directories)
+    #[arg(
+        long,
+        value_name = "SECS",
+        default_value_t = 10,
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    sample_interval: u64,
+
+    /// Daemon: seconds between transfer state updates and alert checks
+    #[arg(
+        long,
+        value_name = "SECS",
+        default_value_t = 60,
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    alert_interval: u64,
+
+    /// Daemon: seconds between archive integrity scans
+    #[arg(
+        long,
+        value_name = "SECS",
+        default_value_t = 3600,
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    scan_interval: u64,
+}
+
+impl DaemonIntervals {
+    fn with_dashboard(self, dashboard: u64) -> daemon::Intervals {
+        daemon::Intervals {
+            sample: Duration::from_secs(self.sample_interval),
+            alerts: Duration::from_secs(self.alert_interval),
+            dashboard: Duration::from_secs(dashboard),
+            scan: Duration::from_secs(self.scan_interval),
+        }
+    }
+}
+
+#[derive(clap::Subcommand, Debug)]
+enum Command {
+    /// Print and summarise the recorded run history of one line
+    History {
+        /// Line ID
+        #[arg(default_value = "B")]
+        line_id: String,
+
+        /// How far back to look, e.g. 90m, 24h, 7d, 2w
+        #[arg(long, default_value = "24h", value_parser = history::parse_window)]
+        window: chrono::Duration,
+    },
+    /// Audit every line continuously and serve the dashboard and a JSON API
+    /// over HTTP (`/`, `/api/report`, `/api/ranking`, `/api/lines/<line>`
+    /// and its `/bad-files`, `/gaps`, `/ranking`; named sites use
+    /// `/api/sites/<site>/lines/<line>`)
+    Serve {
+        /// Address to listen on
+        #[arg(long, value_name = "ADDR", default_value = "127.0.0.1:8080")]
+        listen: String,
+
+        /// Seconds between dashboard refreshes
+        #[arg(
+            long,
+            value_name = "SECS",
+            default_value_t = 60,
+            value_parser = clap::value_parser!(u64).range(1..)
+        )]
+        refresh: u64,
+
+        #[command(flatten)]
+        intervals: DaemonIntervals,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum OutputFormat {
+    /// Coloured terminal text
+    Text,
+    Html,
+    /// Versioned JSON document (see `json_report::SCHEMA_VERSION`)
+    Json,
+}
+
+impl Args {
+    fn output_format(&self) -> OutputFormat {
+        if self.html {
+            OutputFormat::Html
+        } else {
+            self.format
+        }
+    }
+
+    fn scan_options(&self) -> scanner::ScanOptions {
+        scanner::ScanOptions {
+            deep_verify: self.deep_verify,
+            rescan_all: self.rescan_all,
+            cache_file: None,
+            manifests: self.manifest,
+            verify_sidecars: self.verify_sidecars,
+            dir_pattern: self.dir_pattern.clone(),
+            threads: self
+                .scan_threads
+                .map_or_else(scanner::default_scan_threads, usize::from),
+            cancel: None,
+        }
+    }
 }
 
 /// Resolve date range from CLI arguments with priority: CLI flags > Script > Defaults
@@ -127,6 +341,13 @@ fn main() {
         .init();
 
     let args = Args::parse();
+    if args.sites.is_some()
+        && args.dashboard.is_none()
+        && !matches!(args.command, Some(Command::Serve { .. }))
+    {
+        eprintln!("Error: --sites requires --dashboard or the serve subcommand.");
+        std::process::exit(2);
+    }
 
     // Test email mode
     if args.test_email {
@@ -134,34 +355,97 @@ fn main() {
         return;
     }
 
-    // Dashboard mode - generate both lines
+    if let Some(Command::History { line_id, window }) = &args.command {
+        show_history(&args.base_dir, line_id, *window);
+        return;
+    }
+
+    let source_inventory = args.inventory.as_deref().map(|path| {
+        inventory::load_inventory(path).unwrap_or_else(|e| {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        })
+    });
+
+    if let Some(Command::Serve {
+        listen,
+        refresh,
+        intervals,
+    }) = &args.command
+    {
+        serve(
+            &args,
+            listen,
+            intervals.with_dashboard(*refresh),
+            source_inventory.as_deref(),
+        );
+        return;
+    }
+
+    // Dashboard mode - generate all lines of every site
     if let Some(output_file) = &args.dashboard {
+        let sites = resolve_sites_or_exit(&args);
+        if args.daemon {
+            let intervals = args.intervals.with_dashboard(args.dashboard_interval);
+            let options = daemon::DaemonOptions {
+                alert_threshold: args.alert_threshold,
+                max_bad_per_archive: args.max_bad_per_archive,
+                anomaly_threshold: args.anomaly_threshold,
+                scan_options: &args.scan_options(),
+                source_inventory: source_inventory.as_deref(),
+                write_json: args.output_format() == OutputFormat::Json,
+                metrics_file: args.metrics.as_deref(),
+                server: None,
+                history_retention_days: args.history_retention_days,
+            };
+            if let Err(e) = daemon::run(
+                Some(output_file),
+                &sites,
+                intervals,
+                &options,
+                audit::DASHBOARD_LOCK,
+            ) {
+                eprintln!("Error: {e}");
+                std::process::exit(1);
+            }
+            return;
+        }
         generate_dashboard(
             output_file,
-            &args.base_dir,
+            &sites,
             args.alert_threshold,
             args.max_bad_per_archive,
             args.anomaly_threshold,
+            &args.scan_options(),
+            source_inventory.as_deref(),
+            args.output_format() == OutputFormat::Json,
+            args.metrics.as_deref(),
+            args.history_retention_days,
         );
         return;
     }
 
-    // Combined ranking mode - analyze both lines and show combined rankings
+    let lines = resolve_lines_or_exit(&args.base_dir);
+
+    // Combined ranking mode - analyze all lines and show combined rankings
     if args.rank_months_combined {
         generate_combined_ranking(
             &args.base_dir,
+            &lines,
             args.anomaly_threshold,
             &args,
         );
         return;
     }
 
-    let line_id = args.line_id.to_uppercase();
-
-    if line_id != "A" && line_id != "B" {
-        eprintln!("Error: Invalid Line ID '{line_id}'. Use 'A' or 'B'.");
+    let Some(line_id) = lines::find_line(&lines, &args.line_id).map(str::to_string) else {
+        eprintln!(
+            "Error: Invalid Line ID '{}'. Use one of: {}.",
+            args.line_id,
+            lines.join(", ")
+        );
         std::process::exit(1);
-    }
+    };
 
     // Resolve date range from CLI args or defaults
     let (start_date, end_date) = resolve_date_range(&args);
@@ -170,7 +454,7 @@ fn main() {
     let tiny_threshold = 1000;
 
     // Progress messages (only in terminal mode)
-    if !args.html {
+    if args.output_format() == OutputFormat::Text {
         println!(
             "{}",
             format!(
@@ -182,46 +466,21 @@ fn main() {
         );
     }
 
-    let (size_t1, dirs_t1) = scanner::get_total_and_per_dir_sizes(&search_dir);
-
-    thread::sleep(Duration::from_secs(10));
-    let (size_t2, dirs_t2) = scanner::get_total_and_per_dir_sizes(&search_dir);
+    let scan_options = args.scan_options();
+    let sample = transfer::TransferSample::since_snapshot(
+        &search_dir,
+        &scan_options.dir_pattern,
+        &transfer::snapshot_file_path(&args.base_dir, &line_id, transfer::RunKind::Line),
+    );
 
-    let files = scanner::scan_files(&search_dir);
+    let files = scanner::scan_files(
+        &search_dir,
+        &scan_options.for_line(&args.base_dir, &line_id),
+    );
     let total_zip_files = files.len();
 
     // Identify growing directories (actively being copied)
-    let growing_dirs: HashSet<String> = dirs_t1
-        .keys()
-        .filter(|dir| {
-            let size_t1 = dirs_t1.get(*dir).unwrap_or(&0);
-            let size_t2 = dirs_t2.get(*dir).unwrap_or(&0);
-            let is_growing = size_t2 > size_t1;
-            if is_growing {
-                debug!(
-                    "Line {}: Growing directory detected: {} ({} -> {} bytes, delta: {} bytes)",
-                    line_id,
-                    dir,
-                    size_t1,
-                    size_t2,
-                    size_t2.saturating_sub(*size_t1)
-                );
-            }
-            is_growing
-        })
-        .cloned()
-        .collect();
-
-    debug!(
-        "Line {}: Total directories tracked: {}",
-        line_id,
-        dirs_t1.len()
-    );
-    debug!(
-        "Line {}: Growing directories: {}",
-        line_id,
-        growing_dirs.len()
-    );
+    let growing_dirs = sample.growing_dirs(&line_id);
     debug!("Line {}: Growing dirs: {:?}", line_id, growing_dirs);
 
     // Create filtered file list for analysis (exclude files from growing directories)
@@ -239,8 +498,7 @@ fn main() {
     );
 
     // Speed Calc
-    let delta_bytes = size_t2.saturating_sub(size_t1);
-    let speed_bps = delta_bytes / 10;
+    let speed_bps = sample.speed_bps();
     let speed_mib = speed_bps as f64 / 1_024.0 / 1_024.0;
 
     // State Logic
@@ -266,16 +524,17 @@ fn main() {
     let recents = scanner::get_recent_files(&search_dir, 5);
 
     // Redundancy check (only in terminal mode for now)
-    let redundancy_check = if speed_bps == 0 && !args.html {
-        system_io::check_redundancy(&line_id);
+    let redundancy_check = if speed_bps == 0 && args.output_format() == OutputFormat::Text {
+        system_io::check_redundancy(&args.base_dir, &line_id, &lines);
         None
     } else {
         None
     };
 
     // Alerting (background, runs regardless of output mode)
-    check_and_send_alerts(
+    alerts::check_and_send_alerts(
         &args.base_dir,
+        None,
         &line_id,
         current_state,
         prev_state,
@@ -283,7 +542,7 @@ fn main() {
         args.alert_threshold,
     );
 
-    if current_state != prev_state && !args.html {
+    if current_state != prev_state && args.output_format() == OutputFormat::Text {
         println!(
             "\n{}",
             format!("-- State Change Detected ({prev_state} -> {current_state}) --").cyan()
@@ -292,35 +551,78 @@ fn main() {
 
     // Calculate all reports using filtered analysis_files
     let integrity_stats = stats::calculate_integrity_stats(&analysis_files, tiny_threshold);
-    let gap_report = gap_analysis::find_gaps(&files, &line_id);
+    let holidays = calendar::load_holidays(&args.base_dir);
+    let gap_report =
+        gap_analysis::find_gaps(&files, &line_id, &scan_options.dir_pattern, &holidays);
+    let inventory_report = source_inventory.as_deref().map(|source| {
+        inventory::compare_inventory(
+            source,
+            &inventory::destination_sizes(&search_dir, &line_id, &scan_options.dir_pattern),
+            &growing_dirs,
+            &line_id,
+            &scan_options.dir_pattern,
+        )
+    });
+    let manifest_report = scan_options.manifests.then(|| {
+        manifest::process_manifests(
+            &search_dir,
+            &line_id,
+            &scan_options,
+            &growing_dirs,
+            inventory_report.as_ref(),
+            Some(&manifest::hash_cache_path(&args.base_dir, &line_id)),
+        )
+    });
     let estimates_report = estimates::calculate_estimates(
         &search_dir,
         &analysis_files,
         &files,
         &growing_dirs,
         &line_id,
-        size_t2,
+        &scan_options.dir_pattern,
+        sample.after.total,
         speed_bps,
         start_date,
         end_date,
+        inventory_report.as_ref(),
     );
-    // Filter dirs_t2 to exclude growing directories for anomaly detection
-    let stable_dirs: std::collections::HashMap<String, u64> = dirs_t2
-        .iter()
-        .filter(|(dir, _)| !growing_dirs.contains(*dir))
-        .map(|(k, v)| (k.clone(), *v))
-        .collect();
-    let anomalies_report = stats::calculate_anomalies(&stable_dirs, args.anomaly_threshold);
+    // Exclude growing directories from anomaly detection
+    let anomalies_report =
+        stats::calculate_anomalies(&sample.after.stable_dirs(&growing_dirs), args.anomaly_threshold);
     let bad_files_report =
         stats::collect_bad_files(&analysis_files, &line_id, args.max_bad_per_archive);
+    let member_report = members::calculate_member_sets(
+        &analysis_files,
+        &line_id,
+        &scan_options.dir_pattern,
+        members::load_expected_members(&args.base_dir, &line_id),
+    );
+
+    history::append(
+        &args.base_dir,
+        &line_id,
+        &history::HistoryRecord::new(
+            transfer::RunKind::Line,
+            Local::now().naive_local(),
+            sample.after.total,
+            speed_bps,
+            estimates_report.as_ref(),
+            bad_files_report.as_ref(),
+            Some(&gap_report),
+        ),
+        args.history_retention_days,
+    );
 
-    // Monthly ranking (optional)
-    let monthly_ranking = if args.rank_months {
+    // Monthly ranking (optional, always part of the CSV export and metrics)
+    let monthly_ranking = if args.rank_months || args.export_csv.is_some() || args.metrics.is_some()
+    {
         Some(ranking::calculate_monthly_rankings(
             &analysis_files,
             Some(&gap_report),
             anomalies_report.as_ref(),
+            member_report.as_ref(),
             &line_id,
+            &scan_options.dir_pattern,
             start_date,
             end_date,
             tiny_threshold,
@@ -329,30 +631,76 @@ fn main() {
         None
     };
 
+    if let (Some(dir), Some(ranking_report)) = (&args.export_csv, &monthly_ranking) {
+        match csv_export::export_line(
+            std::path::Path::new(dir),
+            &line_id,
+            &analysis_files,
+            integrity_stats.as_ref(),
+            &gap_report,
+            anomalies_report.as_ref(),
+            ranking_report,
+        ) {
+            Ok(written) => {
+                for path in written {
+                    info!("Line {}: CSV written to: {}", line_id, path.display());
+                }
+            }
+            Err(e) => {
+                eprintln!("Error: Cannot export CSV to {}: {}", dir, e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let format = args.output_format();
+    let mut report = html_renderer::AuditReport {
+        total_size: sample.after.total,
+        total_files: total_zip_files,
+        speed_bps,
+        since_timestamp: since_ts,
+        recent_files: recents,
+        recent_files_live: false,
+        redundancy_check,
+        integrity_stats,
+        gap_report: Some(gap_report),
+        estimates_report,
+        anomaly_report: anomalies_report,
+        bad_files_report,
+        manifest_report,
+        inventory_report,
+        member_report,
+        max_bad_per_archive: args.max_bad_per_archive,
+        history: if format == OutputFormat::Text {
+            Vec::new()
+        } else {
+            history::load_kind(&args.base_dir, &line_id, transfer::RunKind::Line)
+        },
+        files: analysis_files,
+        anomaly_threshold: args.anomaly_threshold,
+        archive_pages: None,
+        monthly_ranking,
+        holidays,
+    };
+    // Shown only with --rank-months
+    let hidden_ranking = report.monthly_ranking.take_if(|_| !args.rank_months);
+
     // Output based on mode
-    if args.html {
-        let report = html_renderer::AuditReport {
-            total_size: size_t2,
-            total_files: total_zip_files,
-            speed_bps,
-            since_timestamp: since_ts,
-            recent_files: recents,
-            redundancy_check,
-            integrity_stats,
-            gap_report: Some(gap_report),
-            estimates_report,
-            anomaly_report: anomalies_report,
-            bad_files_report,
-            max_bad_per_archive: args.max_bad_per_archive,
-        };
-
-        println!("{}", html_renderer::render_full_report(&report));
+    if format != OutputFormat::Text {
+        if format == OutputFormat::Json {
+            println!(
+                "{}",
+                json_report::render_line(None, &args.base_dir, &line_id, &report)
+            );
+        } else {
+            println!("{}", html_renderer::render_full_report(&report));
+        }
     } else {
         // Terminal output (existing)
         println!(
-            "Archive Status:  {} across {} zip files.",
-            human_bytes::human_bytes(size_t2 as f64).green(),
-            total_zip_files.to_string().green()
+            "Archive Status:  {} across {} archive files.",
+            human_bytes::human_bytes(report.total_size as f64).green(),
+            report.total_files.to_string().green()
         );
 
         println!("\n{}", "=== Active Transfer Detection ===".cyan());
@@ -361,7 +709,7 @@ fn main() {
             println!(
                 "Status:                 {} (since {})",
                 "ACTIVE TRANSFER DETECTED".green(),
-                since_ts
+                report.since_timestamp
             );
             println!(
                 "Current Transfer Speed: {} MiB/s",
@@ -371,11 +719,12 @@ fn main() {
             println!(
                 "Status:                 {} (since {})",
                 "IDLE".yellow(),
-                since_ts
+                report.since_timestamp
             );
         }
 
         println!("{}", "Active/Recent File Writes (last 5m):".green());
+        let recents = &report.recent_files;
         if recents.is_empty() {
             println!();
         } else {
@@ -387,45 +736,178 @@ fn main() {
             }
         }
 
-        estimates::print_estimates(&estimates_report);
+        estimates::print_estimates(&report.estimates_report);
+
+        if let Some(ref inventory_report) = report.inventory_report {
+            println!("\n{}", "=== Source Inventory ===".cyan());
+            inventory::print_inventory_report(inventory_report, args.max_bad_per_archive);
+        }
 
         println!("\n{}", "=== File Integrity & Heuristics ===".cyan());
-        stats::print_integrity_table(&integrity_stats);
+        stats::print_integrity_table(&report.integrity_stats);
 
         println!("\n{}", "=== Missing Daily Archives ===".cyan());
-        gap_analysis::analyze_gaps(&files, &line_id);
+        gap_analysis::analyze_gaps(
+            &files,
+            &line_id,
+            &scan_options.dir_pattern,
+            &report.holidays,
+        );
 
         println!("\n{}", "=== Directory Size Anomalies ===".cyan());
-        stats::print_anomalies(&anomalies_report);
+        stats::print_anomalies(&report.anomaly_report);
+
+        println!("\n{}", "=== Daily Member Set ===".cyan());
+        members::print_member_sets(&report.member_report);
 
-        let bad_files_report =
-            stats::collect_bad_files(&analysis_files, &line_id, args.max_bad_per_archive);
-        stats::print_bad_files(&bad_files_report, args.max_bad_per_archive);
+        stats::print_bad_files(&report.bad_files_report, args.max_bad_per_archive);
 
-        if let Some(ref ranking_report) = monthly_ranking {
+        if let Some(ref manifest_report) = report.manifest_report {
+            println!("\n{}", "=== Manifest Verification ===".cyan());
+            manifest::print_manifest_report(manifest_report);
+        }
+
+        if let Some(ref ranking_report) = report.monthly_ranking {
             println!("\n{}", "=== Monthly Performance Ranking ===".cyan());
             ranking::print_monthly_rankings(ranking_report);
         }
 
         println!("\n{}", "=== Audit Complete ===".cyan());
     }
+
+    if let Some(metrics_file) = &args.metrics {
+        report.monthly_ranking = report.monthly_ranking.or(hidden_ranking);
+        let site = sites::Site::local(&args.base_dir, vec![line_id.clone()], start_date, end_date);
+        metrics::write_metrics(metrics_file, &[site], &[vec![(line_id, report)]]);
+    }
 }
 
+/// Run the daemon for every line of every site (`--sites`, or the lines
+/// under `--base-dir`), publishing each dashboard to an HTTP server on
+/// `listen` instead of a file
+fn serve(
+    args: &Args,
+    listen: &str,
+    intervals: daemon::Intervals,
+    source_inventory: Option<&[inventory::SourceFile]>,
+) {
+    let sites = resolve_sites_or_exit(args);
+    let server = server::Server::start(listen).unwrap_or_else(|e| {
+        eprintln!("Error: {e}");
+        std::process::exit(1);
+    });
+    info!(
+        "Serving the dashboard on http://{}/",
+        server
+            .local_addr()
+            .map_or_else(|| listen.to_string(), |addr| addr.to_string())
+    );
+
+    let options = daemon::DaemonOptions {
+        alert_threshold: args.alert_threshold,
+        max_bad_per_archive: args.max_bad_per_archive,
+        anomaly_threshold: args.anomaly_threshold,
+        scan_options: &args.scan_options(),
+        source_inventory,
+        write_json: false,
+        metrics_file: args.metrics.as_deref(),
+        server: Some(&server),
+        history_retention_days: args.history_retention_days,
+    };
+    if let Err(e) = daemon::run(None, &sites, intervals, &options, audit::DASHBOARD_LOCK) {
+        eprintln!("Error: {e}");
+        std::process::exit(1);
+    }
+}
+
+/// Print the recorded runs of one line within `window` and their summary
+fn show_history(base_dir: &str, line_id: &str, window: chrono::Duration) {
+    let lines = resolve_lines_or_exit(base_dir);
+    let Some(line_id) = lines::find_line(&lines, line_id) else {
+        eprintln!(
+            "Error: Invalid Line ID '{}'. Use one of: {}.",
+            line_id,
+            lines.join(", ")
+        );
+        std::process::exit(1);
+    };
+
+    let now = Local::now().naive_local();
+    let start = now.checked_sub_signed(window).map_or_else(
+        || "the start".to_string(),
+        |start| start.format("%Y-%m-%d %H:%M").to_string(),
+    );
+    println!(
+        "{}",
+        format!(
+            "=== Run History for LINE {}: {} to {} ===",
+            line_id,
+            start,
+            now.format("%Y-%m-%d %H:%M")
+        )
+        .cyan()
+    );
+    let records = history::load(base_dir, line_id);
+    history::print_history_by_kind(history::within(&records, now, window));
+}
+
+/// Sites from the `--sites` config, or the lines under `--base-dir` as
+/// the only site, exiting when neither can be loaded
+fn resolve_sites_or_exit(args: &Args) -> Vec<sites::Site> {
+    let (default_start, default_end) = dashboard_default_dates();
+    match &args.sites {
+        Some(config_path) => sites::load_sites(config_path, default_start, default_end)
+            .unwrap_or_else(|e| {
+                eprintln!("Error: {e}");
+                std::process::exit(1);
+            }),
+        None => vec![sites::Site::local(
+            &args.base_dir,
+            resolve_lines_or_exit(&args.base_dir),
+            default_start,
+            default_end,
+        )],
+    }
+}
+
+/// Lines under `base_dir`, exiting when there are none
+fn resolve_lines_or_exit(base_dir: &str) -> Vec<String> {
+    let lines = lines::resolve_lines(base_dir);
+    if lines.is_empty() {
+        eprintln!(
+            "Error: No lines found under '{}' (no 'Line *' folders or .lines file).",
+            base_dir
+        );
+        std::process::exit(1);
+    }
+    lines
+}
+
+/// Date range used in dashboard mode (no CLI date args available there)
+fn dashboard_default_dates() -> (NaiveDate, NaiveDate) {
+    (
+        NaiveDate::from_ymd_opt(2024, 7, 29).unwrap(),
+        NaiveDate::from_ymd_opt(2025, 12, 12).unwrap(),
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
 fn generate_dashboard(
     output_file: &str,
-    base_dir: &str,
+    sites: &[sites::Site],
     alert_threshold: u64,
     max_bad_per_archive: usize,
     anomaly_threshold: f64,
+    scan_options: &scanner::ScanOptions,
+    source_inventory: Option<&[inventory::SourceFile]>,
+    write_json: bool,
+    metrics_file: Option<&str>,
+    history_retention_days: u32,
 ) {
-    // Use default dates for dashboard mode (no CLI args available here)
-    let default_start = NaiveDate::from_ymd_opt(2024, 7, 29).unwrap();
-    let default_end = NaiveDate::from_ymd_opt(2025, 12, 12).unwrap();
-
     // Acquire lock to prevent concurrent runs
-    let lockfile = "/tmp/beam_audit_dashboard.lock";
+    let lockfile = audit::DASHBOARD_LOCK;
     debug!("Attempting to acquire lock: {}", lockfile);
-    let _lock = match acquire_lock(lockfile) {
+    let _lock = match audit::acquire_lock(lockfile) {
         Ok(l) => {
             debug!("Lock acquired successfully");
             l
@@ -436,39 +918,58 @@ fn generate_dashboard(
         }
     };
 
-    info!("Generating dashboard for both lines...");
+    for site in sites {
+        info!(
+            "Generating dashboard for {}lines: {}",
+            site.name
+                .as_ref()
+                .map(|name| format!("site {name}, "))
+                .unwrap_or_default(),
+            site.lines.join(", ")
+        );
+    }
 
-    // Generate reports for both lines IN PARALLEL
+    // Generate reports for all lines of all sites IN PARALLEL
     let result = std::panic::catch_unwind(|| {
         thread::scope(|s| {
-            let handle_a = s.spawn(|| {
-                collect_audit_data(
-                    "A",
-                    base_dir,
-                    alert_threshold,
-                    max_bad_per_archive,
-                    anomaly_threshold,
-                    default_start,
-                    default_end,
-                )
-            });
-            let handle_b = s.spawn(|| {
-                collect_audit_data(
-                    "B",
-                    base_dir,
-                    alert_threshold,
-                    max_bad_per_archive,
-                    anomaly_threshold,
-                    default_start,
-                    default_end,
-                )
-            });
-
-            (handle_a.join().unwrap(), handle_b.join().unwrap())
+            let handles: Vec<Vec<_>> = sites
+                .iter()
+                .map(|site| {
+                    site.lines
+                        .iter()
+                        .map(|line_id| {
+                            s.spawn(move || {
+                                audit::collect_audit_data(
+                                    line_id,
+                                    site,
+                                    alert_threshold,
+                                    max_bad_per_archive,
+                                    anomaly_threshold,
+                                    scan_options,
+                                    source_inventory,
+                                    history_retention_days,
+                                )
+                            })
+                        })
+                        .collect()
+                })
+                .collect();
+
+            sites
+                .iter()
+                .zip(handles)
+                .map(|(site, site_handles)| {
+                    site.lines
+                        .iter()
+                        .cloned()
+                        .zip(site_handles.into_iter().map(|h| h.join().unwrap()))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
         })
     });
 
-    let (line_a_report, line_b_report) = if let Ok(reports) = result {
+    let mut site_reports = if let Ok(reports) = result {
         reports
     } else {
         debug!("Removing lock file due to error");
@@ -477,12 +978,20 @@ fn generate_dashboard(
         std::process::exit(1);
     };
 
+    archive_pages::write_archive_pages(output_file, sites, &mut site_reports);
+    if write_json {
+        json_report::write_dashboard_json(output_file, sites, &site_reports);
+    }
+    if let Some(metrics_file) = metrics_file {
+        metrics::write_metrics(metrics_file, sites, &site_reports);
+    }
+
     // Render dashboard HTML
-    let html = html_renderer::render_dashboard(&line_a_report, &line_b_report);
+    let html = audit::render_site_reports(sites, site_reports);
 
     // Write to file
     debug!("Writing dashboard HTML to: {}", output_file);
-    if let Err(e) = fs::write(output_file, html) {
+    if let Err(e) = audit::write_dashboard(output_file, &html) {
         error!("Error writing dashboard to {}: {}", output_file, e);
         debug!("Removing lock file due to error");
         fs::remove_file(lockfile).ok();
@@ -494,58 +1003,97 @@ fn generate_dashboard(
     fs::remove_file(lockfile).ok();
 }
 
-fn generate_combined_ranking(base_dir: &str, anomaly_threshold: f64, args: &Args) {
+fn generate_combined_ranking(
+    base_dir: &str,
+    lines: &[String],
+    anomaly_threshold: f64,
+    args: &Args,
+) {
     let (start_date, end_date) = resolve_date_range(args);
     let tiny_threshold = 1000;
 
-    println!(
-        "{}",
-        format!(
-            "=== Combined Monthly Ranking: {} ===",
-            Local::now().format("%Y-%m-%d %H:%M")
-        )
-        .cyan()
-    );
-    println!("Scanning both lines (this takes ~20 seconds)...\n");
+    if args.output_format() == OutputFormat::Text {
+        println!(
+            "{}",
+            format!(
+                "=== Combined Monthly Ranking: {} ===",
+                Local::now().format("%Y-%m-%d %H:%M")
+            )
+            .cyan()
+        );
+        println!(
+            "Scanning {} lines (this takes ~20 seconds)...\n",
+            lines.len()
+        );
+    }
 
-    // Collect data for both lines in parallel
-    let (line_a_data, line_b_data) = thread::scope(|s| {
-        let handle_a = s.spawn(|| collect_ranking_data("A", base_dir, anomaly_threshold, start_date, end_date, tiny_threshold));
-        let handle_b = s.spawn(|| collect_ranking_data("B", base_dir, anomaly_threshold, start_date, end_date, tiny_threshold));
-        (handle_a.join().unwrap(), handle_b.join().unwrap())
+    let scan_options = args.scan_options();
+
+    // Collect data for all lines in parallel
+    let line_data: Vec<RankingData> = thread::scope(|s| {
+        let handles: Vec<_> = lines
+            .iter()
+            .map(|line_id| {
+                let scan_options = &scan_options;
+                s.spawn(move || {
+                    collect_ranking_data(
+                        line_id,
+                        base_dir,
+                        anomaly_threshold,
+                        scan_options,
+                    )
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
     });
 
     // Calculate rankings for each line
-    let ranking_a = ranking::calculate_monthly_rankings(
-        &line_a_data.files,
-        Some(&line_a_data.gap_report),
-        line_a_data.anomaly_report.as_ref(),
-        "A",
-        start_date,
-        end_date,
-        tiny_threshold,
-    );
-
-    let ranking_b = ranking::calculate_monthly_rankings(
-        &line_b_data.files,
-        Some(&line_b_data.gap_report),
-        line_b_data.anomaly_report.as_ref(),
-        "B",
-        start_date,
-        end_date,
-        tiny_threshold,
-    );
+    let rankings: Vec<ranking::MonthlyRankingReport> = lines
+        .iter()
+        .zip(&line_data)
+        .map(|(line_id, data)| {
+            ranking::calculate_monthly_rankings(
+                &data.files,
+                Some(&data.gap_report),
+                data.anomaly_report.as_ref(),
+                data.member_report.as_ref(),
+                line_id,
+                &scan_options.dir_pattern,
+                start_date,
+                end_date,
+                tiny_threshold,
+            )
+        })
+        .collect();
 
     // Combine and print
-    let combined = ranking::combine_rankings(&ranking_a, &ranking_b);
+    let combined = ranking::combine_rankings(&rankings);
+    if args.output_format() == OutputFormat::Json {
+        println!("{}", json_report::render_rankings(&combined, &rankings));
+        return;
+    }
+    if args.output_format() == OutputFormat::Html {
+        println!("{}", html_renderer::render_combined_ranking(&combined));
+        for ranking in &rankings {
+            println!(
+                "<h2>Line {}</h2>\n{}",
+                html_renderer::escape_html(&ranking.line_id),
+                html_renderer::render_monthly_ranking(ranking)
+            );
+        }
+        return;
+    }
     ranking::print_combined_rankings(&combined);
 
     // Also print individual line summaries
-    println!("\n{}", "=== Line A Rankings ===".cyan());
-    ranking::print_monthly_rankings(&ranking_a);
-
-    println!("\n{}", "=== Line B Rankings ===".cyan());
-    ranking::print_monthly_rankings(&ranking_b);
+    for ranking in &rankings {
+        println!(
+            "\n{}",
+            format!("=== Line {} Rankings ===", ranking.line_id).cyan()
+        );
+        ranking::print_monthly_rankings(ranking);
+    }
 
     println!("\n{}", "=== Ranking Complete ===".cyan());
 }
@@ -555,34 +1103,28 @@ struct RankingData {
     files: Vec<types::FileEntry>,
     gap_report: gap_analysis::GapReport,
     anomaly_report: Option<stats::AnomalyReport>,
+    member_report: Option<members::MemberSetReport>,
 }
 
 fn collect_ranking_data(
     line_id: &str,
     base_dir: &str,
     anomaly_threshold: f64,
-    _start_date: NaiveDate,
-    _end_date: NaiveDate,
-    _tiny_threshold: u64,
+    scan_options: &scanner::ScanOptions,
 ) -> RankingData {
     let search_dir = format!("{}/Line {}", base_dir, line_id);
 
-    let (_size_t1, dirs_t1) = scanner::get_total_and_per_dir_sizes(&search_dir);
-    thread::sleep(Duration::from_secs(10));
-    let (_, dirs_t2) = scanner::get_total_and_per_dir_sizes(&search_dir);
-
-    let files = scanner::scan_files(&search_dir);
+    // Read-only: a one-off ranking must not reset the snapshot that the
+    // regular runs measure speed against
+    let sample = transfer::TransferSample::against_snapshot(
+        &search_dir,
+        &scan_options.dir_pattern,
+        &transfer::snapshot_file_path(base_dir, line_id, transfer::RunKind::Line),
+    );
+    let files = scanner::scan_files(&search_dir, &scan_options.for_line(base_dir, line_id));
 
     // Identify growing directories
-    let growing_dirs: HashSet<String> = dirs_t1
-        .keys()
-        .filter(|dir| {
-            let size_t1 = dirs_t1.get(*dir).unwrap_or(&0);
-            let size_t2 = dirs_t2.get(*dir).unwrap_or(&0);
-            size_t2 > size_t1
-        })
-        .cloned()
-        .collect();
+    let growing_dirs = sample.growing_dirs(line_id);
 
     // Filter out files from growing directories
     let analysis_files: Vec<_> = files
@@ -591,404 +1133,55 @@ fn collect_ranking_data(
         .cloned()
         .collect();
 
-    let gap_report = gap_analysis::find_gaps(&files, line_id);
+    let gap_report = gap_analysis::find_gaps(
+        &files,
+        line_id,
+        &scan_options.dir_pattern,
+        &calendar::load_holidays(base_dir),
+    );
 
-    // Filter dirs for anomaly detection
-    let stable_dirs: HashMap<String, u64> = dirs_t2
-        .iter()
-        .filter(|(dir, _)| !growing_dirs.contains(*dir))
-        .map(|(k, v)| (k.clone(), *v))
-        .collect();
-    let anomaly_report = stats::calculate_anomalies(&stable_dirs, anomaly_threshold);
+    // Exclude growing directories from anomaly detection
+    let anomaly_report =
+        stats::calculate_anomalies(&sample.after.stable_dirs(&growing_dirs), anomaly_threshold);
+    let member_report = members::calculate_member_sets(
+        &analysis_files,
+        line_id,
+        &scan_options.dir_pattern,
+        members::load_expected_members(base_dir, line_id),
+    );
 
     RankingData {
         files: analysis_files,
         gap_report,
         anomaly_report,
+        member_report,
     }
 }
 
-fn acquire_lock(lockfile: &str) -> Result<fs::File, String> {
-    use std::io::ErrorKind;
+fn test_email_config(base_dir: &str) {
+    println!("Testing email configuration...");
 
-    // Try to create lockfile exclusively (fails if exists)
-    match OpenOptions::new()
-        .write(true)
-        .create_new(true)
-        .mode(0o644)
-        .open(lockfile)
-    {
-        Ok(mut file) => {
-            // Write PID to lockfile
-            let pid = std::process::id();
-            writeln!(file, "{pid}").ok();
-            Ok(file)
+    // Try to load the email config
+    let config_path = format!("{}/.email_config", base_dir);
+    println!("Looking for config at: {}", config_path);
+
+    let config = match email::EmailConfig::load(base_dir) {
+        Some(cfg) => {
+            println!("✓ Email config loaded successfully");
+            println!("  SMTP User: {}", cfg.smtp_user);
+            println!("  Recipient: {}", cfg.recipient);
+            cfg
         }
-        Err(e) if e.kind() == ErrorKind::AlreadyExists => {
-            Err("Lockfile already exists (another instance running)".to_string())
+        None => {
+            eprintln!("✗ Failed to load email config from .email_config");
+            eprintln!("  Make sure the file exists at: {}", config_path);
+            eprintln!("  Expected format:");
+            eprintln!("    SMTP_USER=your-email@gmail.com");
+            eprintln!("    SMTP_PASS=your-app-password");
+            eprintln!("    RECIPIENT_EMAIL=recipient@example.com");
+            std::process::exit(1);
         }
-        Err(e) => Err(format!("Failed to create lockfile: {e}")),
-    }
-}
-
-fn collect_audit_data(
-    line_id: &str,
-    base_dir: &str,
-    alert_threshold: u64,
-    max_bad_per_archive: usize,
-    anomaly_threshold: f64,
-    start_date: NaiveDate,
-    end_date: NaiveDate,
-) -> html_renderer::AuditReport {
-    let search_dir = format!("{}/Line {}", base_dir, line_id);
-    let tiny_threshold = 1000;
-
-    let (size_t1, dirs_t1) = scanner::get_total_and_per_dir_sizes(&search_dir);
-
-    thread::sleep(Duration::from_secs(10));
-    let (size_t2, dirs_t2) = scanner::get_total_and_per_dir_sizes(&search_dir);
-
-    let files = scanner::scan_files(&search_dir);
-    let total_zip_files = files.len();
-
-    // Identify growing directories (actively being copied)
-    let growing_dirs: HashSet<String> = dirs_t1
-        .keys()
-        .filter(|dir| {
-            let size_t1 = dirs_t1.get(*dir).unwrap_or(&0);
-            let size_t2 = dirs_t2.get(*dir).unwrap_or(&0);
-            let is_growing = size_t2 > size_t1;
-            if is_growing {
-                debug!(
-                    "Line {}: Growing directory detected: {} ({} -> {} bytes, delta: {} bytes)",
-                    line_id,
-                    dir,
-                    size_t1,
-                    size_t2,
-                    size_t2.saturating_sub(*size_t1)
-                );
-            }
-            is_growing
-        })
-        .cloned()
-        .collect();
-
-    debug!(
-        "Line {}: Total directories tracked: {}",
-        line_id,
-        dirs_t1.len()
-    );
-    debug!(
-        "Line {}: Growing directories: {}",
-        line_id,
-        growing_dirs.len()
-    );
-    debug!("Line {}: Growing dirs: {:?}", line_id, growing_dirs);
-
-    // Create filtered file list for analysis (exclude files from growing directories)
-    let analysis_files: Vec<_> = files
-        .iter()
-        .filter(|f| !growing_dirs.contains(&f.parent_dir))
-        .cloned()
-        .collect();
-
-    debug!(
-        "Line {}: Total files: {}, Analysis files: {}",
-        line_id,
-        files.len(),
-        analysis_files.len()
-    );
-
-    let delta_bytes = size_t2.saturating_sub(size_t1);
-    let speed_bps = delta_bytes / 10;
-
-    let state_file = format!("{}/.transfer_state_{}", base_dir, line_id);
-    let since_file = format!("{}/.transfer_since_{}", base_dir, line_id);
-
-    let current_state = if speed_bps > 0 { "ACTIVE" } else { "IDLE" };
-    let prev_state = fs::read_to_string(&state_file).unwrap_or_else(|_| "IDLE".to_string());
-    let prev_state = prev_state.trim();
-    debug!("Line {}: Read previous state: {}", line_id, prev_state);
-
-    if current_state != prev_state || !std::path::Path::new(&since_file).exists() {
-        let now_str = Local::now().format("%Y-%m-%d %H:%M").to_string();
-        debug!("Line {}: Writing since timestamp: {}", line_id, now_str);
-        fs::write(&since_file, &now_str).ok();
-    }
-    let since_ts = fs::read_to_string(&since_file).unwrap_or_default();
-    let since_ts = since_ts.trim().to_string();
-    debug!("Line {}: Read since timestamp: {}", line_id, since_ts);
-
-    let recents = scanner::get_recent_files(&search_dir, 5);
-
-    // State change handling (email alerts) - use consolidated alert function
-    let speed_mib = speed_bps as f64 / 1_024.0 / 1_024.0;
-    check_and_send_alerts(
-        base_dir,
-        line_id,
-        current_state,
-        prev_state,
-        speed_mib,
-        alert_threshold,
-    );
-
-    let integrity_stats = stats::calculate_integrity_stats(&analysis_files, tiny_threshold);
-    let gap_report = gap_analysis::find_gaps(&files, line_id);
-    let estimates_report = estimates::calculate_estimates(
-        &search_dir,
-        &analysis_files,
-        &files,
-        &growing_dirs,
-        line_id,
-        size_t2,
-        speed_bps,
-        start_date,
-        end_date,
-    );
-    // Filter dirs_t2 to exclude growing directories for anomaly detection
-    let stable_dirs: HashMap<String, u64> = dirs_t2
-        .iter()
-        .filter(|(dir, _)| !growing_dirs.contains(*dir))
-        .map(|(k, v)| (k.clone(), *v))
-        .collect();
-    let anomalies_report = stats::calculate_anomalies(&stable_dirs, anomaly_threshold);
-    let bad_files_report = stats::collect_bad_files(&analysis_files, line_id, max_bad_per_archive);
-
-    // Return AuditReport
-    html_renderer::AuditReport {
-        total_size: size_t2,
-        total_files: total_zip_files,
-        speed_bps,
-        since_timestamp: since_ts,
-        recent_files: recents,
-        redundancy_check: None,
-        integrity_stats,
-        gap_report: Some(gap_report),
-        estimates_report,
-        anomaly_report: anomalies_report,
-        bad_files_report,
-        max_bad_per_archive,
-    }
-}
-
-fn log_state_change(
-    base_dir: &str,
-    line_id: &str,
-    old_state: &str,
-    new_state: &str,
-    speed_mbps: f64,
-) {
-    let log_file = format!("{}/.transfer_interruptions_{}", base_dir, line_id);
-    let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S");
-    let log_entry = format!(
-        "{},{},{},{:.1}\n",
-        timestamp, old_state, new_state, speed_mbps
-    );
-
-    debug!(
-        "Line {}: Appending to interruption log: {}",
-        line_id, log_file
-    );
-    if let Ok(mut file) = OpenOptions::new().create(true).append(true).open(&log_file) {
-        let _ = file.write_all(log_entry.as_bytes());
-        debug!("Line {}: Interruption log entry written", line_id);
-    } else {
-        error!("Line {}: Failed to write to interruption log", line_id);
-    }
-}
-
-// ============================================================================
-// Pure functions for alert logic (testable without I/O)
-// ============================================================================
-
-#[derive(Debug, PartialEq)]
-enum AlertAction {
-    NoAction,         // State unchanged, no pending alert
-    CreateTimestamp,  // State just changed, start tracking
-    WaitForThreshold, // Waiting for threshold to pass
-    SendAlert {
-        // Time to send alert
-        minutes_elapsed: i64,
-    },
-}
-
-struct EmailContent {
-    subject: String,
-    body: String,
-}
-
-struct AlertFiles {
-    state_file: String,
-    timestamp_file: String,
-}
-
-fn get_alert_file_paths(base_dir: &str, line_id: &str) -> AlertFiles {
-    AlertFiles {
-        state_file: format!("{}/.transfer_state_{}", base_dir, line_id),
-        timestamp_file: format!("{}/.transfer_state_changed_{}", base_dir, line_id),
-    }
-}
-
-fn create_alert_email(
-    line_id: &str,
-    current_state: &str,
-    speed_mib: f64,
-    minutes_elapsed: i64,
-    current_time: chrono::DateTime<Local>,
-) -> EmailContent {
-    let action = if current_state == "ACTIVE" {
-        "RESUMED"
-    } else {
-        "STOPPED"
-    };
-    let action_lower = if current_state == "ACTIVE" {
-        "resumed"
-    } else {
-        "stopped"
-    };
-
-    EmailContent {
-        subject: format!("[Beam Alert] Transfer {} on Line {}", action, line_id),
-        body: format!(
-            "The transfer on Line {} has {}.\n\nCurrent Speed: {:.1} MiB/s\nState persisted for: {} minutes\nTime: {}",
-            line_id, action_lower, speed_mib, minutes_elapsed, current_time
-        ),
-    }
-}
-
-fn determine_alert_action(
-    current_state: &str,
-    prev_state: &str,
-    timestamp_file_content: Option<&str>,
-    threshold_minutes: u64,
-    current_time: chrono::DateTime<Local>,
-) -> AlertAction {
-    // State just changed - create timestamp to start tracking
-    if current_state != prev_state {
-        return AlertAction::CreateTimestamp;
-    }
-
-    // No timestamp file exists and state hasn't changed - nothing to do
-    let Some(content) = timestamp_file_content else {
-        return AlertAction::NoAction;
-    };
-
-    // Try to parse the timestamp and calculate elapsed time
-    let Some(minutes_elapsed) = parse_timestamp_and_get_elapsed_minutes(content, current_time)
-    else {
-        // Invalid timestamp format - treat as no action
-        return AlertAction::NoAction;
-    };
-
-    // Check if threshold has been met
-    if minutes_elapsed >= threshold_minutes as i64 {
-        AlertAction::SendAlert { minutes_elapsed }
-    } else {
-        AlertAction::WaitForThreshold
-    }
-}
-
-/// Check and send email alerts based on state changes with threshold
-fn check_and_send_alerts(
-    base_dir: &str,
-    line_id: &str,
-    current_state: &str,
-    prev_state: &str,
-    speed_mib: f64,
-    alert_threshold: u64,
-) {
-    let files = get_alert_file_paths(base_dir, line_id);
-    debug!("Line {}: Reading timestamp file for alert check", line_id);
-    let timestamp_content = fs::read_to_string(&files.timestamp_file).ok();
-
-    // Use pure function to determine what action to take
-    let action = determine_alert_action(
-        current_state,
-        prev_state,
-        timestamp_content.as_deref(),
-        alert_threshold,
-        Local::now(),
-    );
-
-    // Execute the determined action (this is where I/O happens)
-    match action {
-        AlertAction::NoAction => {
-            // State unchanged, no pending alert - just update state file
-            debug!("Line {}: State unchanged ({})", line_id, current_state);
-            debug!("Line {}: Writing state file", line_id);
-            fs::write(&files.state_file, current_state).ok();
-        }
-        AlertAction::CreateTimestamp => {
-            // State just changed - log it and create timestamp
-            info!(
-                "Line {}: State changed {} → {} ({:.1} MiB/s)",
-                line_id, prev_state, current_state, speed_mib
-            );
-            log_state_change(base_dir, line_id, prev_state, current_state, speed_mib);
-            let timestamp_str = Local::now().format("%Y-%m-%d %H:%M").to_string();
-            debug!("Line {}: Created state change timestamp file", line_id);
-            debug!("Line {}: Writing timestamp and state files", line_id);
-            fs::write(&files.timestamp_file, timestamp_str).ok();
-            fs::write(&files.state_file, current_state).ok();
-        }
-        AlertAction::WaitForThreshold => {
-            // Waiting for threshold - just update state file
-            debug!(
-                "Line {}: Waiting for alert threshold (state: {})",
-                line_id, current_state
-            );
-            debug!("Line {}: Writing state file", line_id);
-            fs::write(&files.state_file, current_state).ok();
-        }
-        AlertAction::SendAlert { minutes_elapsed } => {
-            // Threshold met - send alert and clean up
-            info!(
-                "Line {}: Alert threshold reached ({} minutes in {} state)",
-                line_id, minutes_elapsed, current_state
-            );
-            if let Some(cfg) = email::EmailConfig::load(base_dir) {
-                let email = create_alert_email(
-                    line_id,
-                    current_state,
-                    speed_mib,
-                    minutes_elapsed,
-                    Local::now(),
-                );
-                email::send_alert(&email.subject, &email.body, &cfg);
-                info!("Line {}: Alert email sent", line_id);
-            } else {
-                warn!("Line {}: Email config not found, skipping alert", line_id);
-            }
-            debug!("Line {}: Removed state change timestamp file", line_id);
-            fs::remove_file(&files.timestamp_file).ok();
-        }
-    }
-}
-
-fn test_email_config(base_dir: &str) {
-    println!("Testing email configuration...");
-
-    // Try to load the email config
-    let config_path = format!("{}/.email_config", base_dir);
-    println!("Looking for config at: {}", config_path);
-
-    let config = match email::EmailConfig::load(base_dir) {
-        Some(cfg) => {
-            println!("✓ Email config loaded successfully");
-            println!("  SMTP User: {}", cfg.smtp_user);
-            println!("  Recipient: {}", cfg.recipient);
-            cfg
-        }
-        None => {
-            eprintln!("✗ Failed to load email config from .email_config");
-            eprintln!("  Make sure the file exists at: {}", config_path);
-            eprintln!("  Expected format:");
-            eprintln!("    SMTP_USER=your-email@gmail.com");
-            eprintln!("    SMTP_PASS=your-app-password");
-            eprintln!("    RECIPIENT_EMAIL=recipient@example.com");
-            std::process::exit(1);
-        }
-    };
+    };
 
     println!("\nSending test email...");
     let subject = "[Beam Audit] Test Email";
@@ -1000,514 +1193,3 @@ fn test_email_config(base_dir: &str) {
     email::send_alert(subject, &body, &config);
     println!("\nTest complete. Check your inbox at: {}", config.recipient);
 }
-
-/// Parse a human-readable timestamp and calculate minutes elapsed since then
-fn parse_timestamp_and_get_elapsed_minutes(
-    timestamp_str: &str,
-    current_time: chrono::DateTime<Local>,
-) -> Option<i64> {
-    let change_time =
-        chrono::NaiveDateTime::parse_from_str(timestamp_str.trim(), "%Y-%m-%d %H:%M").ok()?;
-
-    match chrono::TimeZone::from_local_datetime(&Local, &change_time) {
-        chrono::LocalResult::Single(change_datetime)
-        | chrono::LocalResult::Ambiguous(change_datetime, _) => Some(
-            current_time
-                .signed_duration_since(change_datetime)
-                .num_minutes(),
-        ),
-        chrono::LocalResult::None => None,
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use chrono::{Duration, TimeZone};
-
-    #[test]
-    fn test_parse_timestamp_format() {
-        // Test valid timestamp format
-        let valid_timestamp = "2026-01-22 04:05";
-        let result = chrono::NaiveDateTime::parse_from_str(valid_timestamp, "%Y-%m-%d %H:%M");
-        assert!(result.is_ok());
-    }
-
-    #[test]
-    fn test_parse_timestamp_invalid_format() {
-        // Test invalid formats
-        let invalid_formats = vec![
-            "2026-01-22",          // Missing time
-            "04:05",               // Missing date
-            "2026/01/22 04:05",    // Wrong separator
-            "22-01-2026 04:05",    // Wrong date order
-            "2026-01-22 04:05:30", // Has seconds (we don't use them)
-        ];
-
-        for invalid in invalid_formats {
-            let result = chrono::NaiveDateTime::parse_from_str(invalid, "%Y-%m-%d %H:%M");
-            assert!(result.is_err(), "Should reject format: {}", invalid);
-        }
-    }
-
-    #[test]
-    fn test_timestamp_elapsed_calculation() {
-        // Create a timestamp from 30 minutes ago
-        let now = Local::now();
-        let past_time = now - Duration::minutes(30);
-        let timestamp_str = past_time.format("%Y-%m-%d %H:%M").to_string();
-
-        if let Some(elapsed) = parse_timestamp_and_get_elapsed_minutes(&timestamp_str, now) {
-            // Should be approximately 30 minutes (allow small variance for test execution time)
-            assert!(
-                (29..=31).contains(&elapsed),
-                "Expected ~30 minutes, got {}",
-                elapsed
-            );
-        } else {
-            panic!("Should successfully parse timestamp");
-        }
-    }
-
-    #[test]
-    fn test_timestamp_threshold_check() {
-        // Test that 20-minute threshold logic works
-        let threshold = 20i64;
-
-        // 25 minutes ago - should trigger alert
-        let now = Local::now();
-        let past_time = now - Duration::minutes(25);
-        let timestamp_str = past_time.format("%Y-%m-%d %H:%M").to_string();
-        if let Some(elapsed) = parse_timestamp_and_get_elapsed_minutes(&timestamp_str, now) {
-            assert!(
-                elapsed >= threshold,
-                "25 minutes should exceed 20-minute threshold"
-            );
-        }
-
-        // 15 minutes ago - should NOT trigger alert
-        let recent_time = now - Duration::minutes(15);
-        let timestamp_str = recent_time.format("%Y-%m-%d %H:%M").to_string();
-        if let Some(elapsed) = parse_timestamp_and_get_elapsed_minutes(&timestamp_str, now) {
-            assert!(
-                elapsed < threshold,
-                "15 minutes should be under 20-minute threshold"
-            );
-        }
-    }
-
-    #[test]
-    fn test_timestamp_format_consistency() {
-        // Ensure writing and reading timestamps produces consistent format
-        let now = Local::now();
-        let written_format = now.format("%Y-%m-%d %H:%M").to_string();
-
-        // Should be able to parse what we write
-        let parsed = chrono::NaiveDateTime::parse_from_str(&written_format, "%Y-%m-%d %H:%M");
-        assert!(parsed.is_ok());
-
-        // Verify format matches expected pattern (YYYY-MM-DD HH:MM)
-        assert_eq!(written_format.len(), 16); // "2026-01-22 04:05" is 16 chars
-        assert_eq!(written_format.chars().nth(4), Some('-'));
-        assert_eq!(written_format.chars().nth(7), Some('-'));
-        assert_eq!(written_format.chars().nth(10), Some(' '));
-        assert_eq!(written_format.chars().nth(13), Some(':'));
-    }
-
-    // Tests for pure alert logic functions
-
-    #[test]
-    fn test_get_alert_file_paths() {
-        let files = get_alert_file_paths("/data/storage", "B");
-        assert_eq!(files.state_file, "/data/storage/.transfer_state_B");
-        assert_eq!(
-            files.timestamp_file,
-            "/data/storage/.transfer_state_changed_B"
-        );
-    }
-
-    #[test]
-    fn test_create_alert_email_stopped() {
-        let now = Local.with_ymd_and_hms(2026, 1, 22, 12, 0, 0).unwrap();
-        let email = create_alert_email("B", "IDLE", 0.0, 25, now);
-
-        assert_eq!(email.subject, "[Beam Alert] Transfer STOPPED on Line B");
-        assert!(email.body.contains("stopped"));
-        assert!(email.body.contains("25 minutes"));
-        assert!(email.body.contains("0.0 MiB/s"));
-    }
-
-    #[test]
-    fn test_create_alert_email_resumed() {
-        let now = Local.with_ymd_and_hms(2026, 1, 22, 12, 0, 0).unwrap();
-        let email = create_alert_email("A", "ACTIVE", 125.5, 30, now);
-
-        assert_eq!(email.subject, "[Beam Alert] Transfer RESUMED on Line A");
-        assert!(email.body.contains("resumed"));
-        assert!(email.body.contains("30 minutes"));
-        assert!(email.body.contains("125.5 MiB/s"));
-    }
-
-    #[test]
-    fn test_determine_alert_action_no_change_no_timestamp() {
-        let now = Local.with_ymd_and_hms(2026, 1, 22, 12, 0, 0).unwrap();
-        let action = determine_alert_action("IDLE", "IDLE", None, 20, now);
-        assert_eq!(action, AlertAction::NoAction);
-    }
-
-    #[test]
-    fn test_determine_alert_action_state_change() {
-        let now = Local.with_ymd_and_hms(2026, 1, 22, 12, 0, 0).unwrap();
-        let action = determine_alert_action("IDLE", "ACTIVE", None, 20, now);
-        assert_eq!(action, AlertAction::CreateTimestamp);
-    }
-
-    #[test]
-    fn test_determine_alert_action_under_threshold() {
-        let now = Local.with_ymd_and_hms(2026, 1, 22, 12, 0, 0).unwrap();
-        let timestamp = "2026-01-22 11:50"; // 10 minutes ago
-        let action = determine_alert_action("IDLE", "IDLE", Some(timestamp), 20, now);
-        assert_eq!(action, AlertAction::WaitForThreshold);
-    }
-
-    #[test]
-    fn test_determine_alert_action_over_threshold() {
-        let now = Local.with_ymd_and_hms(2026, 1, 22, 12, 0, 0).unwrap();
-        let timestamp = "2026-01-22 11:30"; // 30 minutes ago
-        let action = determine_alert_action("IDLE", "IDLE", Some(timestamp), 20, now);
-        assert_eq!(
-            action,
-            AlertAction::SendAlert {
-                minutes_elapsed: 30
-            }
-        );
-    }
-
-    #[test]
-    fn test_determine_alert_action_exactly_at_threshold() {
-        let now = Local.with_ymd_and_hms(2026, 1, 22, 12, 20, 0).unwrap();
-        let timestamp = "2026-01-22 12:00"; // Exactly 20 minutes ago
-        let action = determine_alert_action("IDLE", "IDLE", Some(timestamp), 20, now);
-        assert_eq!(
-            action,
-            AlertAction::SendAlert {
-                minutes_elapsed: 20
-            }
-        );
-    }
-
-    #[test]
-    fn test_determine_alert_action_invalid_timestamp() {
-        let now = Local.with_ymd_and_hms(2026, 1, 22, 12, 0, 0).unwrap();
-        let invalid_timestamp = "invalid-format";
-        let action = determine_alert_action("IDLE", "IDLE", Some(invalid_timestamp), 20, now);
-        assert_eq!(action, AlertAction::NoAction);
-    }
-
-    #[test]
-    fn test_determine_alert_action_state_change_with_existing_timestamp() {
-        // If state changes while a timestamp exists, should create new timestamp
-        let now = Local.with_ymd_and_hms(2026, 1, 22, 12, 0, 0).unwrap();
-        let old_timestamp = "2026-01-22 11:30"; // 30 minutes ago
-        let action = determine_alert_action("ACTIVE", "IDLE", Some(old_timestamp), 20, now);
-        assert_eq!(action, AlertAction::CreateTimestamp);
-    }
-}
-
-// ============================================================================
-// Integration tests - testing check_and_send_alerts() with real file I/O
-// These tests use the ACTUAL production code path, ensuring no divergence
-// ============================================================================
-
-#[cfg(test)]
-mod integration_tests {
-    use super::*;
-    use chrono::TimeZone;
-    use std::path::Path;
-
-    /// Helper to create a test environment with temp directory
-    /// Returns the TempDir (must keep alive) and base_dir path string
-    fn setup_test_env() -> (tempfile::TempDir, String) {
-        let temp_dir = tempfile::tempdir().unwrap();
-        let base_dir = temp_dir.path().to_str().unwrap().to_string();
-        (temp_dir, base_dir)
-    }
-
-    #[test]
-    fn test_no_action_when_state_unchanged() {
-        let (_temp, base_dir) = setup_test_env();
-
-        // Setup: Create initial IDLE state
-        let state_file = format!("{}/.transfer_state_B", base_dir);
-        fs::write(&state_file, "IDLE").unwrap();
-
-        // Action: Call with unchanged state (IDLE -> IDLE)
-        check_and_send_alerts(&base_dir, "B", "IDLE", "IDLE", 0.0, 20);
-
-        // Assert: No timestamp file created
-        let timestamp_file = format!("{}/.transfer_state_changed_B", base_dir);
-        assert!(
-            !Path::new(&timestamp_file).exists(),
-            "Timestamp file should not exist for NoAction"
-        );
-
-        // Assert: No interruption log created
-        let log_file = format!("{}/.transfer_interruptions_B", base_dir);
-        assert!(
-            !Path::new(&log_file).exists(),
-            "Log file should not exist for NoAction"
-        );
-
-        // Assert: State file updated
-        let content = fs::read_to_string(&state_file).unwrap();
-        assert_eq!(content, "IDLE");
-    }
-
-    #[test]
-    fn test_creates_timestamp_on_state_change() {
-        let (_temp, base_dir) = setup_test_env();
-
-        // Setup: Start with IDLE state
-        let state_file = format!("{}/.transfer_state_B", base_dir);
-        fs::write(&state_file, "IDLE").unwrap();
-
-        // Action: State changes to ACTIVE
-        check_and_send_alerts(&base_dir, "B", "ACTIVE", "IDLE", 125.5, 20);
-
-        // Assert: Timestamp file was created
-        let timestamp_file = format!("{}/.transfer_state_changed_B", base_dir);
-        assert!(
-            Path::new(&timestamp_file).exists(),
-            "Timestamp file must be created on state change"
-        );
-
-        // Assert: Timestamp content is valid format
-        let content = fs::read_to_string(&timestamp_file).unwrap();
-        let parsed = chrono::NaiveDateTime::parse_from_str(content.trim(), "%Y-%m-%d %H:%M");
-        assert!(
-            parsed.is_ok(),
-            "Timestamp should be in correct format: {}",
-            content
-        );
-
-        // Assert: State file updated
-        let state_content = fs::read_to_string(&state_file).unwrap();
-        assert_eq!(state_content, "ACTIVE");
-
-        // Assert: Interruption log was created
-        let log_file = format!("{}/.transfer_interruptions_B", base_dir);
-        assert!(
-            Path::new(&log_file).exists(),
-            "Interruption log must be created on state change"
-        );
-    }
-
-    #[test]
-    fn test_log_state_change_format() {
-        let (_temp, base_dir) = setup_test_env();
-
-        // Setup
-        let state_file = format!("{}/.transfer_state_B", base_dir);
-        fs::write(&state_file, "ACTIVE").unwrap();
-
-        // Action: State change from ACTIVE to IDLE
-        check_and_send_alerts(&base_dir, "B", "IDLE", "ACTIVE", 0.0, 20);
-
-        // Assert: Log file has correct CSV format
-        let log_file = format!("{}/.transfer_interruptions_B", base_dir);
-        let log_content = fs::read_to_string(&log_file).unwrap();
-
-        // Should have format: timestamp,old_state,new_state,speed
-        // Example: 2026-01-22 10:15:30,ACTIVE,IDLE,0.0
-        let lines: Vec<&str> = log_content.lines().collect();
-        assert_eq!(lines.len(), 1, "Should have exactly one log entry");
-
-        let parts: Vec<&str> = lines[0].split(',').collect();
-        assert_eq!(
-            parts.len(),
-            4,
-            "CSV should have 4 fields: timestamp,old,new,speed"
-        );
-        assert_eq!(parts[1], "ACTIVE", "Old state should be ACTIVE");
-        assert_eq!(parts[2], "IDLE", "New state should be IDLE");
-        assert_eq!(parts[3], "0.0", "Speed should be 0.0");
-
-        // Verify timestamp format (YYYY-MM-DD HH:MM:SS)
-        assert!(
-            parts[0].contains('-') && parts[0].contains(':'),
-            "Timestamp should contain date and time separators"
-        );
-    }
-
-    #[test]
-    fn test_waits_for_threshold() {
-        let (_temp, base_dir) = setup_test_env();
-
-        // Setup: Create timestamp from 10 minutes ago (under 20 min threshold)
-        let timestamp_file = format!("{}/.transfer_state_changed_B", base_dir);
-        let past_time = Local::now() - chrono::Duration::minutes(10);
-        fs::write(
-            &timestamp_file,
-            past_time.format("%Y-%m-%d %H:%M").to_string(),
-        )
-        .unwrap();
-
-        let state_file = format!("{}/.transfer_state_B", base_dir);
-        fs::write(&state_file, "IDLE").unwrap();
-
-        // Action: Run with same state (IDLE -> IDLE)
-        check_and_send_alerts(&base_dir, "B", "IDLE", "IDLE", 0.0, 20);
-
-        // Assert: Timestamp file still exists (not deleted, waiting for threshold)
-        assert!(
-            Path::new(&timestamp_file).exists(),
-            "Timestamp file should remain while waiting"
-        );
-
-        // Assert: Original timestamp unchanged
-        let content = fs::read_to_string(&timestamp_file).unwrap();
-        let expected = past_time.format("%Y-%m-%d %H:%M").to_string();
-        assert_eq!(
-            content.trim(),
-            expected.trim(),
-            "Timestamp should not be modified"
-        );
-    }
-
-    #[test]
-    fn test_sends_alert_after_threshold() {
-        let (_temp, base_dir) = setup_test_env();
-
-        // Setup: Create timestamp from 25 minutes ago (over 20 min threshold)
-        let timestamp_file = format!("{}/.transfer_state_changed_B", base_dir);
-        let past_time = Local::now() - chrono::Duration::minutes(25);
-        fs::write(
-            &timestamp_file,
-            past_time.format("%Y-%m-%d %H:%M").to_string(),
-        )
-        .unwrap();
-
-        let state_file = format!("{}/.transfer_state_B", base_dir);
-        fs::write(&state_file, "IDLE").unwrap();
-
-        // Action: Run with same state (IDLE -> IDLE)
-        // Note: Email won't actually send without .email_config, but file operations still happen
-        check_and_send_alerts(&base_dir, "B", "IDLE", "IDLE", 0.0, 20);
-
-        // Assert: Timestamp file was DELETED after alert
-        assert!(
-            !Path::new(&timestamp_file).exists(),
-            "Timestamp file must be deleted after sending alert"
-        );
-
-        // Assert: State file still updated
-        let state_content = fs::read_to_string(&state_file).unwrap();
-        assert_eq!(state_content, "IDLE");
-    }
-
-    #[test]
-    fn test_state_change_overrides_pending_alert() {
-        let (_temp, base_dir) = setup_test_env();
-
-        // Setup: Create old timestamp from 25 minutes ago (over threshold)
-        let timestamp_file = format!("{}/.transfer_state_changed_B", base_dir);
-        let old_time = Local::now() - chrono::Duration::minutes(25);
-        fs::write(
-            &timestamp_file,
-            old_time.format("%Y-%m-%d %H:%M").to_string(),
-        )
-        .unwrap();
-        let old_timestamp_content = fs::read_to_string(&timestamp_file).unwrap();
-
-        let state_file = format!("{}/.transfer_state_B", base_dir);
-        fs::write(&state_file, "IDLE").unwrap();
-
-        // Action: State changes (IDLE -> ACTIVE) - should override pending alert
-        check_and_send_alerts(&base_dir, "B", "ACTIVE", "IDLE", 120.0, 20);
-
-        // Assert: Timestamp file still exists but with NEW timestamp
-        assert!(
-            Path::new(&timestamp_file).exists(),
-            "Timestamp file should be recreated"
-        );
-        let new_timestamp_content = fs::read_to_string(&timestamp_file).unwrap();
-        assert_ne!(
-            old_timestamp_content.trim(),
-            new_timestamp_content.trim(),
-            "Timestamp should be updated to current time"
-        );
-
-        // Assert: New timestamp is recent (within last minute)
-        let parsed =
-            chrono::NaiveDateTime::parse_from_str(new_timestamp_content.trim(), "%Y-%m-%d %H:%M")
-                .unwrap();
-        let as_datetime = Local.from_local_datetime(&parsed).unwrap();
-        let elapsed = Local::now()
-            .signed_duration_since(as_datetime)
-            .num_minutes();
-        assert!(
-            elapsed <= 1,
-            "New timestamp should be very recent, got {} minutes",
-            elapsed
-        );
-    }
-
-    #[test]
-    fn test_handles_corrupted_timestamp_file() {
-        let (_temp, base_dir) = setup_test_env();
-
-        // Setup: Create timestamp file with invalid content
-        let timestamp_file = format!("{}/.transfer_state_changed_B", base_dir);
-        fs::write(&timestamp_file, "CORRUPTED###DATA!!!").unwrap();
-
-        let state_file = format!("{}/.transfer_state_B", base_dir);
-        fs::write(&state_file, "IDLE").unwrap();
-
-        // Action: Should handle gracefully (NoAction due to parse failure)
-        check_and_send_alerts(&base_dir, "B", "IDLE", "IDLE", 0.0, 20);
-
-        // Assert: Doesn't panic or crash
-        // Assert: Timestamp file still exists (not cleaned up on parse error)
-        assert!(
-            Path::new(&timestamp_file).exists(),
-            "Corrupted file should remain"
-        );
-
-        // Assert: State file still updated normally
-        let state_content = fs::read_to_string(&state_file).unwrap();
-        assert_eq!(state_content, "IDLE");
-    }
-
-    #[test]
-    fn test_multiple_state_changes_log_accumulation() {
-        let (_temp, base_dir) = setup_test_env();
-
-        let state_file = format!("{}/.transfer_state_B", base_dir);
-
-        // Change 1: IDLE -> ACTIVE
-        fs::write(&state_file, "IDLE").unwrap();
-        check_and_send_alerts(&base_dir, "B", "ACTIVE", "IDLE", 100.0, 20);
-
-        // Change 2: ACTIVE -> IDLE
-        check_and_send_alerts(&base_dir, "B", "IDLE", "ACTIVE", 0.0, 20);
-
-        // Change 3: IDLE -> ACTIVE again
-        check_and_send_alerts(&base_dir, "B", "ACTIVE", "IDLE", 150.0, 20);
-
-        // Assert: Log file has 3 entries
-        let log_file = format!("{}/.transfer_interruptions_B", base_dir);
-        let log_content = fs::read_to_string(&log_file).unwrap();
-        let lines: Vec<&str> = log_content.lines().collect();
-        assert_eq!(
-            lines.len(),
-            3,
-            "Should have 3 log entries for 3 state changes"
-        );
-
-        // Assert: Each entry is properly formatted
-        for line in lines {
-            let parts: Vec<&str> = line.split(',').collect();
-            assert_eq!(parts.len(), 4, "Each log line should have 4 CSV fields");
-        }
-    }
-}
@@ -0,0 +1,800 @@
+use crate::inventory::{DayStatus, InventoryReport};
+use crate::scanner::{self, ScanOptions};
+use colored::Colorize;
+use log::{debug, info, warn};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use walkdir::WalkDir;
+
+/// Manifest written inside each completed archive directory.
+///
+/// The format is the one produced by `sha256sum`, so a manifest can also be
+/// checked by hand with `sha256sum -c .beam_manifest.sha256`.
+pub const MANIFEST_FILE_NAME: &str = ".beam_manifest.sha256";
+
+/// Without a source inventory, a directory gets its manifest once none of
+/// its files has changed for this long, so a paused transfer is not taken
+/// for a finished one
+const SETTLE_TIME: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManifestIssueKind {
+    /// Content hash differs from the manifest
+    Changed,
+    /// Listed in the manifest but no longer on disk
+    Missing,
+    /// On disk but not listed in the manifest
+    Extra,
+    /// Could not be read to compute its hash
+    Unreadable,
+}
+
+impl fmt::Display for ManifestIssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Self::Changed => "Changed",
+            Self::Missing => "Missing",
+            Self::Extra => "Extra",
+            Self::Unreadable => "Unreadable",
+        };
+        f.write_str(label)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManifestIssue {
+    pub relative_path: String,
+    pub kind: ManifestIssueKind,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ManifestReport {
+    /// Directories that received a new manifest in this run
+    pub created: Vec<String>,
+    /// Directories whose files all match their manifest
+    pub verified: usize,
+    /// Directories skipped because they are still being copied
+    pub skipped_growing: usize,
+    /// Directories without a manifest that may still receive files
+    pub skipped_incomplete: usize,
+    /// Directories with problems, sorted by name
+    pub issues_by_dir: Vec<(String, Vec<ManifestIssue>)>,
+}
+
+impl ManifestReport {
+    #[must_use]
+    pub fn issue_count(&self) -> usize {
+        self.issues_by_dir
+            .iter()
+            .map(|(_, issues)| issues.len())
+            .sum()
+    }
+}
+
+#[must_use]
+pub fn hash_cache_path(base_dir: &str, line_id: &str) -> String {
+    format!("{}/.manifest_cache_{}", base_dir, line_id)
+}
+
+/// One file of an archive directory, as listed before hashing
+struct DirFile {
+    rel: String,
+    path: PathBuf,
+    size: u64,
+    mtime_secs: i64,
+    mtime_nsec: i64,
+}
+
+/// Archive directory to verify against its manifest or to write one for
+struct ManifestDir {
+    name: String,
+    path: PathBuf,
+    files: Vec<DirFile>,
+    expected: Option<BTreeMap<String, String>>,
+}
+
+/// Write manifests for complete archive directories that lack one and
+/// re-verify the directories that already have one.
+///
+/// Directories in `growing_dirs` are skipped entirely: their content is
+/// expected to change until the transfer finishes. A directory only gets a
+/// manifest once it is complete per the source `inventory` or, when the
+/// inventory does not list it, unchanged for a day.
+///
+/// Directories that already have a manifest are always hashed from disk,
+/// so content that changed without a new size or mtime is still caught.
+/// For the others, hashes are kept in `cache_file` and reused while a
+/// file's size and mtime are unchanged (ignored with `--rescan-all`). A
+/// cancelled scan (see `ScanOptions::cancel`) writes nothing and returns an
+/// empty report.
+#[must_use]
+pub fn process_manifests(
+    search_dir: &str,
+    line_id: &str,
+    options: &ScanOptions,
+    growing_dirs: &HashSet<String>,
+    inventory: Option<&InventoryReport>,
+    cache_file: Option<&str>,
+) -> ManifestReport {
+    let mut report = ManifestReport::default();
+    let now = SystemTime::now();
+
+    let mut dirs: Vec<ManifestDir> = Vec::new();
+    for (name, path) in options.dir_pattern.find_dirs(search_dir, line_id) {
+        if growing_dirs.contains(&name) {
+            debug!(
+                "Line {}: Skipping manifest for growing dir {}",
+                line_id, name
+            );
+            report.skipped_growing += 1;
+            continue;
+        }
+        let files = list_files(&path);
+        let expected = read_manifest(&path.join(MANIFEST_FILE_NAME));
+        if expected.is_none() {
+            if files.is_empty() {
+                continue;
+            }
+            if !is_complete(&name, &files, inventory, now) {
+                debug!(
+                    "Line {}: No manifest yet for {}, may still receive files",
+                    line_id, name
+                );
+                report.skipped_incomplete += 1;
+                continue;
+            }
+        }
+        dirs.push(ManifestDir {
+            name,
+            path,
+            files,
+            expected,
+        });
+    }
+
+    // Hash every changed file of every directory on one worker pool
+    let old_cache = match cache_file {
+        Some(cache_file) if !options.rescan_all => HashCache::load(cache_file),
+        _ => HashCache::default(),
+    };
+    let files: Vec<(usize, &DirFile)> = dirs
+        .iter()
+        .enumerate()
+        .flat_map(|(i, dir)| dir.files.iter().map(move |file| (i, file)))
+        .collect();
+    let hashes = scanner::parallel_map(&files, options.threads, |(i, file)| {
+        let cached = dirs[*i]
+            .expected
+            .is_none()
+            .then(|| old_cache.lookup(file))
+            .flatten();
+        match cached {
+            Some(hash) => Ok(hash.to_string()),
+            None if options.cancelled() => Err("Scan stopped".to_string()),
+            None => hash_file::<Sha256>(&file.path).map_err(|e| e.to_string()),
+        }
+    });
+    if options.cancelled() {
+        // Partial hashes must neither become a manifest nor replace the cache
+        info!("Line {}: Manifest check stopped", line_id);
+        return report;
+    }
+
+    let mut new_cache = HashCache::default();
+    let mut actual_by_dir: Vec<BTreeMap<String, Result<String, String>>> =
+        vec![BTreeMap::new(); dirs.len()];
+    for ((i, file), hash) in files.into_iter().zip(hashes) {
+        if let Ok(hash) = &hash {
+            new_cache.insert(file, hash.clone());
+        }
+        actual_by_dir[i].insert(file.rel.clone(), hash);
+    }
+    if let Some(cache_file) = cache_file {
+        new_cache.save(cache_file);
+    }
+
+    for (dir, actual) in dirs.into_iter().zip(actual_by_dir) {
+        let issues = if let Some(expected) = &dir.expected {
+            compare_manifest(expected, &actual)
+        } else {
+            let unreadable = unreadable_issues(&actual);
+            if unreadable.is_empty() {
+                write_manifest(&dir.path.join(MANIFEST_FILE_NAME), &actual);
+                report.created.push(dir.name);
+                continue;
+            }
+            unreadable
+        };
+
+        if issues.is_empty() {
+            report.verified += 1;
+        } else {
+            report.issues_by_dir.push((dir.name, issues));
+        }
+    }
+
+    info!(
+        "Line {}: Manifests: {} verified, {} created, {} with issues, {} skipped (growing), {} not yet complete",
+        line_id,
+        report.verified,
+        report.created.len(),
+        report.issues_by_dir.len(),
+        report.skipped_growing,
+        report.skipped_incomplete
+    );
+
+    report
+}
+
+/// Whether a directory without a manifest has all its files: complete per
+/// the source inventory when it lists the directory, otherwise unchanged
+/// for `SETTLE_TIME`. Files arriving later would show up as `Extra`.
+fn is_complete(
+    name: &str,
+    files: &[DirFile],
+    inventory: Option<&InventoryReport>,
+    now: SystemTime,
+) -> bool {
+    if let Some(day) = inventory.and_then(|r| r.days.iter().find(|d| d.dir_name == name)) {
+        return day.status == DayStatus::Complete;
+    }
+    let settled_before = now
+        .checked_sub(SETTLE_TIME)
+        .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+        .map_or(0, |d| d.as_secs() as i64);
+    files.iter().all(|f| f.mtime_secs < settled_before)
+}
+
+/// All regular files below `dir`, excluding the manifest itself
+fn list_files(dir: &Path) -> Vec<DirFile> {
+    WalkDir::new(dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_map(std::result::Result::ok)
+        .filter(|e| e.file_type().is_file() && !is_manifest_file(e.path()))
+        .filter_map(|e| {
+            let rel = e
+                .path()
+                .strip_prefix(dir)
+                .ok()?
+                .to_string_lossy()
+                .to_string();
+            if rel.contains('\n') {
+                warn!("Skipping file with newline in name: {:?}", e.path());
+                return None;
+            }
+            let metadata = e.metadata().ok()?;
+            Some(DirFile {
+                rel,
+                path: e.into_path(),
+                size: metadata.len(),
+                mtime_secs: metadata.mtime(),
+                mtime_nsec: metadata.mtime_nsec(),
+            })
+        })
+        .collect()
+}
+
+/// SHA-256 of a file as hashed by the previous run
+#[derive(Debug, Clone, PartialEq)]
+struct CachedHash {
+    size: u64,
+    mtime_secs: i64,
+    mtime_nsec: i64,
+    hash: String,
+}
+
+/// Hashes of the previous run keyed by path, size and mtime.
+///
+/// Stored as a tab-separated text file in the base directory:
+/// `path  size  mtime_secs  mtime_nsec  sha256`
+#[derive(Debug, Default)]
+struct HashCache {
+    entries: HashMap<String, CachedHash>,
+}
+
+impl HashCache {
+    /// Load the cache file, returning an empty cache if it is missing or unreadable
+    fn load(cache_file: &str) -> Self {
+        let Ok(content) = fs::read_to_string(cache_file) else {
+            debug!("No manifest hash cache found at {}", cache_file);
+            return Self::default();
+        };
+        let entries = content
+            .lines()
+            .filter_map(|line| {
+                let parts: Vec<&str> = line.split('\t').collect();
+                let [path, size, secs, nsec, hash] = parts[..] else {
+                    return None;
+                };
+                Some((
+                    path.to_string(),
+                    CachedHash {
+                        size: size.parse().ok()?,
+                        mtime_secs: secs.parse().ok()?,
+                        mtime_nsec: nsec.parse().ok()?,
+                        hash: hash.to_string(),
+                    },
+                ))
+            })
+            .collect();
+        Self { entries }
+    }
+
+    /// The cached hash if the file is unchanged
+    fn lookup(&self, file: &DirFile) -> Option<&str> {
+        self.entries
+            .get(file.path.to_string_lossy().as_ref())
+            .filter(|c| {
+                c.size == file.size
+                    && c.mtime_secs == file.mtime_secs
+                    && c.mtime_nsec == file.mtime_nsec
+            })
+            .map(|c| c.hash.as_str())
+    }
+
+    fn insert(&mut self, file: &DirFile, hash: String) {
+        self.entries.insert(
+            file.path.to_string_lossy().to_string(),
+            CachedHash {
+                size: file.size,
+                mtime_secs: file.mtime_secs,
+                mtime_nsec: file.mtime_nsec,
+                hash,
+            },
+        );
+    }
+
+    /// Write the cache atomically (temp file + rename)
+    fn save(&self, cache_file: &str) {
+        let mut paths: Vec<_> = self
+            .entries
+            .keys()
+            .filter(|path| !path.contains(['\t', '\n']))
+            .collect();
+        paths.sort();
+
+        let mut content = String::new();
+        for path in paths {
+            let c = &self.entries[path];
+            content.push_str(&format!(
+                "{}\t{}\t{}\t{}\t{}\n",
+                path, c.size, c.mtime_secs, c.mtime_nsec, c.hash
+            ));
+        }
+
+        let tmp_file = format!("{}.tmp", cache_file);
+        if let Err(e) =
+            fs::write(&tmp_file, content).and_then(|()| fs::rename(&tmp_file, cache_file))
+        {
+            warn!("Failed to write manifest hash cache {}: {}", cache_file, e);
+            fs::remove_file(&tmp_file).ok();
+        }
+    }
+}
+
+/// True for the manifest file and its temporary copy while being written
+#[must_use]
+pub fn is_manifest_file(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|n| n.to_string_lossy().starts_with(MANIFEST_FILE_NAME))
+}
+
+/// Lowercase hex digest of the file content
+pub fn hash_file<D: Digest>(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = D::new();
+    let mut buf = vec![0u8; 1024 * 1024];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+fn read_manifest(path: &Path) -> Option<BTreeMap<String, String>> {
+    let content = fs::read_to_string(path).ok()?;
+    Some(parse_manifest(&content))
+}
+
+/// Parse `sha256sum` output: `<hex>  <path>` (text mode) or `<hex> *<path>` (binary mode)
+fn parse_manifest(content: &str) -> BTreeMap<String, String> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let (hash, rest) = line.split_once(' ')?;
+            let rel = rest.strip_prefix(' ').or_else(|| rest.strip_prefix('*'))?;
+            Some((rel.to_string(), hash.to_ascii_lowercase()))
+        })
+        .collect()
+}
+
+/// Write the manifest atomically (temp file + rename)
+fn write_manifest(path: &Path, hashes: &BTreeMap<String, Result<String, String>>) {
+    let mut content = String::new();
+    for (rel, hash) in hashes {
+        if let Ok(hash) = hash {
+            content.push_str(&format!("{}  {}\n", hash, rel));
+        }
+    }
+
+    let tmp_path = path.with_file_name(format!("{}.tmp", MANIFEST_FILE_NAME));
+    if let Err(e) = fs::write(&tmp_path, content).and_then(|()| fs::rename(&tmp_path, path)) {
+        warn!("Failed to write manifest {}: {}", path.display(), e);
+        fs::remove_file(&tmp_path).ok();
+    } else {
+        debug!("Wrote manifest {} ({} files)", path.display(), hashes.len());
+    }
+}
+
+fn compare_manifest(
+    expected: &BTreeMap<String, String>,
+    actual: &BTreeMap<String, Result<String, String>>,
+) -> Vec<ManifestIssue> {
+    let mut issues: Vec<ManifestIssue> = expected
+        .iter()
+        .filter_map(|(rel, hash)| {
+            let kind = match actual.get(rel) {
+                None => ManifestIssueKind::Missing,
+                Some(Err(_)) => ManifestIssueKind::Unreadable,
+                Some(Ok(actual_hash)) if actual_hash != hash => ManifestIssueKind::Changed,
+                Some(Ok(_)) => return None,
+            };
+            Some(ManifestIssue {
+                relative_path: rel.clone(),
+                kind,
+            })
+        })
+        .collect();
+
+    issues.extend(
+        actual
+            .keys()
+            .filter(|rel| !expected.contains_key(*rel))
+            .map(|rel| ManifestIssue {
+                relative_path: rel.clone(),
+                kind: ManifestIssueKind::Extra,
+            }),
+    );
+    issues.sort_by(|a, b| a.relative_path.cmp(&b.relative_path));
+    issues
+}
+
+fn unreadable_issues(actual: &BTreeMap<String, Result<String, String>>) -> Vec<ManifestIssue> {
+    actual
+        .iter()
+        .filter(|(_, hash)| hash.is_err())
+        .map(|(rel, _)| ManifestIssue {
+            relative_path: rel.clone(),
+            kind: ManifestIssueKind::Unreadable,
+        })
+        .collect()
+}
+
+pub fn print_manifest_report(report: &ManifestReport) {
+    println!(
+        "Manifests: {} verified, {} created, {} skipped (still growing), {} not yet complete",
+        report.verified.to_string().green(),
+        report.created.len(),
+        report.skipped_growing,
+        report.skipped_incomplete
+    );
+
+    if report.issues_by_dir.is_empty() {
+        println!("No content changes since manifests were written.");
+        return;
+    }
+
+    println!(
+        "Found {} issues across {} archives:",
+        report.issue_count().to_string().red(),
+        report.issues_by_dir.len()
+    );
+    for (dir, issues) in &report.issues_by_dir {
+        println!("\n{} ({} issues)", dir.yellow(), issues.len());
+        for issue in issues {
+            let kind = match issue.kind {
+                ManifestIssueKind::Extra => issue.kind.to_string().yellow(),
+                _ => issue.kind.to_string().red(),
+            };
+            println!("  {} {:<10} {}", "⚠️".yellow(), kind, issue.relative_path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inventory::DayCompletion;
+
+    const TWO_DAYS: Duration = Duration::from_secs(2 * 24 * 60 * 60);
+
+    fn options(threads: usize) -> ScanOptions {
+        ScanOptions {
+            threads,
+            ..ScanOptions::default()
+        }
+    }
+
+    /// Files written `age` ago
+    fn make_dir_aged(base: &Path, name: &str, files: &[(&str, &[u8])], age: Duration) -> PathBuf {
+        let dir = base.join(name);
+        fs::create_dir_all(&dir).unwrap();
+        for (file, content) in files {
+            fs::write(dir.join(file), content).unwrap();
+            File::options()
+                .write(true)
+                .open(dir.join(file))
+                .unwrap()
+                .set_modified(SystemTime::now() - age)
+                .unwrap();
+        }
+        dir
+    }
+
+    /// Files settled long ago
+    fn make_dir(base: &Path, name: &str, files: &[(&str, &[u8])]) -> PathBuf {
+        make_dir_aged(base, name, files, TWO_DAYS)
+    }
+
+    fn inventory(days: &[(&str, DayStatus)]) -> InventoryReport {
+        InventoryReport {
+            days: days
+                .iter()
+                .map(|(name, status)| DayCompletion {
+                    dir_name: (*name).to_string(),
+                    status: *status,
+                    copying: false,
+                    expected_files: 1,
+                    complete_files: 0,
+                    missing_files: Vec::new(),
+                    size_mismatches: Vec::new(),
+                    expected_bytes: 0,
+                    copied_bytes: 0,
+                })
+                .collect(),
+            total_bytes: 0,
+            copied_bytes: 0,
+            complete_days: 0,
+            incomplete_days: 0,
+            missing_days: 0,
+        }
+    }
+
+    #[test]
+    fn test_manifest_created_then_verified() {
+        let temp = tempfile::tempdir().unwrap();
+        let search_dir = temp.path().to_str().unwrap();
+        let dir = make_dir(
+            temp.path(),
+            "Archive_Beam_B_2024-08-01",
+            &[("a.zip", b"aaa"), ("b.zip", b"bbb")],
+        );
+
+        let report = process_manifests(search_dir, "B", &options(2), &HashSet::new(), None, None);
+        assert_eq!(report.created, vec!["Archive_Beam_B_2024-08-01"]);
+        assert_eq!(report.verified, 0);
+
+        let manifest = fs::read_to_string(dir.join(MANIFEST_FILE_NAME)).unwrap();
+        assert!(
+            manifest.contains(
+                "9834876dcfb05cb167a5c24953eba58c4ac89b1adf57f28f2f9d09af107ee8f0  a.zip"
+            )
+        );
+
+        let report = process_manifests(search_dir, "B", &options(2), &HashSet::new(), None, None);
+        assert!(report.created.is_empty());
+        assert_eq!(report.verified, 1);
+        assert!(report.issues_by_dir.is_empty());
+    }
+
+    #[test]
+    fn test_changed_missing_and_extra_files_reported() {
+        let temp = tempfile::tempdir().unwrap();
+        let search_dir = temp.path().to_str().unwrap();
+        let dir = make_dir(
+            temp.path(),
+            "Archive_Beam_B_2024-08-01",
+            &[("a.zip", b"aaa"), ("b.zip", b"bbb")],
+        );
+        let _ = process_manifests(search_dir, "B", &options(1), &HashSet::new(), None, None);
+
+        fs::write(dir.join("a.zip"), b"AAA").unwrap();
+        fs::remove_file(dir.join("b.zip")).unwrap();
+        fs::write(dir.join("c.zip"), b"ccc").unwrap();
+
+        let report = process_manifests(search_dir, "B", &options(1), &HashSet::new(), None, None);
+        assert_eq!(report.issue_count(), 3);
+        let (name, issues) = &report.issues_by_dir[0];
+        assert_eq!(name, "Archive_Beam_B_2024-08-01");
+        let kinds: Vec<_> = issues
+            .iter()
+            .map(|i| (i.relative_path.as_str(), i.kind))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("a.zip", ManifestIssueKind::Changed),
+                ("b.zip", ManifestIssueKind::Missing),
+                ("c.zip", ManifestIssueKind::Extra),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_growing_and_foreign_dirs_skipped() {
+        let temp = tempfile::tempdir().unwrap();
+        let search_dir = temp.path().to_str().unwrap();
+        let growing = make_dir(temp.path(), "Archive_Beam_B_2024-08-02", &[("a.zip", b"a")]);
+        let other_line = make_dir(temp.path(), "Archive_Beam_A_2024-08-02", &[("a.zip", b"a")]);
+
+        let growing_dirs: HashSet<String> = ["Archive_Beam_B_2024-08-02".to_string()].into();
+        let report = process_manifests(search_dir, "B", &options(1), &growing_dirs, None, None);
+        assert_eq!(report.skipped_growing, 1);
+        assert!(report.created.is_empty());
+        assert!(!growing.join(MANIFEST_FILE_NAME).exists());
+        assert!(!other_line.join(MANIFEST_FILE_NAME).exists());
+    }
+
+    #[test]
+    fn test_manifest_only_for_complete_dirs() {
+        let temp = tempfile::tempdir().unwrap();
+        let search_dir = temp.path().to_str().unwrap();
+        // Paused transfer: not growing, but written to an hour ago
+        let paused = make_dir_aged(
+            temp.path(),
+            "Archive_Beam_B_2024-08-01",
+            &[("a.zip", b"a")],
+            Duration::from_secs(3600),
+        );
+        let report = process_manifests(search_dir, "B", &options(1), &HashSet::new(), None, None);
+        assert_eq!(report.skipped_incomplete, 1);
+        assert!(!paused.join(MANIFEST_FILE_NAME).exists());
+
+        // The source inventory decides when it lists the directory
+        let incomplete = inventory(&[("Archive_Beam_B_2024-08-01", DayStatus::Incomplete)]);
+        let old = make_dir(temp.path(), "Archive_Beam_B_2024-08-01", &[("a.zip", b"a")]);
+        let report = process_manifests(
+            search_dir,
+            "B",
+            &options(1),
+            &HashSet::new(),
+            Some(&incomplete),
+            None,
+        );
+        assert_eq!(report.skipped_incomplete, 1);
+        assert!(!old.join(MANIFEST_FILE_NAME).exists());
+
+        let complete = inventory(&[("Archive_Beam_B_2024-08-02", DayStatus::Complete)]);
+        make_dir_aged(
+            temp.path(),
+            "Archive_Beam_B_2024-08-02",
+            &[("a.zip", b"a")],
+            Duration::ZERO,
+        );
+        let report = process_manifests(
+            search_dir,
+            "B",
+            &options(1),
+            &HashSet::new(),
+            Some(&complete),
+            None,
+        );
+        assert_eq!(
+            report.created,
+            vec!["Archive_Beam_B_2024-08-01", "Archive_Beam_B_2024-08-02"]
+        );
+    }
+
+    #[test]
+    fn test_hash_cache_used_only_for_new_manifests() {
+        let temp = tempfile::tempdir().unwrap();
+        let search_dir = temp.path().to_str().unwrap();
+        let cache_file = hash_cache_path(search_dir, "B");
+        let dir = make_dir(
+            temp.path(),
+            "Archive_Beam_B_2024-08-01",
+            &[("a.zip", b"aaa")],
+        );
+        let path = dir.join("a.zip");
+        let run = |options: &ScanOptions| {
+            process_manifests(
+                search_dir,
+                "B",
+                options,
+                &HashSet::new(),
+                None,
+                Some(&cache_file),
+            )
+        };
+
+        // Same size and mtime: a new manifest takes the cached hash
+        let metadata = fs::metadata(&path).unwrap();
+        fs::write(
+            &cache_file,
+            format!(
+                "{}\t3\t{}\t{}\tcached\n",
+                path.display(),
+                metadata.mtime(),
+                metadata.mtime_nsec()
+            ),
+        )
+        .unwrap();
+        assert_eq!(run(&options(1)).created.len(), 1);
+        let manifest = fs::read_to_string(dir.join(MANIFEST_FILE_NAME)).unwrap();
+        assert_eq!(manifest, "cached  a.zip\n");
+
+        let rescan = ScanOptions {
+            rescan_all: true,
+            ..options(1)
+        };
+        fs::remove_file(dir.join(MANIFEST_FILE_NAME)).unwrap();
+        assert_eq!(run(&rescan).created.len(), 1);
+        assert!(run(&options(1)).issues_by_dir.is_empty());
+    }
+
+    #[test]
+    fn test_reverify_ignores_hash_cache() {
+        let temp = tempfile::tempdir().unwrap();
+        let search_dir = temp.path().to_str().unwrap();
+        let cache_file = hash_cache_path(search_dir, "B");
+        let dir = make_dir(
+            temp.path(),
+            "Archive_Beam_B_2024-08-01",
+            &[("a.zip", b"aaa")],
+        );
+        let run = || {
+            process_manifests(
+                search_dir,
+                "B",
+                &options(1),
+                &HashSet::new(),
+                None,
+                Some(&cache_file),
+            )
+        };
+        assert_eq!(run().created.len(), 1);
+        assert_eq!(run().verified, 1);
+
+        // Bit rot: one byte flipped, size and mtime unchanged
+        let path = dir.join("a.zip");
+        let mtime = fs::metadata(&path).unwrap().modified().unwrap();
+        fs::write(&path, b"aAa").unwrap();
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(mtime)
+            .unwrap();
+        let report = run();
+        assert_eq!(report.verified, 0);
+        assert_eq!(
+            report.issues_by_dir[0].1,
+            vec![ManifestIssue {
+                relative_path: "a.zip".to_string(),
+                kind: ManifestIssueKind::Changed,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_manifest_accepts_binary_mode() {
+        let parsed = parse_manifest("ABCD  a.zip\nef01 *sub/b.zip\ngarbage\n");
+        assert_eq!(parsed.get("a.zip").map(String::as_str), Some("abcd"));
+        assert_eq!(parsed.get("sub/b.zip").map(String::as_str), Some("ef01"));
+        assert_eq!(parsed.len(), 2);
+    }
+}
@@ -0,0 +1,253 @@
+use crate::naming::DirPattern;
+use crate::types::FileEntry;
+use colored::Colorize;
+use log::debug;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CanonicalSource {
+    /// Names present in more than half of the daily folders
+    Majority,
+    /// Names listed in `.expected_members_<line>`
+    Config,
+}
+
+/// Deviation of one daily folder from the canonical member set
+#[derive(Debug, Clone, PartialEq)]
+pub struct FolderMembers {
+    pub dir_name: String,
+    pub missing: Vec<String>,
+    pub unexpected: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct MemberSetReport {
+    pub canonical: Vec<String>,
+    pub source: CanonicalSource,
+    pub folders_checked: usize,
+    /// Only folders that deviate, sorted by name
+    pub folders: Vec<FolderMembers>,
+}
+
+impl MemberSetReport {
+    #[must_use]
+    pub fn missing_count(&self) -> usize {
+        self.folders.iter().map(|f| f.missing.len()).sum()
+    }
+
+    #[must_use]
+    pub fn unexpected_count(&self) -> usize {
+        self.folders.iter().map(|f| f.unexpected.len()).sum()
+    }
+}
+
+/// Load the declared member set from `<base_dir>/.expected_members_<line>`:
+/// one archive name per line, `#` starts a comment
+#[must_use]
+pub fn load_expected_members(base_dir: &str, line_id: &str) -> Option<Vec<String>> {
+    let config_path = format!("{}/.expected_members_{}", base_dir, line_id);
+    let content = fs::read_to_string(&config_path).ok()?;
+    let names: Vec<String> = content
+        .lines()
+        .map(|l| l.split('#').next().unwrap_or("").trim())
+        .filter(|l| !l.is_empty())
+        .map(str::to_string)
+        .collect();
+    debug!(
+        "Line {}: Loaded {} expected member names from {}",
+        line_id,
+        names.len(),
+        config_path
+    );
+    (!names.is_empty()).then_some(names)
+}
+
+/// Compare every daily folder against the canonical member set.
+///
+/// Files are expected to be filtered to stable folders already: a folder
+/// that is still being copied would show most names as missing.
+#[must_use]
+pub fn calculate_member_sets(
+    files: &[FileEntry],
+    line_id: &str,
+    pattern: &DirPattern,
+    declared: Option<Vec<String>>,
+) -> Option<MemberSetReport> {
+    let mut by_folder: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for f in files
+        .iter()
+        .filter(|f| pattern.matches(&f.parent_dir, line_id))
+    {
+        by_folder
+            .entry(f.parent_dir.as_str())
+            .or_default()
+            .insert(f.name.as_str());
+    }
+    if by_folder.is_empty() {
+        return None;
+    }
+
+    let (canonical, source): (BTreeSet<String>, _) = if let Some(names) = declared {
+        (names.into_iter().collect(), CanonicalSource::Config)
+    } else {
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for names in by_folder.values() {
+            for name in names {
+                *counts.entry(name).or_insert(0) += 1;
+            }
+        }
+        let majority = by_folder.len() / 2;
+        let names = counts
+            .into_iter()
+            .filter(|(_, count)| *count > majority)
+            .map(|(name, _)| name.to_string())
+            .collect();
+        (names, CanonicalSource::Majority)
+    };
+
+    let folders = by_folder
+        .iter()
+        .filter_map(|(dir, names)| {
+            let missing: Vec<String> = canonical
+                .iter()
+                .filter(|n| !names.contains(n.as_str()))
+                .cloned()
+                .collect();
+            let unexpected: Vec<String> = names
+                .iter()
+                .filter(|n| !canonical.contains(**n))
+                .map(|n| (*n).to_string())
+                .collect();
+            (!missing.is_empty() || !unexpected.is_empty()).then(|| FolderMembers {
+                dir_name: (*dir).to_string(),
+                missing,
+                unexpected,
+            })
+        })
+        .collect();
+
+    Some(MemberSetReport {
+        canonical: canonical.into_iter().collect(),
+        source,
+        folders_checked: by_folder.len(),
+        folders,
+    })
+}
+
+pub fn print_member_sets(report: &Option<MemberSetReport>) {
+    let Some(r) = report else {
+        println!("No completed directories.");
+        return;
+    };
+
+    let source = match r.source {
+        CanonicalSource::Majority => "majority of folders",
+        CanonicalSource::Config => "config",
+    };
+    println!(
+        "Expected Members: {} names (from {}), {} folders checked",
+        r.canonical.len(),
+        source,
+        r.folders_checked
+    );
+    println!("-------------------------------------------------------------------------------");
+
+    if r.folders.is_empty() {
+        println!("All folders hold the expected set of archives.");
+        return;
+    }
+
+    println!(
+        "{} folders deviate: {} missing, {} unexpected",
+        r.folders.len(),
+        r.missing_count().to_string().red(),
+        r.unexpected_count().to_string().yellow()
+    );
+    for folder in &r.folders {
+        println!("⚠️ {}", folder.dir_name.yellow());
+        if !folder.missing.is_empty() {
+            println!("     Missing:    {}", folder.missing.join(", ").red());
+        }
+        if !folder.unexpected.is_empty() {
+            println!("     Unexpected: {}", folder.unexpected.join(", ").yellow());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ArchiveFormat;
+    use chrono::Local;
+
+    fn entry(dir: &str, name: &str) -> FileEntry {
+        FileEntry {
+            name: name.to_string(),
+            size: 5000,
+            format: ArchiveFormat::Zip,
+            is_valid: true,
+            invalid_reason: None,
+            modified: Local::now(),
+            parent_dir: format!("Archive_Beam_B_{}", dir),
+        }
+    }
+
+    fn sample_files() -> Vec<FileEntry> {
+        vec![
+            entry("2024-08-01", "Cam1.zip"),
+            entry("2024-08-01", "Cam2.zip"),
+            entry("2024-08-02", "Cam1.zip"),
+            entry("2024-08-02", "Cam2.zip"),
+            entry("2024-08-05", "Cam1.zip"),
+            entry("2024-08-05", "Debug.zip"),
+        ]
+    }
+
+    #[test]
+    fn test_majority_member_set() {
+        let report =
+            calculate_member_sets(&sample_files(), "B", &DirPattern::default(), None).unwrap();
+        assert_eq!(report.source, CanonicalSource::Majority);
+        assert_eq!(report.canonical, vec!["Cam1.zip", "Cam2.zip"]);
+        assert_eq!(report.folders_checked, 3);
+        assert_eq!(
+            report.folders,
+            vec![FolderMembers {
+                dir_name: "Archive_Beam_B_2024-08-05".to_string(),
+                missing: vec!["Cam2.zip".to_string()],
+                unexpected: vec!["Debug.zip".to_string()],
+            }]
+        );
+        assert_eq!((report.missing_count(), report.unexpected_count()), (1, 1));
+    }
+
+    #[test]
+    fn test_declared_member_set_overrides_majority() {
+        let declared = vec!["Cam1.zip".to_string(), "Cam3.zip".to_string()];
+        let report =
+            calculate_member_sets(&sample_files(), "B", &DirPattern::default(), Some(declared))
+                .unwrap();
+        assert_eq!(report.source, CanonicalSource::Config);
+        assert_eq!(report.folders.len(), 3);
+        assert_eq!(report.missing_count(), 3);
+        assert_eq!(report.unexpected_count(), 3);
+    }
+
+    #[test]
+    fn test_load_expected_members() {
+        let temp = tempfile::tempdir().unwrap();
+        let base_dir = temp.path().to_str().unwrap();
+        assert!(load_expected_members(base_dir, "B").is_none());
+
+        fs::write(
+            temp.path().join(".expected_members_B"),
+            "# cameras\nCam1.zip\n\n  Cam2.zip  # spare\n",
+        )
+        .unwrap();
+        assert_eq!(
+            load_expected_members(base_dir, "B"),
+            Some(vec!["Cam1.zip".to_string(), "Cam2.zip".to_string()])
+        );
+    }
+}
@@ -0,0 +1,239 @@
+use crate::audit::write_dashboard;
+use crate::html_renderer::AuditReport;
+use crate::sites::Site;
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use log::{debug, error};
+use std::fmt::Write;
+
+/// Prefix of every metric name
+const PREFIX: &str = "beam_audit";
+
+/// One gauge and its value for every line that has one
+struct Gauge {
+    name: &'static str,
+    help: &'static str,
+    value: fn(&AuditReport, DateTime<Local>) -> Option<f64>,
+}
+
+const GAUGES: [Gauge; 12] = [
+    Gauge {
+        name: "total_bytes",
+        help: "Bytes archived on the line",
+        value: |r, _| Some(r.total_size as f64),
+    },
+    Gauge {
+        name: "archive_files",
+        help: "Archive files (ZIP and other formats) on the line",
+        value: |r, _| Some(r.total_files as f64),
+    },
+    Gauge {
+        name: "speed_bytes_per_second",
+        help: "Current transfer speed",
+        value: |r, _| Some(r.speed_bps as f64),
+    },
+    Gauge {
+        name: "transfer_active",
+        help: "1 while the transfer is ACTIVE, 0 while IDLE",
+        value: |r, _| Some(if r.speed_bps > 0 { 1.0 } else { 0.0 }),
+    },
+    Gauge {
+        name: "state_duration_seconds",
+        help: "Seconds since the transfer entered its current ACTIVE/IDLE state",
+        value: |r, now| state_duration(&r.since_timestamp, now),
+    },
+    Gauge {
+        name: "bad_archives",
+        help: "Archive files that failed validation",
+        value: |r, _| Some(r.bad_files_report.as_ref().map_or(0, |b| b.total_count) as f64),
+    },
+    Gauge {
+        name: "missing_weekdays",
+        help: "Weekdays without an archive folder",
+        value: |r, _| {
+            r.gap_report
+                .as_ref()
+                .map(|g| g.missing_weekdays.len() as f64)
+        },
+    },
+    Gauge {
+        name: "size_anomalies",
+        help: "Archive folders far below or above the median size",
+        value: |r, _| Some(r.anomaly_report.as_ref().map_or(0, |a| a.anomalies.len()) as f64),
+    },
+    Gauge {
+        name: "progress_ratio",
+        help: "Fraction of the transfer window copied (0-1)",
+        value: |r, _| r.estimates_report.as_ref().map(|e| e.progress_ratio()),
+    },
+    Gauge {
+        name: "remaining_bytes",
+        help: "Estimated bytes still to copy",
+        value: |r, _| {
+            r.estimates_report
+                .as_ref()
+                .map(|e| e.remaining_bytes as f64)
+        },
+    },
+    Gauge {
+        name: "free_bytes",
+        help: "Free bytes on the destination filesystem",
+        value: |r, _| r.estimates_report.as_ref().map(|e| e.free_bytes as f64),
+    },
+    Gauge {
+        name: "health_score",
+        help: "Monthly health score (0-100) of the latest month",
+        value: |r, _| {
+            let ranking = r.monthly_ranking.as_ref()?;
+            let latest = ranking.months.iter().max_by_key(|m| (m.year, m.month))?;
+            Some(latest.health_score)
+        },
+    },
+];
+
+/// Seconds since a `since_timestamp` ("YYYY-MM-DD HH:MM", local time)
+fn state_duration(since: &str, now: DateTime<Local>) -> Option<f64> {
+    let since = NaiveDateTime::parse_from_str(since.trim(), "%Y-%m-%d %H:%M").ok()?;
+    let since = Local.from_local_datetime(&since).earliest()?;
+    Some((now - since).num_seconds().max(0) as f64)
+}
+
+/// Label values escaped per the exposition format
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Prometheus text exposition of every line of every site. `site` is empty
+/// for the site given by `--base-dir`.
+#[must_use]
+pub fn render(
+    sites: &[Site],
+    site_reports: &[Vec<(String, AuditReport)>],
+    now: DateTime<Local>,
+) -> String {
+    let mut out = String::new();
+    for gauge in &GAUGES {
+        let _ = writeln!(out, "# HELP {PREFIX}_{} {}", gauge.name, gauge.help);
+        let _ = writeln!(out, "# TYPE {PREFIX}_{} gauge", gauge.name);
+        for (site, reports) in sites.iter().zip(site_reports) {
+            let site_name = escape_label(site.name.as_deref().unwrap_or(""));
+            for (line_id, report) in reports {
+                if let Some(value) = (gauge.value)(report, now) {
+                    let _ = writeln!(
+                        out,
+                        "{PREFIX}_{}{{site=\"{}\",line=\"{}\"}} {}",
+                        gauge.name,
+                        site_name,
+                        escape_label(line_id),
+                        value
+                    );
+                }
+            }
+        }
+    }
+    let _ = writeln!(
+        out,
+        "# HELP {PREFIX}_last_run_timestamp_seconds When these metrics were written"
+    );
+    let _ = writeln!(out, "# TYPE {PREFIX}_last_run_timestamp_seconds gauge");
+    let _ = writeln!(
+        out,
+        "{PREFIX}_last_run_timestamp_seconds {}",
+        now.timestamp()
+    );
+    out
+}
+
+/// Replace the `.prom` file atomically so the textfile collector never
+/// reads half of it
+pub fn write_metrics(
+    metrics_file: &str,
+    sites: &[Site],
+    site_reports: &[Vec<(String, AuditReport)>],
+) {
+    match write_dashboard(metrics_file, &render(sites, site_reports, Local::now())) {
+        Ok(()) => debug!("Metrics written to: {}", metrics_file),
+        Err(e) => error!("Error writing metrics to {}: {}", metrics_file, e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gap_analysis::GapReport;
+    use chrono::NaiveDate;
+
+    fn report(speed_bps: u64) -> AuditReport {
+        AuditReport {
+            total_size: 4096,
+            total_files: 2,
+            speed_bps,
+            since_timestamp: "2025-03-14 10:00".to_string(),
+            recent_files: Vec::new(),
+            recent_files_live: false,
+            redundancy_check: None,
+            integrity_stats: None,
+            gap_report: Some(GapReport {
+                start_date: NaiveDate::from_ymd_opt(2025, 3, 13).unwrap(),
+                end_date: NaiveDate::from_ymd_opt(2025, 3, 17).unwrap(),
+                missing_weekdays: vec![NaiveDate::from_ymd_opt(2025, 3, 14).unwrap()],
+                skipped_weekends: 2,
+                is_empty: false,
+                folders: Default::default(),
+            }),
+            estimates_report: None,
+            anomaly_report: None,
+            bad_files_report: None,
+            manifest_report: None,
+            inventory_report: None,
+            member_report: None,
+            max_bad_per_archive: 5,
+            history: Vec::new(),
+            files: Vec::new(),
+            anomaly_threshold: 0.5,
+            archive_pages: None,
+            monthly_ranking: None,
+            holidays: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let day = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap();
+        let mut north = Site::local("/data", Vec::new(), day, day);
+        north.name = Some("North \"1\"".to_string());
+        let now = Local
+            .from_local_datetime(&day.and_hms_opt(11, 30, 0).unwrap())
+            .unwrap();
+
+        let text = render(
+            &[north],
+            &[vec![
+                ("A".to_string(), report(0)),
+                ("B".to_string(), report(2048)),
+            ]],
+            now,
+        );
+        let lines: Vec<&str> = text.lines().collect();
+
+        assert!(lines.contains(&"# TYPE beam_audit_total_bytes gauge"));
+        assert!(lines.contains(&r#"beam_audit_total_bytes{site="North \"1\"",line="A"} 4096"#));
+        assert!(lines.contains(&r#"beam_audit_transfer_active{site="North \"1\"",line="A"} 0"#));
+        assert!(lines.contains(&r#"beam_audit_transfer_active{site="North \"1\"",line="B"} 1"#));
+        assert!(
+            lines.contains(
+                &r#"beam_audit_state_duration_seconds{site="North \"1\"",line="B"} 5400"#
+            )
+        );
+        assert!(lines.contains(&r#"beam_audit_missing_weekdays{site="North \"1\"",line="A"} 1"#));
+        // No estimates or ranking: those gauges have no samples
+        assert!(!text.contains("beam_audit_free_bytes{"));
+        assert!(!text.contains("beam_audit_health_score{"));
+        assert_eq!(
+            lines.last(),
+            Some(&format!("beam_audit_last_run_timestamp_seconds {}", now.timestamp()).as_str())
+        );
+    }
+}
@@ -0,0 +1,395 @@
+use chrono::{Datelike, NaiveDate};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use walkdir::WalkDir;
+
+/// Layout used by the original beamlines
+pub const DEFAULT_DIR_PATTERN: &str = "Archive_Beam_{line}_{date}";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token {
+    Line,
+    /// `YYYY-MM-DD`
+    Date,
+    Year,
+    Month,
+    Day,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Piece {
+    Literal(String),
+    Token(Token),
+}
+
+/// Naming pattern of the per-day archive directories below `Line <X>/`.
+///
+/// A template of literals and placeholders: `{line}`, `{date}` (YYYY-MM-DD),
+/// `{yyyy}`, `{mm}` and `{dd}`. A `/` separates nested directory levels, so
+/// `Beam{line}/{yyyy}/{mm}/{dd}` describes a year/month/day tree. Archive
+/// directories are identified by their path below `Line <X>/` joined with
+/// `/` (e.g. `BeamB/2025/03/14`), which is also what `FileEntry::parent_dir`
+/// holds. Text after the last placeholder of the last level is ignored, as
+/// with the historical `Archive_Beam_B_2024-08-01` parsing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirPattern {
+    template: String,
+    levels: Vec<Vec<Piece>>,
+}
+
+impl Default for DirPattern {
+    fn default() -> Self {
+        DEFAULT_DIR_PATTERN.parse().unwrap()
+    }
+}
+
+impl fmt::Display for DirPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.template)
+    }
+}
+
+impl FromStr for DirPattern {
+    type Err = String;
+
+    fn from_str(template: &str) -> Result<Self, Self::Err> {
+        let template = template.trim().trim_matches('/');
+        let mut levels = Vec::new();
+        for level in template.split('/') {
+            if level.is_empty() {
+                return Err(format!("Empty directory level in pattern '{}'", template));
+            }
+            levels.push(parse_level(level, template)?);
+        }
+
+        let tokens: Vec<Token> = levels
+            .iter()
+            .flatten()
+            .filter_map(|p| match p {
+                Piece::Token(t) => Some(*t),
+                Piece::Literal(_) => None,
+            })
+            .collect();
+        let count = |t: Token| tokens.iter().filter(|x| **x == t).count();
+        let full_date = count(Token::Date) == 1
+            && count(Token::Year) + count(Token::Month) + count(Token::Day) == 0;
+        let split_date = count(Token::Date) == 0
+            && count(Token::Year) == 1
+            && count(Token::Month) == 1
+            && count(Token::Day) == 1;
+        if !full_date && !split_date {
+            return Err(format!(
+                "Pattern '{}' must contain either {{date}} or each of {{yyyy}}, {{mm}} and {{dd}} exactly once",
+                template
+            ));
+        }
+        if count(Token::Line) > 1 {
+            return Err(format!(
+                "Pattern '{}' contains {{line}} more than once",
+                template
+            ));
+        }
+
+        Ok(Self {
+            template: template.to_string(),
+            levels,
+        })
+    }
+}
+
+fn parse_level(level: &str, template: &str) -> Result<Vec<Piece>, String> {
+    let mut pieces = Vec::new();
+    let mut rest = level;
+    while let Some(open) = rest.find('{') {
+        if open > 0 {
+            pieces.push(Piece::Literal(rest[..open].to_string()));
+        }
+        let close = rest[open..]
+            .find('}')
+            .ok_or_else(|| format!("Unclosed '{{' in pattern '{}'", template))?;
+        let token = match &rest[open + 1..open + close] {
+            "line" => Token::Line,
+            "date" => Token::Date,
+            "yyyy" => Token::Year,
+            "mm" => Token::Month,
+            "dd" => Token::Day,
+            other => {
+                return Err(format!(
+                    "Unknown placeholder {{{}}} in pattern '{}' (expected line, date, yyyy, mm or dd)",
+                    other, template
+                ));
+            }
+        };
+        pieces.push(Piece::Token(token));
+        rest = &rest[open + close + 1..];
+    }
+    if rest.contains('}') {
+        return Err(format!("Unmatched '}}' in pattern '{}'", template));
+    }
+    if !rest.is_empty() {
+        pieces.push(Piece::Literal(rest.to_string()));
+    }
+    Ok(pieces)
+}
+
+/// Date parts captured while matching
+#[derive(Default)]
+struct Captures {
+    year: Option<i32>,
+    month: Option<u32>,
+    day: Option<u32>,
+}
+
+/// Take exactly `width` ASCII digits from the start of `s`
+fn take_digits(s: &str, width: usize) -> Option<(&str, &str)> {
+    let digits = s.get(..width)?;
+    digits
+        .bytes()
+        .all(|b| b.is_ascii_digit())
+        .then(|| (digits, &s[width..]))
+}
+
+impl DirPattern {
+    /// Number of nested directory levels making up one archive directory
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Date of the archive directory `dir` (levels joined with `/`) for
+    /// `line_id`, or `None` if it does not follow the pattern
+    #[must_use]
+    pub fn parse_date(&self, dir: &str, line_id: &str) -> Option<NaiveDate> {
+        let parts: Vec<&str> = dir.split('/').collect();
+        if parts.len() != self.levels.len() {
+            return None;
+        }
+
+        let mut captures = Captures::default();
+        let last = self.levels.len() - 1;
+        for (i, (level, part)) in self.levels.iter().zip(&parts).enumerate() {
+            let rest = match_level(level, part, line_id, &mut captures)?;
+            if i != last && !rest.is_empty() {
+                return None;
+            }
+        }
+        NaiveDate::from_ymd_opt(captures.year?, captures.month?, captures.day?)
+    }
+
+    /// Whether `dir` is an archive directory of `line_id`
+    #[must_use]
+    pub fn matches(&self, dir: &str, line_id: &str) -> bool {
+        self.parse_date(dir, line_id).is_some()
+    }
+
+    /// Archive directory key of a file below `root`: its first `depth()`
+    /// directory levels joined with `/` for nested patterns. Single-level
+    /// patterns and files that sit less deep keep the name of their
+    /// immediate parent, as before patterns were configurable.
+    #[must_use]
+    pub fn dir_key(&self, root: &Path, file: &Path) -> String {
+        let parent = file.parent().unwrap_or(file);
+        if self.depth() > 1
+            && let Ok(rel) = parent.strip_prefix(root)
+        {
+            let levels: Vec<String> = rel
+                .components()
+                .take(self.depth())
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .collect();
+            if levels.len() == self.depth() {
+                return levels.join("/");
+            }
+        }
+        parent.file_name().map_or_else(
+            || "Unknown".to_string(),
+            |name| name.to_string_lossy().to_string(),
+        )
+    }
+
+    /// Locate an archive directory inside a split source or destination
+    /// path: returns its key and the index of the first component below it.
+    /// The right-most match wins.
+    #[must_use]
+    pub fn find_in_path(&self, parts: &[&str], line_id: &str) -> Option<(String, usize)> {
+        let depth = self.depth();
+        (0..=parts.len().saturating_sub(depth))
+            .rev()
+            .find_map(|start| {
+                let key = parts.get(start..start + depth)?.join("/");
+                self.matches(&key, line_id).then_some((key, start + depth))
+            })
+    }
+
+    /// Every archive directory of `line_id` below `search_dir`, sorted by key
+    #[must_use]
+    pub fn find_dirs(&self, search_dir: &str, line_id: &str) -> Vec<(String, PathBuf)> {
+        let root = Path::new(search_dir);
+        WalkDir::new(root)
+            .min_depth(self.depth())
+            .max_depth(self.depth())
+            .sort_by_file_name()
+            .into_iter()
+            .filter_map(std::result::Result::ok)
+            .filter(|e| e.file_type().is_dir())
+            .filter_map(|e| {
+                let key = e
+                    .path()
+                    .strip_prefix(root)
+                    .ok()?
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy().to_string())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                self.matches(&key, line_id).then(|| (key, e.into_path()))
+            })
+            .collect()
+    }
+}
+
+/// Match one directory level, returning the unmatched remainder
+fn match_level<'a>(
+    level: &[Piece],
+    part: &'a str,
+    line_id: &str,
+    captures: &mut Captures,
+) -> Option<&'a str> {
+    let mut rest = part;
+    for piece in level {
+        rest = match piece {
+            Piece::Literal(lit) => rest.strip_prefix(lit.as_str())?,
+            Piece::Token(Token::Line) => rest.strip_prefix(line_id)?,
+            Piece::Token(Token::Date) => {
+                let date = NaiveDate::parse_from_str(rest.get(..10)?, "%Y-%m-%d").ok()?;
+                captures.year = Some(date.year());
+                captures.month = Some(date.month());
+                captures.day = Some(date.day());
+                &rest[10..]
+            }
+            Piece::Token(Token::Year) => {
+                let (digits, tail) = take_digits(rest, 4)?;
+                captures.year = digits.parse().ok();
+                tail
+            }
+            Piece::Token(Token::Month) => {
+                let (digits, tail) = take_digits(rest, 2)?;
+                captures.month = digits.parse().ok();
+                tail
+            }
+            Piece::Token(Token::Day) => {
+                let (digits, tail) = take_digits(rest, 2)?;
+                captures.day = digits.parse().ok();
+                tail
+            }
+        };
+    }
+    Some(rest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn date(y: i32, m: u32, d: u32) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(y, m, d)
+    }
+
+    #[test]
+    fn test_default_pattern() {
+        let pattern = DirPattern::default();
+        assert_eq!(pattern.depth(), 1);
+        assert_eq!(
+            pattern.parse_date("Archive_Beam_B_2024-08-01", "B"),
+            date(2024, 8, 1)
+        );
+        assert_eq!(
+            pattern.parse_date("Archive_Beam_A_2024-12-25", "A"),
+            date(2024, 12, 25)
+        );
+        // Trailing text after the date is ignored
+        assert_eq!(
+            pattern.parse_date("Archive_Beam_B_2024-08-01_retry", "B"),
+            date(2024, 8, 1)
+        );
+        assert_eq!(pattern.parse_date("Archive_Beam_B_invalid", "B"), None);
+        assert_eq!(pattern.parse_date("SomeOtherDir_2024-08-01", "B"), None);
+        assert_eq!(pattern.parse_date("Archive_Beam_A_2024-08-01", "B"), None);
+    }
+
+    #[test]
+    fn test_compact_and_nested_patterns() {
+        let compact: DirPattern = "Archive_{line}_{yyyy}{mm}{dd}".parse().unwrap();
+        assert_eq!(
+            compact.parse_date("Archive_B_20250314", "B"),
+            date(2025, 3, 14)
+        );
+        assert_eq!(compact.parse_date("Archive_B_20251314", "B"), None);
+
+        let nested: DirPattern = "Beam{line}/{yyyy}/{mm}/{dd}".parse().unwrap();
+        assert_eq!(nested.depth(), 4);
+        assert_eq!(
+            nested.parse_date("BeamB/2025/03/14", "B"),
+            date(2025, 3, 14)
+        );
+        assert_eq!(nested.parse_date("BeamB/2025/03", "B"), None);
+        assert_eq!(nested.parse_date("BeamB/2025x/03/14", "B"), None);
+        assert_eq!(nested.parse_date("BeamA/2025/03/14", "B"), None);
+
+        let parts = ["D:", "Data", "BeamB", "2025", "03", "14", "sub", "a.zip"];
+        assert_eq!(
+            nested.find_in_path(&parts, "B"),
+            Some(("BeamB/2025/03/14".to_string(), 6))
+        );
+    }
+
+    #[test]
+    fn test_invalid_patterns() {
+        for template in [
+            "Archive_{line}",
+            "{yyyy}/{mm}",
+            "{date}_{dd}",
+            "{line}_{line}_{date}",
+            "Archive_{year}",
+            "Archive_{date",
+            "a//{date}",
+        ] {
+            assert!(
+                template.parse::<DirPattern>().is_err(),
+                "{} should be rejected",
+                template
+            );
+        }
+    }
+
+    #[test]
+    fn test_dir_key_and_find_dirs() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let nested: DirPattern = "Beam{line}/{yyyy}/{mm}/{dd}".parse().unwrap();
+        fs::create_dir_all(root.join("BeamB/2025/03/14/sub")).unwrap();
+        fs::create_dir_all(root.join("BeamB/2025/03/notes")).unwrap();
+
+        assert_eq!(
+            nested.dir_key(root, &root.join("BeamB/2025/03/14/sub/a.zip")),
+            "BeamB/2025/03/14"
+        );
+        assert_eq!(nested.dir_key(root, &root.join("BeamB/stray.zip")), "BeamB");
+
+        let default = DirPattern::default();
+        assert_eq!(
+            default.dir_key(root, &root.join("Archive_Beam_B_2025-03-14/Cam1.zip")),
+            "Archive_Beam_B_2025-03-14"
+        );
+        assert_eq!(
+            default.dir_key(root, &root.join("Archive_Beam_B_2025-03-14/sub/Cam1.zip")),
+            "sub"
+        );
+
+        let dirs = nested.find_dirs(root.to_str().unwrap(), "B");
+        assert_eq!(dirs.len(), 1);
+        assert_eq!(dirs[0].0, "BeamB/2025/03/14");
+        assert_eq!(dirs[0].1, root.join("BeamB/2025/03/14"));
+    }
+}
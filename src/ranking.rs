@@ -1,4 +1,6 @@
 use crate::gap_analysis::GapReport;
+use crate::members::MemberSetReport;
+use crate::naming::DirPattern;
 use crate::stats::AnomalyReport;
 use crate::types::FileEntry;
 use chrono::{Datelike, Local, NaiveDate};
@@ -13,34 +15,29 @@ pub struct MonthlyMetrics {
     pub anomaly_count: usize,
     pub invalid_files: usize,
     pub empty_files: usize,
+    /// Canonical archive names absent from the month's folders
+    pub missing_members: usize,
+    /// Archive names outside the canonical set
+    pub unexpected_members: usize,
     pub expected_weekdays: usize,
     pub actual_archives: usize,
     pub health_score: f64,
     pub is_complete: bool,
 }
 
-#[derive(Debug)]
+/// Scores from here up are shown green, from `FAIR_SCORE` up yellow, below red
+pub const GOOD_SCORE: f64 = 90.0;
+pub const FAIR_SCORE: f64 = 70.0;
+
+#[derive(Debug, Clone)]
 pub struct MonthlyRankingReport {
     pub months: Vec<MonthlyMetrics>,
     pub best_month: Option<MonthlyMetrics>,
     pub worst_month: Option<MonthlyMetrics>,
     pub average_score: f64,
-    #[allow(dead_code)]
     pub line_id: String,
 }
 
-/// Extract date from directory name (e.g., "Archive_Beam_B_2024-10-15" -> 2024-10-15)
-fn extract_date_from_dir(parent_dir: &str, line_id: &str) -> Option<NaiveDate> {
-    let prefix = format!("Archive_Beam_{}_", line_id);
-    if parent_dir.starts_with(&prefix) {
-        let date_str = &parent_dir[prefix.len()..];
-        if date_str.len() >= 10 {
-            return NaiveDate::parse_from_str(&date_str[0..10], "%Y-%m-%d").ok();
-        }
-    }
-    None
-}
-
 /// Count expected weekdays in a month, clamped to the date range and today
 fn count_expected_weekdays_in_month(
     year: i32,
@@ -116,25 +113,32 @@ fn calculate_health_score(metrics: &MonthlyMetrics) -> f64 {
     // - invalid_files * 3 (corrupted data - high severity)
     // - anomaly_count * 2 (size issues - medium severity)
     // - empty_files * 1 (sometimes empty - low severity)
+    // - missing_members * 3 (one device's data lost for the day - high severity)
+    // - unexpected_members * 1 (stray archive - low severity)
     let penalty = (metrics.missing_days * 10
         + metrics.invalid_files * 3
         + metrics.anomaly_count * 2
-        + metrics.empty_files) as f64;
+        + metrics.empty_files
+        + metrics.missing_members * 3
+        + metrics.unexpected_members) as f64;
 
     // max_penalty = expected_weekdays * 10 (all days missing scenario)
     let max_penalty = (metrics.expected_weekdays * 10) as f64;
 
     let score = 100.0 * (1.0 - penalty / max_penalty);
-    score.max(0.0).min(100.0)
+    score.clamp(0.0, 100.0)
 }
 
 /// Calculate monthly performance rankings
 #[must_use]
+#[allow(clippy::too_many_arguments)]
 pub fn calculate_monthly_rankings(
     files: &[FileEntry],
     gap_report: Option<&GapReport>,
     anomaly_report: Option<&AnomalyReport>,
+    member_report: Option<&MemberSetReport>,
     line_id: &str,
+    pattern: &DirPattern,
     start_date: NaiveDate,
     end_date: NaiveDate,
     tiny_threshold: u64,
@@ -149,7 +153,7 @@ pub fn calculate_monthly_rankings(
     let mut monthly_archive_dates: HashMap<(i32, u32), HashSet<NaiveDate>> = HashMap::new();
 
     for file in files {
-        if let Some(date) = extract_date_from_dir(&file.parent_dir, line_id) {
+        if let Some(date) = pattern.parse_date(&file.parent_dir, line_id) {
             let key = (date.year(), date.month());
             monthly_files.entry(key).or_default().push(file);
             monthly_archive_dates.entry(key).or_default().insert(date);
@@ -170,13 +174,27 @@ pub fn calculate_monthly_rankings(
     if let Some(report) = anomaly_report {
         for anomaly in &report.anomalies {
             // Anomaly.name is the directory name, extract date from it
-            if let Some(date) = extract_date_from_dir(&anomaly.name, line_id) {
+            if let Some(date) = pattern.parse_date(&anomaly.name, line_id) {
                 let key = (date.year(), date.month());
                 *anomalies_by_month.entry(key).or_insert(0) += 1;
             }
         }
     }
 
+    // Member set deviations per month: (missing, unexpected)
+    let mut members_by_month: HashMap<(i32, u32), (usize, usize)> = HashMap::new();
+    if let Some(report) = member_report {
+        for folder in &report.folders {
+            if let Some(date) = pattern.parse_date(&folder.dir_name, line_id) {
+                let entry = members_by_month
+                    .entry((date.year(), date.month()))
+                    .or_insert((0, 0));
+                entry.0 += folder.missing.len();
+                entry.1 += folder.unexpected.len();
+            }
+        }
+    }
+
     // Collect all months that have any data
     let mut all_months: HashSet<(i32, u32)> = monthly_files.keys().copied().collect();
     all_months.extend(missing_by_month.keys().copied());
@@ -213,6 +231,10 @@ pub fn calculate_monthly_rankings(
             let actual_archives = archive_dates.map(|d| d.len()).unwrap_or(0);
             let missing_days = missing_by_month.get(&(year, month)).copied().unwrap_or(0);
             let anomaly_count = anomalies_by_month.get(&(year, month)).copied().unwrap_or(0);
+            let (missing_members, unexpected_members) = members_by_month
+                .get(&(year, month))
+                .copied()
+                .unwrap_or((0, 0));
 
             // Determine if month is complete
             let month_end = if month == 12 {
@@ -235,6 +257,8 @@ pub fn calculate_monthly_rankings(
                 anomaly_count,
                 invalid_files,
                 empty_files,
+                missing_members,
+                unexpected_members,
                 expected_weekdays,
                 actual_archives,
                 health_score: 0.0, // Calculated below
@@ -306,7 +330,8 @@ pub fn print_monthly_rankings(report: &MonthlyRankingReport) {
     let mut table = Table::new();
     table.load_preset(comfy_table::presets::UTF8_HORIZONTAL_ONLY);
     table.set_header(vec![
-        "Month", "Score", "Missing", "Anomalies", "Invalid", "Empty", "Archives", "Status",
+        "Month", "Score", "Missing", "Anomalies", "Invalid", "Empty", "Members", "Archives",
+        "Status",
     ]);
 
     for metrics in &report.months {
@@ -318,18 +343,11 @@ pub fn print_monthly_rankings(report: &MonthlyRankingReport) {
             "Partial"
         };
 
-        // Color code the score
-        let score_color = if metrics.health_score >= 90.0 {
-            Color::Green
-        } else if metrics.health_score >= 70.0 {
-            Color::Yellow
-        } else {
-            Color::Red
-        };
-
         let row = vec![
             Cell::new(&month_str),
-            Cell::new(&score_str).fg(score_color).add_attribute(Attribute::Bold),
+            Cell::new(&score_str)
+                .fg(score_color(metrics.health_score))
+                .add_attribute(Attribute::Bold),
             Cell::new(metrics.missing_days).fg(if metrics.missing_days > 0 {
                 Color::Yellow
             } else {
@@ -350,6 +368,13 @@ pub fn print_monthly_rankings(report: &MonthlyRankingReport) {
             } else {
                 Color::White
             }),
+            Cell::new(metrics.missing_members + metrics.unexpected_members).fg(
+                if metrics.missing_members + metrics.unexpected_members > 0 {
+                    Color::Yellow
+                } else {
+                    Color::White
+                },
+            ),
             Cell::new(format!("{}/{}", metrics.actual_archives, metrics.expected_weekdays)),
             Cell::new(status).fg(if metrics.is_complete {
                 Color::White
@@ -363,111 +388,129 @@ pub fn print_monthly_rankings(report: &MonthlyRankingReport) {
     println!("\n{table}");
 }
 
-/// Combined metrics for a month across both lines
+/// Combined metrics for a month across all lines
 #[derive(Debug, Clone)]
 pub struct CombinedMonthlyMetrics {
     pub year: i32,
     pub month: u32,
-    pub line_a_score: Option<f64>,
-    pub line_b_score: Option<f64>,
+    /// Per-line metrics, in the order of `CombinedRankingReport::line_ids`
+    pub line_metrics: Vec<Option<MonthlyMetrics>>,
     pub combined_score: f64,
-    pub line_a_metrics: Option<MonthlyMetrics>,
-    pub line_b_metrics: Option<MonthlyMetrics>,
 }
 
-/// Combined ranking report for both lines
+impl CombinedMonthlyMetrics {
+    #[must_use]
+    pub fn line_score(&self, index: usize) -> Option<f64> {
+        self.line_metrics
+            .get(index)?
+            .as_ref()
+            .map(|m| m.health_score)
+    }
+}
+
+/// Combined ranking report for all lines
 #[derive(Debug)]
 pub struct CombinedRankingReport {
     pub months: Vec<CombinedMonthlyMetrics>,
     pub best_month: Option<CombinedMonthlyMetrics>,
     pub worst_month: Option<CombinedMonthlyMetrics>,
-    pub line_a_average: f64,
-    pub line_b_average: f64,
+    pub line_ids: Vec<String>,
+    pub line_averages: Vec<f64>,
     pub combined_average: f64,
 }
 
-/// Combine rankings from both lines into a unified report
+/// Combine rankings from all lines into a unified report
 #[must_use]
-pub fn combine_rankings(
-    line_a: &MonthlyRankingReport,
-    line_b: &MonthlyRankingReport,
-) -> CombinedRankingReport {
+pub fn combine_rankings(lines: &[MonthlyRankingReport]) -> CombinedRankingReport {
     // Build lookup maps for each line
-    let line_a_map: HashMap<(i32, u32), &MonthlyMetrics> = line_a
-        .months
+    let line_maps: Vec<HashMap<(i32, u32), &MonthlyMetrics>> = lines
         .iter()
-        .map(|m| ((m.year, m.month), m))
+        .map(|line| line.months.iter().map(|m| ((m.year, m.month), m)).collect())
         .collect();
 
-    let line_b_map: HashMap<(i32, u32), &MonthlyMetrics> = line_b
-        .months
+    // Collect all unique months
+    let all_months: HashSet<(i32, u32)> = line_maps
         .iter()
-        .map(|m| ((m.year, m.month), m))
+        .flat_map(|map| map.keys().copied())
         .collect();
 
-    // Collect all unique months
-    let mut all_months: HashSet<(i32, u32)> = line_a_map.keys().copied().collect();
-    all_months.extend(line_b_map.keys().copied());
-
     // Build combined metrics
     let mut months: Vec<CombinedMonthlyMetrics> = all_months
         .into_iter()
         .map(|(year, month)| {
-            let a_metrics = line_a_map.get(&(year, month)).copied().cloned();
-            let b_metrics = line_b_map.get(&(year, month)).copied().cloned();
-
-            let a_score = a_metrics.as_ref().map(|m| m.health_score);
-            let b_score = b_metrics.as_ref().map(|m| m.health_score);
+            let line_metrics: Vec<Option<MonthlyMetrics>> = line_maps
+                .iter()
+                .map(|map| map.get(&(year, month)).copied().cloned())
+                .collect();
 
             // Combined score: average of available scores
-            let combined_score = match (a_score, b_score) {
-                (Some(a), Some(b)) => (a + b) / 2.0,
-                (Some(a), None) => a,
-                (None, Some(b)) => b,
-                (None, None) => 0.0,
+            let scores: Vec<f64> = line_metrics
+                .iter()
+                .flatten()
+                .map(|m| m.health_score)
+                .collect();
+            let combined_score = if scores.is_empty() {
+                0.0
+            } else {
+                scores.iter().sum::<f64>() / scores.len() as f64
             };
 
             CombinedMonthlyMetrics {
                 year,
                 month,
-                line_a_score: a_score,
-                line_b_score: b_score,
+                line_metrics,
                 combined_score,
-                line_a_metrics: a_metrics,
-                line_b_metrics: b_metrics,
             }
         })
         .collect();
 
-    // Sort by combined score descending (best first)
+    // Sort by combined score descending (best first), oldest month first on ties
     months.sort_by(|a, b| {
         b.combined_score
             .partial_cmp(&a.combined_score)
             .unwrap_or(std::cmp::Ordering::Equal)
+            .then((a.year, a.month).cmp(&(b.year, b.month)))
     });
 
-    // Find best and worst (only among months with data from both lines, if possible)
-    let both_lines: Vec<_> = months
+    // Find best and worst (only among months with data from every line, if possible)
+    let all_lines: Vec<_> = months
         .iter()
-        .filter(|m| m.line_a_score.is_some() && m.line_b_score.is_some())
+        .filter(|m| m.line_metrics.iter().all(Option::is_some))
         .collect();
 
-    let (best_month, worst_month) = if both_lines.is_empty() {
+    let (best_month, worst_month) = if all_lines.is_empty() {
         (months.first().cloned(), months.last().cloned())
     } else {
         (
-            both_lines.first().cloned().cloned(),
-            both_lines.last().cloned().cloned(),
+            all_lines.first().copied().cloned(),
+            all_lines.last().copied().cloned(),
         )
     };
 
+    let line_averages: Vec<f64> = lines.iter().map(|l| l.average_score).collect();
+    let combined_average = if line_averages.is_empty() {
+        0.0
+    } else {
+        line_averages.iter().sum::<f64>() / line_averages.len() as f64
+    };
+
     CombinedRankingReport {
         months,
         best_month,
         worst_month,
-        line_a_average: line_a.average_score,
-        line_b_average: line_b.average_score,
-        combined_average: (line_a.average_score + line_b.average_score) / 2.0,
+        line_ids: lines.iter().map(|l| l.line_id.clone()).collect(),
+        line_averages,
+        combined_average,
+    }
+}
+
+fn score_color(score: f64) -> Color {
+    if score >= GOOD_SCORE {
+        Color::Green
+    } else if score >= FAIR_SCORE {
+        Color::Yellow
+    } else {
+        Color::Red
     }
 }
 
@@ -479,8 +522,9 @@ pub fn print_combined_rankings(report: &CombinedRankingReport) {
     }
 
     // Print summary
-    println!("Line A Average: {:.1}%", report.line_a_average);
-    println!("Line B Average: {:.1}%", report.line_b_average);
+    for (line_id, average) in report.line_ids.iter().zip(&report.line_averages) {
+        println!("Line {} Average: {:.1}%", line_id, average);
+    }
     println!("Combined Average: {:.1}%", report.combined_average);
 
     if let Some(ref best) = report.best_month {
@@ -496,131 +540,54 @@ pub fn print_combined_rankings(report: &CombinedRankingReport) {
         );
     }
 
-    // Create combined table
+    // Create combined table: scores, then invalid files, then missing days per line
     let mut table = Table::new();
     table.load_preset(comfy_table::presets::UTF8_HORIZONTAL_ONLY);
-    table.set_header(vec![
-        "Month",
-        "Combined",
-        "Line A",
-        "Line B",
-        "A Invalid",
-        "B Invalid",
-        "A Missing",
-        "B Missing",
-    ]);
+    let mut header = vec!["Month".to_string(), "Combined".to_string()];
+    header.extend(report.line_ids.iter().map(|l| format!("Line {}", l)));
+    header.extend(report.line_ids.iter().map(|l| format!("{} Invalid", l)));
+    header.extend(report.line_ids.iter().map(|l| format!("{} Missing", l)));
+    table.set_header(header);
 
     for m in &report.months {
         let month_str = format!("{}-{:02}", m.year, m.month);
-
         let combined_str = format!("{:.1}%", m.combined_score);
-        let a_str = m
-            .line_a_score
-            .map(|s| format!("{:.1}%", s))
-            .unwrap_or_else(|| "-".to_string());
-        let b_str = m
-            .line_b_score
-            .map(|s| format!("{:.1}%", s))
-            .unwrap_or_else(|| "-".to_string());
-
-        let a_invalid = m
-            .line_a_metrics
-            .as_ref()
-            .map(|m| m.invalid_files.to_string())
-            .unwrap_or_else(|| "-".to_string());
-        let b_invalid = m
-            .line_b_metrics
-            .as_ref()
-            .map(|m| m.invalid_files.to_string())
-            .unwrap_or_else(|| "-".to_string());
-        let a_missing = m
-            .line_a_metrics
-            .as_ref()
-            .map(|m| m.missing_days.to_string())
-            .unwrap_or_else(|| "-".to_string());
-        let b_missing = m
-            .line_b_metrics
-            .as_ref()
-            .map(|m| m.missing_days.to_string())
-            .unwrap_or_else(|| "-".to_string());
-
-        // Color code the combined score
-        let combined_color = if m.combined_score >= 90.0 {
-            Color::Green
-        } else if m.combined_score >= 70.0 {
-            Color::Yellow
-        } else {
-            Color::Red
-        };
-
-        let a_color = m.line_a_score.map_or(Color::White, |s| {
-            if s >= 90.0 {
-                Color::Green
-            } else if s >= 70.0 {
-                Color::Yellow
-            } else {
-                Color::Red
-            }
-        });
 
-        let b_color = m.line_b_score.map_or(Color::White, |s| {
-            if s >= 90.0 {
-                Color::Green
-            } else if s >= 70.0 {
-                Color::Yellow
-            } else {
-                Color::Red
-            }
-        });
-
-        let row = vec![
+        let mut row = vec![
             Cell::new(&month_str),
             Cell::new(&combined_str)
-                .fg(combined_color)
+                .fg(score_color(m.combined_score))
                 .add_attribute(Attribute::Bold),
-            Cell::new(&a_str).fg(a_color),
-            Cell::new(&b_str).fg(b_color),
-            Cell::new(&a_invalid).fg(
-                if m.line_a_metrics
-                    .as_ref()
-                    .map_or(false, |m| m.invalid_files > 0)
-                {
-                    Color::Red
-                } else {
-                    Color::White
-                },
-            ),
-            Cell::new(&b_invalid).fg(
-                if m.line_b_metrics
-                    .as_ref()
-                    .map_or(false, |m| m.invalid_files > 0)
-                {
-                    Color::Red
-                } else {
-                    Color::White
-                },
-            ),
-            Cell::new(&a_missing).fg(
-                if m.line_a_metrics
-                    .as_ref()
-                    .map_or(false, |m| m.missing_days > 0)
-                {
-                    Color::Yellow
-                } else {
-                    Color::White
-                },
-            ),
-            Cell::new(&b_missing).fg(
-                if m.line_b_metrics
-                    .as_ref()
-                    .map_or(false, |m| m.missing_days > 0)
-                {
+        ];
+
+        for i in 0..m.line_metrics.len() {
+            row.push(match m.line_score(i) {
+                Some(score) => Cell::new(format!("{:.1}%", score)).fg(score_color(score)),
+                None => Cell::new("-").fg(Color::White),
+            });
+        }
+        for metrics in &m.line_metrics {
+            row.push(match metrics {
+                Some(metrics) => {
+                    Cell::new(metrics.invalid_files).fg(if metrics.invalid_files > 0 {
+                        Color::Red
+                    } else {
+                        Color::White
+                    })
+                }
+                None => Cell::new("-").fg(Color::White),
+            });
+        }
+        for metrics in &m.line_metrics {
+            row.push(match metrics {
+                Some(metrics) => Cell::new(metrics.missing_days).fg(if metrics.missing_days > 0 {
                     Color::Yellow
                 } else {
                     Color::White
-                },
-            ),
-        ];
+                }),
+                None => Cell::new("-").fg(Color::White),
+            });
+        }
         table.add_row(row);
     }
 
@@ -630,6 +597,7 @@ pub fn print_combined_rankings(report: &CombinedRankingReport) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::ArchiveFormat;
     use chrono::{Local, Utc};
 
     fn make_test_entry(
@@ -642,6 +610,7 @@ mod tests {
         FileEntry {
             name: name.to_string(),
             size,
+            format: ArchiveFormat::Zip,
             is_valid,
             invalid_reason: if is_valid {
                 None
@@ -653,22 +622,6 @@ mod tests {
         }
     }
 
-    #[test]
-    fn test_extract_date_from_dir() {
-        assert_eq!(
-            extract_date_from_dir("Archive_Beam_B_2024-10-15", "B"),
-            Some(NaiveDate::from_ymd_opt(2024, 10, 15).unwrap())
-        );
-        assert_eq!(
-            extract_date_from_dir("Archive_Beam_A_2024-07-29", "A"),
-            Some(NaiveDate::from_ymd_opt(2024, 7, 29).unwrap())
-        );
-        // Wrong line ID
-        assert_eq!(extract_date_from_dir("Archive_Beam_B_2024-10-15", "A"), None);
-        // Invalid format
-        assert_eq!(extract_date_from_dir("SomeOtherDir", "B"), None);
-    }
-
     #[test]
     fn test_count_expected_weekdays_full_month() {
         // October 2024 has 23 weekdays (full month)
@@ -746,6 +699,8 @@ mod tests {
             anomaly_count: 0,
             invalid_files: 0,
             empty_files: 0,
+            missing_members: 0,
+            unexpected_members: 0,
             expected_weekdays: 22,
             actual_archives: 22,
             health_score: 0.0,
@@ -765,6 +720,8 @@ mod tests {
             anomaly_count: 0,
             invalid_files: 0,
             empty_files: 0,
+            missing_members: 0,
+            unexpected_members: 0,
             expected_weekdays: 22,
             actual_archives: 0,
             health_score: 0.0,
@@ -785,6 +742,8 @@ mod tests {
             anomaly_count: 3,
             invalid_files: 5,
             empty_files: 4,
+            missing_members: 0,
+            unexpected_members: 0,
             expected_weekdays: 23,
             actual_archives: 21,
             health_score: 0.0,
@@ -816,7 +775,17 @@ mod tests {
         let start = NaiveDate::from_ymd_opt(2024, 10, 1).unwrap();
         let end = NaiveDate::from_ymd_opt(2024, 10, 2).unwrap();
 
-        let report = calculate_monthly_rankings(&files, None, None, "B", start, end, 1000);
+        let report = calculate_monthly_rankings(
+            &files,
+            None,
+            None,
+            None,
+            "B",
+            &DirPattern::default(),
+            start,
+            end,
+            1000,
+        );
 
         // Should have October 2024
         assert_eq!(report.months.len(), 1);
@@ -843,7 +812,17 @@ mod tests {
         let start = NaiveDate::from_ymd_opt(2024, 10, 1).unwrap();
         let end = NaiveDate::from_ymd_opt(2024, 11, 30).unwrap();
 
-        let report = calculate_monthly_rankings(&files, None, None, "B", start, end, 1000);
+        let report = calculate_monthly_rankings(
+            &files,
+            None,
+            None,
+            None,
+            "B",
+            &DirPattern::default(),
+            start,
+            end,
+            1000,
+        );
 
         assert_eq!(report.months.len(), 2);
         assert_eq!(report.line_id, "B");
@@ -854,6 +833,37 @@ mod tests {
         assert!(nov.health_score > oct.health_score);
     }
 
+    #[test]
+    fn test_holiday_not_counted_as_missing_day() {
+        // Tue 1, Thu 3 October 2024; Wed 2 is a holiday
+        let files = vec![
+            make_test_entry("2024-10-01", "B", "test.zip", true, 5000),
+            make_test_entry("2024-10-03", "B", "test.zip", true, 5000),
+        ];
+        let start = NaiveDate::from_ymd_opt(2024, 10, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 10, 31).unwrap();
+        let holiday = NaiveDate::from_ymd_opt(2024, 10, 2).unwrap();
+        let missing_days = |holidays: &[NaiveDate]| {
+            let gaps =
+                crate::gap_analysis::find_gaps(&files, "B", &DirPattern::default(), holidays);
+            let report = calculate_monthly_rankings(
+                &files,
+                Some(&gaps),
+                None,
+                None,
+                "B",
+                &DirPattern::default(),
+                start,
+                end,
+                1000,
+            );
+            report.months[0].missing_days
+        };
+
+        assert_eq!(missing_days(&[]), 1);
+        assert_eq!(missing_days(&[holiday]), 0);
+    }
+
     #[test]
     fn test_combine_rankings() {
         // Create Line A report with Oct=80%, Nov=90%
@@ -866,6 +876,8 @@ mod tests {
                     anomaly_count: 0,
                     invalid_files: 2,
                     empty_files: 0,
+                    missing_members: 0,
+                    unexpected_members: 0,
                     expected_weekdays: 23,
                     actual_archives: 23,
                     health_score: 80.0,
@@ -878,6 +890,8 @@ mod tests {
                     anomaly_count: 0,
                     invalid_files: 0,
                     empty_files: 0,
+                    missing_members: 0,
+                    unexpected_members: 0,
                     expected_weekdays: 21,
                     actual_archives: 21,
                     health_score: 90.0,
@@ -900,6 +914,8 @@ mod tests {
                     anomaly_count: 0,
                     invalid_files: 5,
                     empty_files: 0,
+                    missing_members: 0,
+                    unexpected_members: 0,
                     expected_weekdays: 23,
                     actual_archives: 23,
                     health_score: 70.0,
@@ -912,6 +928,8 @@ mod tests {
                     anomaly_count: 0,
                     invalid_files: 0,
                     empty_files: 0,
+                    missing_members: 0,
+                    unexpected_members: 0,
                     expected_weekdays: 21,
                     actual_archives: 21,
                     health_score: 100.0,
@@ -924,7 +942,7 @@ mod tests {
             line_id: "B".to_string(),
         };
 
-        let combined = combine_rankings(&line_a, &line_b);
+        let combined = combine_rankings(&[line_a, line_b]);
 
         assert_eq!(combined.months.len(), 2);
 
@@ -957,6 +975,8 @@ mod tests {
                     anomaly_count: 0,
                     invalid_files: 0,
                     empty_files: 0,
+                    missing_members: 0,
+                    unexpected_members: 0,
                     expected_weekdays: 23,
                     actual_archives: 23,
                     health_score: 100.0,
@@ -969,6 +989,8 @@ mod tests {
                     anomaly_count: 0,
                     invalid_files: 0,
                     empty_files: 0,
+                    missing_members: 0,
+                    unexpected_members: 0,
                     expected_weekdays: 21,
                     actual_archives: 21,
                     health_score: 90.0,
@@ -989,6 +1011,8 @@ mod tests {
                 anomaly_count: 0,
                 invalid_files: 0,
                 empty_files: 0,
+                missing_members: 0,
+                unexpected_members: 0,
                 expected_weekdays: 21,
                 actual_archives: 21,
                 health_score: 80.0,
@@ -1000,20 +1024,66 @@ mod tests {
             line_id: "B".to_string(),
         };
 
-        let combined = combine_rankings(&line_a, &line_b);
+        let combined = combine_rankings(&[line_a, line_b]);
 
         assert_eq!(combined.months.len(), 2);
 
         // October only has Line A data, so combined = 100
         let oct = combined.months.iter().find(|m| m.month == 10).unwrap();
-        assert!(oct.line_a_score.is_some());
-        assert!(oct.line_b_score.is_none());
+        assert!(oct.line_score(0).is_some());
+        assert!(oct.line_score(1).is_none());
         assert!((oct.combined_score - 100.0).abs() < 0.1);
 
         // November has both, combined = (90 + 80) / 2 = 85
         let nov = combined.months.iter().find(|m| m.month == 11).unwrap();
-        assert!(nov.line_a_score.is_some());
-        assert!(nov.line_b_score.is_some());
+        assert!(nov.line_score(0).is_some());
+        assert!(nov.line_score(1).is_some());
         assert!((nov.combined_score - 85.0).abs() < 0.1);
     }
+
+    #[test]
+    fn test_combine_rankings_any_number_of_lines() {
+        let report = |line_id: &str, scores: &[(u32, f64)]| MonthlyRankingReport {
+            months: scores
+                .iter()
+                .map(|&(month, health_score)| MonthlyMetrics {
+                    year: 2024,
+                    month,
+                    missing_days: 0,
+                    anomaly_count: 0,
+                    invalid_files: 0,
+                    empty_files: 0,
+                    missing_members: 0,
+                    unexpected_members: 0,
+                    expected_weekdays: 21,
+                    actual_archives: 21,
+                    health_score,
+                    is_complete: true,
+                })
+                .collect(),
+            best_month: None,
+            worst_month: None,
+            average_score: scores.iter().map(|(_, s)| s).sum::<f64>() / scores.len() as f64,
+            line_id: line_id.to_string(),
+        };
+
+        // Line D only started in November
+        let combined = combine_rankings(&[
+            report("A", &[(10, 90.0), (11, 60.0)]),
+            report("B", &[(10, 90.0), (11, 60.0)]),
+            report("C", &[(10, 90.0), (11, 60.0)]),
+            report("D", &[(11, 100.0)]),
+        ]);
+
+        assert_eq!(combined.line_ids, vec!["A", "B", "C", "D"]);
+        assert!((combined.combined_average - 81.25).abs() < 0.01);
+
+        // October scores higher but lacks Line D, so November is both best and worst
+        assert_eq!(combined.months[0].month, 10);
+        assert_eq!(combined.months[0].line_metrics.len(), 4);
+        assert!(combined.months[0].line_score(3).is_none());
+        assert!((combined.months[1].combined_score - 70.0).abs() < 0.01);
+        assert_eq!(combined.best_month.as_ref().unwrap().month, 11);
+        assert_eq!(combined.worst_month.as_ref().unwrap().month, 11);
+    }
 }
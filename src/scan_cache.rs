@@ -0,0 +1,315 @@
+use crate::sidecar::SidecarSource;
+use log::{debug, warn};
+use std::collections::HashMap;
+use std::fs;
+
+/// Cached validation result for a single archive
+#[derive(Debug, Clone, PartialEq)]
+pub struct CachedResult {
+    pub size: u64,
+    pub mtime_secs: i64,
+    pub mtime_nsec: i64,
+    /// Whether the result came from a `--deep-verify` scan
+    pub deep_verified: bool,
+    /// Structural validity; the sidecar outcome is kept apart in `sidecar`
+    pub is_valid: bool,
+    pub invalid_reason: Option<String>,
+    /// Last checksum sidecar outcome, only valid while its source is unchanged
+    pub sidecar: Option<SidecarVerdict>,
+}
+
+/// Outcome of a checksum sidecar check and the file it was read from
+#[derive(Debug, Clone, PartialEq)]
+pub struct SidecarVerdict {
+    pub source: SidecarSource,
+    /// Mismatch or malformed sidecar, `None` when verified
+    pub failure: Option<String>,
+}
+
+/// Persistent validation cache keyed by path, size and mtime.
+///
+/// Stored as a tab-separated text file in the base directory:
+/// `path  size  mtime_secs  mtime_nsec  deep(0/1)  valid(0/1)  sidecar_name
+/// sidecar_mtime_secs  sidecar_mtime_nsec  sidecar_failure  reason`, the
+/// sidecar columns empty when no sidecar outcome is cached
+#[derive(Debug, Default)]
+pub struct ScanCache {
+    entries: HashMap<String, CachedResult>,
+}
+
+#[must_use]
+pub fn cache_file_path(base_dir: &str, line_id: &str) -> String {
+    format!("{}/.scan_cache_{}", base_dir, line_id)
+}
+
+impl ScanCache {
+    /// Load the cache file, returning an empty cache if it is missing or unreadable
+    #[must_use]
+    pub fn load(cache_file: &str) -> Self {
+        let Ok(content) = fs::read_to_string(cache_file) else {
+            debug!("No scan cache found at {}", cache_file);
+            return Self::default();
+        };
+
+        let entries: HashMap<String, CachedResult> =
+            content.lines().filter_map(parse_line).collect();
+        debug!(
+            "Loaded {} scan cache entries from {}",
+            entries.len(),
+            cache_file
+        );
+        Self { entries }
+    }
+
+    /// Return the cached result if the file is unchanged and the cached
+    /// result is at least as thorough as the one requested
+    #[must_use]
+    pub fn lookup(
+        &self,
+        path: &str,
+        size: u64,
+        mtime_secs: i64,
+        mtime_nsec: i64,
+        deep_verify: bool,
+    ) -> Option<&CachedResult> {
+        self.entries.get(path).filter(|c| {
+            c.size == size
+                && c.mtime_secs == mtime_secs
+                && c.mtime_nsec == mtime_nsec
+                && (c.deep_verified || !deep_verify)
+        })
+    }
+
+    pub fn insert(&mut self, path: String, result: CachedResult) {
+        self.entries.insert(path, result);
+    }
+
+    /// Write the cache atomically (temp file + rename)
+    pub fn save(&self, cache_file: &str) {
+        let mut paths: Vec<_> = self.entries.keys().collect();
+        paths.sort();
+
+        let mut content = String::new();
+        for path in paths {
+            // Tabs and newlines would break the line format
+            if path.contains(['\t', '\n']) {
+                continue;
+            }
+            let c = &self.entries[path];
+            let reason = c
+                .invalid_reason
+                .as_deref()
+                .unwrap_or("")
+                .replace(['\t', '\n', '\r'], " ");
+            let sidecar = match &c.sidecar {
+                Some(v) if !v.source.name.contains(['\t', '\n']) => format!(
+                    "{}\t{}\t{}\t{}",
+                    v.source.name,
+                    v.source.mtime_secs,
+                    v.source.mtime_nsec,
+                    v.failure
+                        .as_deref()
+                        .unwrap_or("")
+                        .replace(['\t', '\n', '\r'], " ")
+                ),
+                _ => "\t\t\t".to_string(),
+            };
+            content.push_str(&format!(
+                "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\n",
+                path,
+                c.size,
+                c.mtime_secs,
+                c.mtime_nsec,
+                u8::from(c.deep_verified),
+                u8::from(c.is_valid),
+                sidecar,
+                reason
+            ));
+        }
+
+        let tmp_file = format!("{}.tmp", cache_file);
+        if let Err(e) =
+            fs::write(&tmp_file, content).and_then(|()| fs::rename(&tmp_file, cache_file))
+        {
+            warn!("Failed to write scan cache {}: {}", cache_file, e);
+            fs::remove_file(&tmp_file).ok();
+        } else {
+            debug!(
+                "Wrote {} scan cache entries to {}",
+                self.entries.len(),
+                cache_file
+            );
+        }
+    }
+}
+
+fn parse_line(line: &str) -> Option<(String, CachedResult)> {
+    let parts: Vec<&str> = line.splitn(11, '\t').collect();
+    if parts.len() != 11 {
+        return None;
+    }
+    let sidecar = parse_sidecar(&parts[6..10])?;
+
+    let reason = parts[10].trim_end();
+    Some((
+        parts[0].to_string(),
+        CachedResult {
+            size: parts[1].parse().ok()?,
+            mtime_secs: parts[2].parse().ok()?,
+            mtime_nsec: parts[3].parse().ok()?,
+            deep_verified: parts[4] == "1",
+            is_valid: parts[5] == "1",
+            invalid_reason: if reason.is_empty() {
+                None
+            } else {
+                Some(reason.to_string())
+            },
+            sidecar,
+        },
+    ))
+}
+
+/// `name  mtime_secs  mtime_nsec  failure`, all empty for no outcome.
+/// `None` for a malformed entry, `Some(None)` for no outcome.
+fn parse_sidecar(parts: &[&str]) -> Option<Option<SidecarVerdict>> {
+    if parts[0].is_empty() {
+        return Some(None);
+    }
+    Some(Some(SidecarVerdict {
+        source: SidecarSource {
+            name: parts[0].to_string(),
+            mtime_secs: parts[1].parse().ok()?,
+            mtime_nsec: parts[2].parse().ok()?,
+        },
+        failure: (!parts[3].is_empty()).then(|| parts[3].to_string()),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(size: u64, deep: bool, reason: Option<&str>) -> CachedResult {
+        CachedResult {
+            size,
+            mtime_secs: 1_700_000_000,
+            mtime_nsec: 42,
+            deep_verified: deep,
+            is_valid: reason.is_none(),
+            invalid_reason: reason.map(str::to_string),
+            sidecar: None,
+        }
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let temp = tempfile::tempdir().unwrap();
+        let cache_file = cache_file_path(temp.path().to_str().unwrap(), "B");
+
+        let mut cache = ScanCache::default();
+        cache.insert("/x/a.zip".to_string(), result(100, false, None));
+        cache.insert(
+            "/x/b.zip".to_string(),
+            result(200, true, Some("CRC mismatch\tin member")),
+        );
+        cache.save(&cache_file);
+
+        let loaded = ScanCache::load(&cache_file);
+        assert_eq!(
+            loaded.lookup("/x/a.zip", 100, 1_700_000_000, 42, false),
+            Some(&result(100, false, None))
+        );
+        let b = loaded
+            .lookup("/x/b.zip", 200, 1_700_000_000, 42, false)
+            .unwrap();
+        assert!(!b.is_valid);
+        assert_eq!(b.invalid_reason.as_deref(), Some("CRC mismatch in member"));
+    }
+
+    #[test]
+    fn test_lookup_misses_on_changed_file() {
+        let mut cache = ScanCache::default();
+        cache.insert("/x/a.zip".to_string(), result(100, false, None));
+
+        assert!(
+            cache
+                .lookup("/x/a.zip", 101, 1_700_000_000, 42, false)
+                .is_none()
+        );
+        assert!(
+            cache
+                .lookup("/x/a.zip", 100, 1_700_000_001, 42, false)
+                .is_none()
+        );
+        assert!(
+            cache
+                .lookup("/x/a.zip", 100, 1_700_000_000, 43, false)
+                .is_none()
+        );
+        assert!(
+            cache
+                .lookup("/x/other.zip", 100, 1_700_000_000, 42, false)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_lookup_requires_deep_result_for_deep_scan() {
+        let mut cache = ScanCache::default();
+        cache.insert("/x/shallow.zip".to_string(), result(100, false, None));
+        cache.insert("/x/deep.zip".to_string(), result(100, true, None));
+
+        assert!(
+            cache
+                .lookup("/x/shallow.zip", 100, 1_700_000_000, 42, true)
+                .is_none()
+        );
+        assert!(
+            cache
+                .lookup("/x/deep.zip", 100, 1_700_000_000, 42, true)
+                .is_some()
+        );
+        assert!(
+            cache
+                .lookup("/x/deep.zip", 100, 1_700_000_000, 42, false)
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_sidecar_outcome_round_trip() {
+        let temp = tempfile::tempdir().unwrap();
+        let cache_file = cache_file_path(temp.path().to_str().unwrap(), "B");
+
+        let mut failed = result(100, false, None);
+        failed.sidecar = Some(SidecarVerdict {
+            source: SidecarSource {
+                name: "a.zip.sha256".to_string(),
+                mtime_secs: 1_700_000_100,
+                mtime_nsec: 7,
+            },
+            failure: Some("SHA-256 mismatch against a.zip.sha256".to_string()),
+        });
+        let mut cache = ScanCache::default();
+        cache.insert("/x/a.zip".to_string(), failed.clone());
+        cache.save(&cache_file);
+
+        let loaded = ScanCache::load(&cache_file);
+        assert_eq!(
+            loaded.lookup("/x/a.zip", 100, 1_700_000_000, 42, false),
+            Some(&failed)
+        );
+    }
+
+    #[test]
+    fn test_entries_without_sidecar_columns_are_dropped() {
+        assert!(parse_line("/x/a.zip\t100\t1700000000\t42\t1\t0\tBad EOCD").is_none());
+        assert!(parse_line("/x/a.zip\t100\t1700000000\t42\t0\t1\t0\tBad EOCD").is_none());
+    }
+
+    #[test]
+    fn test_load_missing_file_is_empty() {
+        let cache = ScanCache::load("/nonexistent/.scan_cache_B");
+        assert!(cache.entries.is_empty());
+    }
+}
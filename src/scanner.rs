@@ -1,14 +1,24 @@
-use crate::types::FileEntry;
+use crate::manifest;
+use crate::naming::DirPattern;
+use crate::scan_cache::{self, CachedResult, ScanCache, SidecarVerdict};
+use crate::sidecar::{self, SidecarOutcome};
+use crate::types::{FileEntry, FileWrite};
+use crate::validators::{self, ArchiveValidator};
 use chrono::{DateTime, Local};
+use log::{debug, info};
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::{Read, Seek, SeekFrom};
 use std::os::unix::fs::MetadataExt;
 use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
 use std::time::SystemTime;
-use walkdir::WalkDir;
+use walkdir::{DirEntry, WalkDir};
 
-pub fn get_total_and_per_dir_sizes(path: &str) -> (u64, HashMap<String, u64>) {
+pub fn get_total_and_per_dir_sizes(
+    path: &str,
+    pattern: &DirPattern,
+) -> (u64, HashMap<String, u64>) {
     // Use blocks() (512-byte blocks) instead of len() for accurate disk usage
     // This matches du behavior and detects active transfers immediately
     let mut total = 0u64;
@@ -24,134 +34,275 @@ pub fn get_total_and_per_dir_sizes(path: &str) -> (u64, HashMap<String, u64>) {
                 let size = metadata.blocks() * 512;
                 total += size;
 
-                // Extract parent directory name (e.g., "Archive_Beam_B_2024-10-01")
-                if let Some(parent) = entry.path().parent()
-                    && let Some(dir_name) = parent.file_name()
-                    && let Some(dir_str) = dir_name.to_str()
-                {
-                    *per_dir.entry(dir_str.to_string()).or_insert(0) += size;
-                }
+                // Archive directory key (e.g., "Archive_Beam_B_2024-10-01")
+                let dir_key = pattern.dir_key(Path::new(path), entry.path());
+                *per_dir.entry(dir_key).or_insert(0) += size;
             }
         });
 
     (total, per_dir)
 }
 
+/// Options controlling how thoroughly `scan_files` validates archives
+#[derive(Debug, Clone, Default)]
+pub struct ScanOptions {
+    /// Decompress every member and compare against the central directory CRC32
+    pub deep_verify: bool,
+    /// Ignore cached validation results and re-validate every archive
+    pub rescan_all: bool,
+    /// Validation cache file; `None` disables caching
+    pub cache_file: Option<String>,
+    /// Number of worker threads validating archives (values below 1 mean 1)
+    pub threads: usize,
+    /// Write and re-verify per-directory content manifests (`--manifest`)
+    pub manifests: bool,
+    /// Check archives against source-side `.sha256`/`.md5` checksum files
+    pub verify_sidecars: bool,
+    /// Naming pattern of the per-day archive directories (`--dir-pattern`)
+    pub dir_pattern: DirPattern,
+    /// Set to stop a scan between files (daemon shutdown). A stopped scan
+    /// returns incomplete results and leaves its cache file untouched.
+    pub cancel: Option<Arc<AtomicBool>>,
+}
+
+/// Default `--scan-threads`: one worker per CPU, capped at 8 since the scan
+/// is bound by network storage rather than CPU
+#[must_use]
+pub fn default_scan_threads() -> usize {
+    thread::available_parallelism().map_or(4, |n| n.get().min(8))
+}
+
+impl ScanOptions {
+    /// Copy of these options using the per-line cache file in `base_dir`
+    #[must_use]
+    pub fn for_line(&self, base_dir: &str, line_id: &str) -> Self {
+        Self {
+            cache_file: Some(scan_cache::cache_file_path(base_dir, line_id)),
+            ..self.clone()
+        }
+    }
+
+    /// Whether the scan was asked to stop, see `cancel`
+    #[must_use]
+    pub fn cancelled(&self) -> bool {
+        self.cancel
+            .as_ref()
+            .is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+    }
+}
+
 #[must_use]
-pub fn scan_files(path: &str) -> Vec<FileEntry> {
-    // 1. Collect all ZIP files into a vector (Sequential Walk)
-    let entries: Vec<_> = WalkDir::new(path)
+pub fn scan_files(path: &str, options: &ScanOptions) -> Vec<FileEntry> {
+    // 1. Collect all archives with a known format into a vector (Sequential
+    //    Walk, sorted so the output order does not depend on directory listing order)
+    let entries: Vec<(DirEntry, &dyn ArchiveValidator)> = WalkDir::new(path)
+        .sort_by_file_name()
         .into_iter()
         .filter_map(std::result::Result::ok)
-        .filter(|e| {
-            e.file_type().is_file()
-                && e.path()
-                    .extension()
-                    .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"))
-        })
+        .filter(|e| e.file_type().is_file())
+        .filter_map(|e| validators::validator_for(e.path()).map(|v| (e, v)))
         .collect();
 
-    // 2. Process metadata and integrity sequentially
-    let results: Vec<FileEntry> = entries
+    // Previous results are only consulted for unchanged files; the cache is
+    // rewritten from scratch so deleted files drop out of it
+    let old_cache = match &options.cache_file {
+        Some(cache_file) if !options.rescan_all => ScanCache::load(cache_file),
+        _ => ScanCache::default(),
+    };
+
+    // 2. Process metadata and integrity on a bounded pool of workers
+    let workers = options.threads.clamp(1, entries.len().max(1));
+    debug!(
+        "Scanning {} archives in {} with {} worker(s)",
+        entries.len(),
+        path,
+        workers
+    );
+    let scanned = parallel_map(&entries, workers, |(entry, validator)| {
+        if options.cancelled() {
+            return None;
+        }
+        scan_entry(Path::new(path), entry, *validator, options, &old_cache)
+    });
+    if options.cancelled() {
+        info!("Scan of {} stopped", path);
+        return scanned.into_iter().flatten().map(|s| s.entry).collect();
+    }
+
+    let mut new_cache = ScanCache::default();
+    let mut cache_hits = 0usize;
+    let mut cache_misses = 0usize;
+    let results: Vec<FileEntry> = scanned
         .into_iter()
-        .map(|entry| {
-            let p = entry.path();
-
-            // Metadata access
-            let metadata = entry
-                .metadata()
-                .unwrap_or_else(|_| std::fs::metadata(p).unwrap());
-            let size = metadata.len();
-            let name = entry.file_name().to_string_lossy().to_string();
-
-            let parent = p
-                .parent()
-                .and_then(|parent_path| parent_path.file_name())
-                .map_or_else(
-                    || "Unknown".to_string(),
-                    |name| name.to_string_lossy().to_string(),
-                );
-
-            // Integrity check
-            let (is_valid, invalid_reason) = is_zip_valid(p);
-
-            // Use UNIX_EPOCH as fallback instead of now() to avoid falsely marking
-            // files as "recent" when we can't read their modification time
-            let modified: DateTime<Local> =
-                metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH).into();
-
-            FileEntry {
-                name,
-                size,
-                is_valid,
-                invalid_reason,
-                modified,
-                parent_dir: parent,
+        .flatten()
+        .map(|scanned| {
+            if scanned.cache_hit {
+                cache_hits += 1;
+            } else {
+                cache_misses += 1;
             }
+            new_cache.insert(scanned.path_key, scanned.cached);
+            scanned.entry
         })
         .collect();
 
+    if let Some(cache_file) = &options.cache_file {
+        info!(
+            "Scan cache for {}: {} hits, {} misses{}",
+            path,
+            cache_hits,
+            cache_misses,
+            if options.rescan_all {
+                " (--rescan-all)"
+            } else {
+                ""
+            }
+        );
+        new_cache.save(cache_file);
+    }
+
     results
 }
 
-fn is_zip_valid(path: &Path) -> (bool, Option<String>) {
-    let mut file = match File::open(path) {
-        Ok(f) => f,
-        Err(_) => return (false, Some("Cannot open file".to_string())),
-    };
+/// Apply `f` to every item on a bounded pool of `threads` workers. Each
+/// worker pulls the next unclaimed index; results are returned in input order.
+pub fn parallel_map<T: Sync, R: Send>(
+    items: &[T],
+    threads: usize,
+    f: impl Fn(&T) -> R + Sync,
+) -> Vec<R> {
+    let workers = threads.clamp(1, items.len().max(1));
+    let next_index = AtomicUsize::new(0);
 
-    // Quick check: valid zip must be at least 22 bytes (EOCD size)
-    let len = match file.metadata() {
-        Ok(m) => m.len(),
-        Err(_) => return (false, Some("Cannot read file metadata".to_string())),
-    };
-    if len < 22 {
-        return (
-            false,
-            Some(format!(
-                "File too small ({} bytes, minimum 22 bytes required)",
-                len
-            )),
-        );
-    }
+    let mut results: Vec<(usize, R)> = thread::scope(|s| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                s.spawn(|| {
+                    let mut local = Vec::new();
+                    loop {
+                        let i = next_index.fetch_add(1, Ordering::Relaxed);
+                        let Some(item) = items.get(i) else {
+                            break;
+                        };
+                        local.push((i, f(item)));
+                    }
+                    local
+                })
+            })
+            .collect();
 
-    // Search last 64KB + 22 bytes for EOCD signature (0x06054b50)
-    let search_len = std::cmp::min(len, 65535 + 22) as i64;
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect()
+    });
+    results.sort_by_key(|(i, _)| *i);
+    results.into_iter().map(|(_, r)| r).collect()
+}
 
-    if file.seek(SeekFrom::End(-search_len)).is_err() {
-        return (false, Some("Cannot seek to end of file".to_string()));
-    }
+/// Result of scanning a single archive, including what to store in the cache
+struct ScannedFile {
+    entry: FileEntry,
+    path_key: String,
+    cached: CachedResult,
+    cache_hit: bool,
+}
 
-    let mut buffer = Vec::with_capacity(search_len as usize);
-    if file.read_to_end(&mut buffer).is_err() {
-        return (false, Some("Cannot read file contents".to_string()));
-    }
+fn scan_entry(
+    root: &Path,
+    entry: &DirEntry,
+    validator: &dyn ArchiveValidator,
+    options: &ScanOptions,
+    old_cache: &ScanCache,
+) -> Option<ScannedFile> {
+    let p = entry.path();
+
+    // Metadata access. A file renamed or removed since the directory walk
+    // (normal while a transfer is copying) is skipped rather than reported.
+    let metadata = match entry.metadata().or_else(|_| std::fs::metadata(p)) {
+        Ok(metadata) => metadata,
+        Err(e) => {
+            debug!("Skipping {}: {}", p.display(), e);
+            return None;
+        }
+    };
+    let size = metadata.len();
+    let name = entry.file_name().to_string_lossy().to_string();
 
-    // Search for signature: 0x06054b50 => [0x50, 0x4b, 0x05, 0x06]
-    let signature = [0x50, 0x4b, 0x05, 0x06];
+    let parent = options.dir_pattern.dir_key(root, p);
 
-    for i in (0..buffer.len().saturating_sub(3)).rev() {
-        if buffer[i] == signature[0]
-            && buffer[i + 1] == signature[1]
-            && buffer[i + 2] == signature[2]
-            && buffer[i + 3] == signature[3]
-        {
-            return (true, None);
+    // Integrity check (skipped when the cache has an unchanged entry)
+    let path_key = p.to_string_lossy().to_string();
+    let (mtime_secs, mtime_nsec) = (metadata.mtime(), metadata.mtime_nsec());
+    let cached = old_cache
+        .lookup(&path_key, size, mtime_secs, mtime_nsec, options.deep_verify)
+        .cloned();
+    let cache_hit = cached.is_some();
+    let mut result = cached.unwrap_or_else(|| {
+        let outcome = validator.validate(p, options.deep_verify);
+        CachedResult {
+            size,
+            mtime_secs,
+            mtime_nsec,
+            deep_verified: options.deep_verify || validator.reads_all_data(),
+            is_valid: outcome.is_ok(),
+            invalid_reason: outcome.err(),
+            sidecar: None,
         }
+    });
+
+    // Source-side checksum, only for structurally valid archives and only
+    // with --verify-sidecars. The outcome is cached apart from the archive's
+    // and reused while its checksum file is unchanged. A missing sidecar is
+    // reported but not cached, so it is looked for again next run.
+    let mut sidecar_failure = None;
+    if options.verify_sidecars && result.is_valid {
+        let cached = result
+            .sidecar
+            .as_ref()
+            .filter(|v| v.source.is_current(p))
+            .map(|v| v.failure.clone());
+        sidecar_failure = cached.unwrap_or_else(|| {
+            let (outcome, source) = sidecar::verify(p);
+            let failure = match outcome {
+                SidecarOutcome::Verified => None,
+                SidecarOutcome::Missing(reason) | SidecarOutcome::Failed(reason) => Some(reason),
+            };
+            result.sidecar = source.map(|source| SidecarVerdict {
+                source,
+                failure: failure.clone(),
+            });
+            failure
+        });
     }
 
-    (
-        false,
-        Some("Missing ZIP signature (corrupted or incomplete transfer)".to_string()),
-    )
+    // Use UNIX_EPOCH as fallback instead of now() to avoid falsely marking
+    // files as "recent" when we can't read their modification time
+    let modified: DateTime<Local> = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH).into();
+
+    Some(ScannedFile {
+        entry: FileEntry {
+            name,
+            size,
+            format: validator.format(),
+            is_valid: result.is_valid && sidecar_failure.is_none(),
+            invalid_reason: sidecar_failure.or_else(|| result.invalid_reason.clone()),
+            modified,
+            parent_dir: parent,
+        },
+        cached: result,
+        path_key,
+        cache_hit,
+    })
 }
 
 #[must_use]
-pub fn get_recent_files(path: &str, minutes: i64) -> Vec<String> {
+pub fn get_recent_files(path: &str, minutes: i64) -> Vec<FileWrite> {
     WalkDir::new(path)
         .into_iter()
         .filter_map(std::result::Result::ok)
-        .filter(|e| e.file_type().is_file())
+        // Our own manifests are not transfer activity
+        .filter(|e| e.file_type().is_file() && !manifest::is_manifest_file(e.path()))
         .filter_map(|e| {
             let m = e.metadata().ok()?;
             let mod_time: DateTime<Local> = m.modified().ok()?.into();
@@ -159,20 +310,7 @@ pub fn get_recent_files(path: &str, minutes: i64) -> Vec<String> {
             let diff = now.signed_duration_since(mod_time);
 
             if diff.num_minutes() < minutes {
-                let full_path = e.path().to_string_lossy();
-                // Extract path starting from "Line " onwards
-                let display_path = if let Some(idx) = full_path.find("Line ") {
-                    &full_path[idx..]
-                } else {
-                    &full_path
-                };
-
-                Some(format!(
-                    "  - {} ({}) at {}",
-                    display_path,
-                    human_bytes::human_bytes(m.len() as f64),
-                    mod_time.format("%Y-%m-%d %H:%M")
-                ))
+                Some(FileWrite::new(e.path(), m.len(), mod_time))
             } else {
                 None
             }
@@ -185,7 +323,7 @@ pub fn has_recent_activity(path: &str, minutes: i64) -> bool {
     WalkDir::new(path)
         .into_iter()
         .filter_map(std::result::Result::ok)
-        .filter(|e| e.file_type().is_file())
+        .filter(|e| e.file_type().is_file() && !manifest::is_manifest_file(e.path()))
         .any(|e| {
             if let Ok(m) = e.metadata()
                 && let Ok(mod_time) = m.modified()
@@ -197,3 +335,251 @@ pub fn has_recent_activity(path: &str, minutes: i64) -> bool {
             false
         })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ArchiveFormat;
+    use crate::validators::zip::tests::build_zip;
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use std::fs::File;
+    use std::io::Write;
+
+    #[test]
+    fn test_scan_cache_skips_unchanged_files() {
+        let temp = tempfile::tempdir().unwrap();
+        let base_dir = temp.path().to_str().unwrap();
+        let archive_dir = temp.path().join("Line B/Archive_Beam_B_2024-08-01");
+        std::fs::create_dir_all(&archive_dir).unwrap();
+
+        let zip_path = archive_dir.join("data.zip");
+        let zip = build_zip(&[("a.txt", b"hello")]);
+        std::fs::write(&zip_path, &zip).unwrap();
+        let mtime = std::fs::metadata(&zip_path).unwrap().modified().unwrap();
+
+        let search_dir = format!("{}/Line B", base_dir);
+        let options = ScanOptions::default().for_line(base_dir, "B");
+        let first = scan_files(&search_dir, &options);
+        assert!(first[0].is_valid);
+        assert!(std::path::Path::new(&scan_cache::cache_file_path(base_dir, "B")).exists());
+
+        // Corrupt the file but keep size and mtime: the cached result is reused
+        let file = File::options().write(true).open(&zip_path).unwrap();
+        (&file).write_all(&vec![0u8; zip.len()]).unwrap();
+        file.set_modified(mtime).unwrap();
+        drop(file);
+
+        let cached = scan_files(&search_dir, &options);
+        assert!(cached[0].is_valid);
+
+        let rescan = ScanOptions {
+            rescan_all: true,
+            ..options.clone()
+        };
+        let fresh = scan_files(&search_dir, &rescan);
+        assert!(!fresh[0].is_valid);
+    }
+
+    #[test]
+    fn test_cancelled_scan_leaves_cache_untouched() {
+        let temp = tempfile::tempdir().unwrap();
+        let base_dir = temp.path().to_str().unwrap();
+        let zip = build_zip(&[("a.txt", b"hello")]);
+        std::fs::write(temp.path().join("data.zip"), &zip).unwrap();
+
+        let cancel = Arc::new(AtomicBool::new(true));
+        let options = ScanOptions {
+            cancel: Some(Arc::clone(&cancel)),
+            ..ScanOptions::default().for_line(base_dir, "B")
+        };
+        assert!(scan_files(base_dir, &options).is_empty());
+        assert!(!Path::new(&scan_cache::cache_file_path(base_dir, "B")).exists());
+
+        cancel.store(false, Ordering::Relaxed);
+        assert_eq!(scan_files(base_dir, &options).len(), 1);
+        assert!(Path::new(&scan_cache::cache_file_path(base_dir, "B")).exists());
+    }
+
+    #[test]
+    fn test_sidecar_results_reported_as_bad_files() {
+        let temp = tempfile::tempdir().unwrap();
+        let base_dir = temp.path().to_str().unwrap();
+        let zip = build_zip(&[("a.txt", b"hello")]);
+        std::fs::write(temp.path().join("good.zip"), &zip).unwrap();
+        std::fs::write(temp.path().join("bad.zip"), &zip).unwrap();
+        std::fs::write(temp.path().join("orphan.zip"), &zip).unwrap();
+
+        let digest = manifest::hash_file::<sha2::Sha256>(&temp.path().join("good.zip")).unwrap();
+        std::fs::write(temp.path().join("good.zip.sha256"), &digest).unwrap();
+        std::fs::write(temp.path().join("bad.zip.sha256"), "0".repeat(64)).unwrap();
+
+        let options = ScanOptions {
+            verify_sidecars: true,
+            ..ScanOptions::default()
+        }
+        .for_line(base_dir, "B");
+        let files = scan_files(base_dir, &options);
+        let reason = |name: &str| {
+            let f = files.iter().find(|f| f.name == name).unwrap();
+            (f.is_valid, f.invalid_reason.clone().unwrap_or_default())
+        };
+
+        assert_eq!(reason("good.zip"), (true, String::new()));
+        let (valid, bad_reason) = reason("bad.zip");
+        assert!(!valid);
+        assert!(bad_reason.starts_with("SHA-256 mismatch against bad.zip.sha256"));
+        let (valid, orphan_reason) = reason("orphan.zip");
+        assert!(!valid);
+        assert!(orphan_reason.starts_with("Missing checksum sidecar"));
+
+        // A sidecar arriving later is picked up although the archive is cached
+        let digest = manifest::hash_file::<sha2::Sha256>(&temp.path().join("orphan.zip")).unwrap();
+        std::fs::write(temp.path().join("orphan.zip.sha256"), &digest).unwrap();
+        let files = scan_files(base_dir, &options);
+        assert!(files.iter().all(|f| f.name == "bad.zip" || f.is_valid));
+    }
+
+    #[test]
+    fn test_fixed_sidecar_makes_cached_archive_good_again() {
+        let temp = tempfile::tempdir().unwrap();
+        let base_dir = temp.path().to_str().unwrap();
+        let zip_path = temp.path().join("a.zip");
+        std::fs::write(&zip_path, build_zip(&[("a.txt", b"hello")])).unwrap();
+        let sidecar_path = temp.path().join("a.zip.sha256");
+        std::fs::write(&sidecar_path, "0".repeat(64)).unwrap();
+
+        let options = ScanOptions {
+            verify_sidecars: true,
+            ..ScanOptions::default()
+        }
+        .for_line(base_dir, "B");
+        assert!(!scan_files(base_dir, &options)[0].is_valid);
+
+        // A cached sidecar failure is not reported without --verify-sidecars
+        let plain = ScanOptions {
+            verify_sidecars: false,
+            ..options.clone()
+        };
+        assert!(scan_files(base_dir, &plain)[0].is_valid);
+        assert!(!scan_files(base_dir, &options)[0].is_valid);
+
+        // Fixing the sidecar is picked up although the archive is unchanged
+        let digest = manifest::hash_file::<sha2::Sha256>(&zip_path).unwrap();
+        let file = File::create(&sidecar_path).unwrap();
+        (&file).write_all(digest.as_bytes()).unwrap();
+        file.set_modified(SystemTime::now() + std::time::Duration::from_secs(5))
+            .unwrap();
+        drop(file);
+        let files = scan_files(base_dir, &options);
+        assert!(files[0].is_valid, "{:?}", files[0].invalid_reason);
+    }
+
+    #[test]
+    fn test_parallel_scan_matches_sequential() {
+        let temp = tempfile::tempdir().unwrap();
+        for day in 1..=5 {
+            let dir = temp.path().join(format!("Archive_Beam_B_2024-08-0{}", day));
+            std::fs::create_dir_all(&dir).unwrap();
+            for n in 0..6 {
+                let bytes = if (day + n) % 4 == 0 {
+                    vec![0u8; 64]
+                } else {
+                    build_zip(&[("a.txt", b"hello")])
+                };
+                std::fs::write(dir.join(format!("Device{}.zip", n)), bytes).unwrap();
+            }
+        }
+
+        let path = temp.path().to_str().unwrap();
+        let summarize = |threads: usize| -> Vec<(String, String, bool)> {
+            let options = ScanOptions {
+                threads,
+                ..ScanOptions::default()
+            };
+            scan_files(path, &options)
+                .into_iter()
+                .map(|f| (f.parent_dir, f.name, f.is_valid))
+                .collect()
+        };
+
+        let sequential = summarize(1);
+        assert_eq!(sequential.len(), 30);
+        assert!(sequential.iter().any(|(_, _, valid)| !valid));
+        assert_eq!(summarize(4), sequential);
+        assert_eq!(summarize(64), sequential);
+    }
+
+    #[test]
+    fn test_scan_picks_up_all_formats() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join("Archive_Beam_B_2024-08-01");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.zip"), build_zip(&[])).unwrap();
+        std::fs::write(dir.join("b.tar"), vec![0u8; 1024]).unwrap();
+        std::fs::write(dir.join("c.7z"), b"not really 7z").unwrap();
+        std::fs::write(dir.join("notes.txt"), b"ignored").unwrap();
+        std::fs::write(dir.join("log.gz"), b"not an archive").unwrap();
+
+        let files = scan_files(temp.path().to_str().unwrap(), &ScanOptions::default());
+        let summary: Vec<_> = files
+            .iter()
+            .map(|f| (f.name.as_str(), f.format, f.is_valid))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("a.zip", ArchiveFormat::Zip, true),
+                ("b.tar", ArchiveFormat::Tar, true),
+                ("c.7z", ArchiveFormat::SevenZip, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cached_tar_gz_result_serves_deep_scan() {
+        let temp = tempfile::tempdir().unwrap();
+        let base_dir = temp.path().to_str().unwrap();
+        let path = temp.path().join("a.tar.gz");
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&[0u8; 1024]).unwrap();
+        let tgz = encoder.finish().unwrap();
+        std::fs::write(&path, &tgz).unwrap();
+        let mtime = std::fs::metadata(&path).unwrap().modified().unwrap();
+
+        let options = ScanOptions::default().for_line(base_dir, "B");
+        assert!(scan_files(base_dir, &options)[0].is_valid);
+
+        // The default scan already inflated everything: a deep scan of the
+        // unchanged file reuses it instead of inflating again
+        let file = File::options().write(true).open(&path).unwrap();
+        (&file).write_all(&vec![0u8; tgz.len()]).unwrap();
+        file.set_modified(mtime).unwrap();
+        drop(file);
+        let deep = ScanOptions {
+            deep_verify: true,
+            ..options.clone()
+        };
+        assert!(scan_files(base_dir, &deep)[0].is_valid);
+    }
+
+    #[test]
+    fn test_nested_layout_keys_files_by_day_directory() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join("BeamB/2025/03/14/cam1");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a.zip"), build_zip(&[("a.txt", b"hello")])).unwrap();
+
+        let path = temp.path().to_str().unwrap();
+        let options = ScanOptions {
+            dir_pattern: "Beam{line}/{yyyy}/{mm}/{dd}".parse().unwrap(),
+            ..ScanOptions::default()
+        };
+        let files = scan_files(path, &options);
+        assert_eq!(files[0].parent_dir, "BeamB/2025/03/14");
+
+        let (total, per_dir) = get_total_and_per_dir_sizes(path, &options.dir_pattern);
+        assert_eq!(per_dir.keys().collect::<Vec<_>>(), vec!["BeamB/2025/03/14"]);
+        assert_eq!(per_dir["BeamB/2025/03/14"], total);
+    }
+}
@@ -0,0 +1,387 @@
+use crate::html_renderer::AuditReport;
+use crate::json_report;
+use crate::ranking;
+use crate::sites::Site;
+use log::{debug, warn};
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
+use std::thread::{self, JoinHandle};
+use tiny_http::{Header, Method, Request, Response, StatusCode};
+
+const HTML: &str = "text/html; charset=utf-8";
+const JSON: &str = "application/json";
+
+struct Page {
+    content_type: &'static str,
+    body: String,
+}
+
+/// Every page served, rendered once from a completed audit so requests
+/// never wait for one
+#[derive(Default)]
+pub struct Snapshot {
+    pages: HashMap<String, Page>,
+}
+
+impl Snapshot {
+    /// JSON endpoints for the reports of every line:
+    ///
+    /// - `/api/report`: every line, as written by `--format json`
+    /// - `/api/ranking`: monthly rankings, combined and per line
+    /// - `/api/sites/<site>/lines/<line>`: one line
+    /// - `/api/sites/<site>/lines/<line>/bad-files`: every bad file, not truncated
+    /// - `/api/sites/<site>/lines/<line>/gaps`: missing weekdays
+    /// - `/api/sites/<site>/lines/<line>/ranking`: the line's monthly ranking
+    ///
+    /// The single unnamed site given by `--base-dir` has its lines under
+    /// `/api/lines/<line>` instead.
+    #[must_use]
+    pub fn new(sites: &[Site], site_reports: &[Vec<(String, AuditReport)>]) -> Self {
+        let mut snapshot = Self::default();
+        snapshot.insert(
+            "/api/report",
+            JSON,
+            json_report::render_sites(sites, site_reports),
+        );
+
+        let line_rankings: Vec<_> = site_reports
+            .iter()
+            .flatten()
+            .filter_map(|(_, report)| report.monthly_ranking.clone())
+            .collect();
+        snapshot.insert(
+            "/api/ranking",
+            JSON,
+            json_report::render_rankings(
+                &ranking::combine_rankings(&line_rankings),
+                &line_rankings,
+            ),
+        );
+
+        for (site, reports) in sites.iter().zip(site_reports) {
+            let name = site.name.as_deref();
+            let site_prefix = match name {
+                Some(name) => format!("/api/sites/{name}"),
+                None => "/api".to_string(),
+            };
+            for (line_id, report) in reports {
+                let prefix = format!("{site_prefix}/lines/{line_id}");
+                snapshot.insert(
+                    &format!("{prefix}/bad-files"),
+                    JSON,
+                    json_report::render_line_bad_files(name, line_id, report),
+                );
+                snapshot.insert(
+                    &format!("{prefix}/gaps"),
+                    JSON,
+                    json_report::render_line_gaps(name, line_id, report),
+                );
+                snapshot.insert(
+                    &format!("{prefix}/ranking"),
+                    JSON,
+                    json_report::render_line_ranking(name, line_id, report),
+                );
+                snapshot.insert(
+                    &prefix,
+                    JSON,
+                    json_report::render_line(name, &site.base_dir, line_id, report),
+                );
+            }
+        }
+        snapshot
+    }
+
+    /// The dashboard, at `/` and `/index.html`
+    pub fn set_dashboard(&mut self, html: String) {
+        self.insert("/index.html", HTML, html.clone());
+        self.insert("/", HTML, html);
+    }
+
+    fn insert(&mut self, path: &str, content_type: &'static str, body: String) {
+        self.pages
+            .insert(path.to_string(), Page { content_type, body });
+    }
+}
+
+/// HTTP server for the dashboard and the JSON API, answering from the
+/// latest published snapshot. Stops when dropped.
+pub struct Server {
+    http: Arc<tiny_http::Server>,
+    latest: Arc<RwLock<Option<Snapshot>>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl Server {
+    /// Listen on `addr` (e.g. `127.0.0.1:8080`) and answer requests on a
+    /// background thread
+    pub fn start(addr: &str) -> Result<Self, String> {
+        let http = Arc::new(
+            tiny_http::Server::http(addr).map_err(|e| format!("Cannot listen on {addr}: {e}"))?,
+        );
+        let latest: Arc<RwLock<Option<Snapshot>>> = Arc::default();
+        let worker = {
+            let http = Arc::clone(&http);
+            let latest = Arc::clone(&latest);
+            thread::spawn(move || {
+                for request in http.incoming_requests() {
+                    handle(request, &latest);
+                }
+            })
+        };
+        Ok(Self {
+            http,
+            latest,
+            worker: Some(worker),
+        })
+    }
+
+    #[must_use]
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.http.server_addr().to_ip()
+    }
+
+    /// Serve `snapshot` from now on
+    pub fn publish(&self, snapshot: Snapshot) {
+        *self.latest.write().unwrap_or_else(|e| e.into_inner()) = Some(snapshot);
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.http.unblock();
+        if let Some(worker) = self.worker.take() {
+            worker.join().ok();
+        }
+    }
+}
+
+/// Status, content type and body for a request
+fn route(snapshot: Option<&Snapshot>, method: &Method, url: &str) -> (u16, &'static str, String) {
+    if !matches!(method, Method::Get | Method::Head) {
+        return (405, JSON, error_body("Only GET is supported"));
+    }
+    let Some(snapshot) = snapshot else {
+        return (
+            503,
+            JSON,
+            error_body("First audit still running, try again shortly"),
+        );
+    };
+    let path = percent_decode(url.split(['?', '#']).next().unwrap_or_default());
+    let path = if path.len() > 1 {
+        path.trim_end_matches('/')
+    } else {
+        &path
+    };
+    match snapshot.pages.get(path) {
+        Some(page) => (200, page.content_type, page.body.clone()),
+        None => (404, JSON, error_body(&format!("No such page: {path}"))),
+    }
+}
+
+/// Decode `%XX` escapes, so site and line names with spaces can be requested
+fn percent_decode(path: &str) -> String {
+    let bytes = path.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| bytes.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn error_body(message: &str) -> String {
+    serde_json::json!({ "error": message }).to_string()
+}
+
+fn handle(request: Request, latest: &RwLock<Option<Snapshot>>) {
+    let (status, content_type, body) = {
+        let snapshot = latest.read().unwrap_or_else(|e| e.into_inner());
+        route(snapshot.as_ref(), request.method(), request.url())
+    };
+    debug!("{} {} -> {}", request.method(), request.url(), status);
+
+    // HEAD gets the status and headers of a GET, Content-Length included,
+    // but no body
+    let length = body.len();
+    let body = if *request.method() == Method::Head {
+        Vec::new()
+    } else {
+        body.into_bytes()
+    };
+    let mut response = Response::new(
+        StatusCode(status),
+        vec![
+            Header::from_bytes(&b"Content-Type"[..], content_type.as_bytes())
+                .expect("valid header"),
+        ],
+        Cursor::new(body),
+        Some(length),
+        None,
+    );
+    if status == 503 {
+        response
+            .add_header(Header::from_bytes(&b"Retry-After"[..], &b"30"[..]).expect("valid header"));
+    }
+    if let Err(e) = request.respond(response) {
+        warn!("Cannot send HTTP response: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gap_analysis::GapReport;
+    use chrono::NaiveDate;
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+
+    fn report() -> AuditReport {
+        AuditReport {
+            total_size: 4096,
+            total_files: 2,
+            speed_bps: 0,
+            since_timestamp: "2025-03-14 10:00".to_string(),
+            recent_files: Vec::new(),
+            recent_files_live: false,
+            redundancy_check: None,
+            integrity_stats: None,
+            gap_report: Some(GapReport {
+                start_date: NaiveDate::from_ymd_opt(2025, 3, 13).unwrap(),
+                end_date: NaiveDate::from_ymd_opt(2025, 3, 17).unwrap(),
+                missing_weekdays: vec![NaiveDate::from_ymd_opt(2025, 3, 14).unwrap()],
+                skipped_weekends: 2,
+                is_empty: false,
+                folders: Default::default(),
+            }),
+            estimates_report: None,
+            anomaly_report: None,
+            bad_files_report: None,
+            manifest_report: None,
+            inventory_report: None,
+            member_report: None,
+            max_bad_per_archive: 5,
+            history: Vec::new(),
+            files: Vec::new(),
+            anomaly_threshold: 0.5,
+            archive_pages: None,
+            monthly_ranking: None,
+            holidays: Vec::new(),
+        }
+    }
+
+    fn request(addr: SocketAddr, method: &str, path: &str) -> String {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "{method} {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n"
+        )
+        .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    fn get(addr: SocketAddr, path: &str) -> String {
+        request(addr, "GET", path)
+    }
+
+    #[test]
+    fn test_serves_latest_snapshot_on_localhost() {
+        let server = Server::start("127.0.0.1:0").unwrap();
+        let addr = server.local_addr().unwrap();
+
+        assert!(get(addr, "/").starts_with("HTTP/1.1 503"));
+
+        let day = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap();
+        let sites = [Site::local("/data", vec!["B".to_string()], day, day)];
+        let mut snapshot = Snapshot::new(&sites, &[vec![("B".to_string(), report())]]);
+        snapshot.set_dashboard("<html>dashboard</html>".to_string());
+        server.publish(snapshot);
+
+        let dashboard = get(addr, "/");
+        assert!(dashboard.starts_with("HTTP/1.1 200"));
+        assert!(dashboard.contains("text/html"));
+        assert!(dashboard.ends_with("<html>dashboard</html>"));
+
+        let gaps = get(addr, "/api/lines/B/gaps?pretty");
+        assert!(gaps.starts_with("HTTP/1.1 200"));
+        assert!(gaps.contains("\"kind\": \"gaps\""));
+        assert!(gaps.contains("\"2025-03-14\""));
+
+        assert!(get(addr, "/api/lines/B/bad-files").contains("\"total_count\": 0"));
+        assert!(get(addr, "/api/report").contains("\"kind\": \"audit\""));
+        assert!(get(addr, "/api/ranking").contains("\"kind\": \"ranking\""));
+        assert!(get(addr, "/api/lines/Z").starts_with("HTTP/1.1 404"));
+
+        // HEAD: the headers of the GET and no body
+        let head = request(addr, "HEAD", "/");
+        assert!(head.starts_with("HTTP/1.1 200"));
+        assert!(head.contains("Content-Length: 22\r\n"));
+        assert!(head.ends_with("\r\n\r\n"));
+    }
+
+    #[test]
+    fn test_lines_of_named_sites_keyed_by_site() {
+        let day = NaiveDate::from_ymd_opt(2025, 3, 14).unwrap();
+        let mut north = Site::local("/data/north", vec!["B".to_string()], day, day);
+        north.name = Some("North 1".to_string());
+        let mut south = Site::local("/data/south", vec!["B".to_string()], day, day);
+        south.name = Some("South".to_string());
+        let mut south_report = report();
+        south_report
+            .gap_report
+            .as_mut()
+            .unwrap()
+            .missing_weekdays
+            .clear();
+        let snapshot = Snapshot::new(
+            &[north, south],
+            &[
+                vec![("B".to_string(), report())],
+                vec![("B".to_string(), south_report)],
+            ],
+        );
+
+        let (status, _, north_gaps) = route(
+            Some(&snapshot),
+            &Method::Get,
+            "/api/sites/North%201/lines/B/gaps",
+        );
+        assert_eq!(status, 200);
+        assert!(north_gaps.contains("\"site\": \"North 1\""));
+        assert!(north_gaps.contains("\"2025-03-14\""));
+
+        let (status, _, south_gaps) = route(
+            Some(&snapshot),
+            &Method::Get,
+            "/api/sites/South/lines/B/gaps",
+        );
+        assert_eq!(status, 200);
+        assert!(!south_gaps.contains("\"2025-03-14\""));
+
+        let (status, _, south_line) =
+            route(Some(&snapshot), &Method::Get, "/api/sites/South/lines/B");
+        assert_eq!(status, 200);
+        assert!(south_line.contains("/data/south"));
+
+        // No short form once sites have names
+        let (status, _, _) = route(Some(&snapshot), &Method::Get, "/api/lines/B");
+        assert_eq!(status, 404);
+    }
+}
@@ -0,0 +1,305 @@
+use crate::manifest;
+use md5::Md5;
+use sha2::Sha256;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Algorithm {
+    Sha256,
+    Md5,
+}
+
+impl Algorithm {
+    fn from_file_name(name: &str) -> Option<Self> {
+        let lower = name.to_ascii_lowercase();
+        if lower.ends_with(".sha256") || lower == "sha256sums" {
+            Some(Self::Sha256)
+        } else if lower.ends_with(".md5") || lower == "md5sums" {
+            Some(Self::Md5)
+        } else {
+            None
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Sha256 => "SHA-256",
+            Self::Md5 => "MD5",
+        }
+    }
+
+    fn hex_len(self) -> usize {
+        match self {
+            Self::Sha256 => 64,
+            Self::Md5 => 32,
+        }
+    }
+}
+
+/// Checksum the source side recorded for one archive
+#[derive(Debug, PartialEq)]
+struct Expected {
+    algorithm: Algorithm,
+    hex: String,
+    /// Sidecar file name, for error messages
+    source: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SidecarOutcome {
+    Verified,
+    /// No sidecar or per-day list mentions the archive
+    Missing(String),
+    /// Checksum mismatch, malformed sidecar or unreadable archive
+    Failed(String),
+}
+
+/// Checksum file an outcome was read from, as it was when read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SidecarSource {
+    pub name: String,
+    pub mtime_secs: i64,
+    pub mtime_nsec: i64,
+}
+
+impl SidecarSource {
+    fn stat(dir: &Path, name: &str) -> Option<Self> {
+        let metadata = fs::metadata(dir.join(name)).ok()?;
+        Some(Self {
+            name: name.to_string(),
+            mtime_secs: metadata.mtime(),
+            mtime_nsec: metadata.mtime_nsec(),
+        })
+    }
+
+    /// Whether an outcome read from this file still holds for `archive`:
+    /// the file is unchanged and, for a per-day list, no dedicated sidecar
+    /// has appeared since
+    #[must_use]
+    pub fn is_current(&self, archive: &Path) -> bool {
+        let Some(name) = archive.file_name().map(|n| n.to_string_lossy().to_string()) else {
+            return false;
+        };
+        let dir = archive.parent().unwrap_or_else(|| Path::new("."));
+        let dedicated = ["sha256", "md5"].map(|ext| format!("{}.{}", name, ext));
+        if !dedicated.contains(&self.name) && dedicated.iter().any(|d| dir.join(d).exists()) {
+            return false;
+        }
+        Self::stat(dir, &self.name).as_ref() == Some(self)
+    }
+}
+
+/// Verify `archive` against the checksum the source side wrote next to it,
+/// also returning the checksum file used (none when the sidecar is missing).
+///
+/// A dedicated `<archive>.sha256` / `<archive>.md5` sidecar wins; otherwise
+/// any per-day checksum list in the same directory (`*.sha256`, `*.md5`,
+/// `SHA256SUMS`, `MD5SUMS`) that names the archive is used.
+#[must_use]
+pub fn verify(archive: &Path) -> (SidecarOutcome, Option<SidecarSource>) {
+    let dir = archive.parent().unwrap_or_else(|| Path::new("."));
+    let expected = match find_expected(archive) {
+        Ok(Some(expected)) => expected,
+        Ok(None) => {
+            return (
+                SidecarOutcome::Missing(
+                    "Missing checksum sidecar (.sha256/.md5 or per-day checksum list)".to_string(),
+                ),
+                None,
+            );
+        }
+        Err((reason, source)) => {
+            let source = source.and_then(|name| SidecarSource::stat(dir, &name));
+            return (SidecarOutcome::Failed(reason), source);
+        }
+    };
+    let source = SidecarSource::stat(dir, &expected.source);
+
+    let actual = match expected.algorithm {
+        Algorithm::Sha256 => manifest::hash_file::<Sha256>(archive),
+        Algorithm::Md5 => manifest::hash_file::<Md5>(archive),
+    };
+    let outcome = match actual {
+        Ok(actual) if actual == expected.hex => SidecarOutcome::Verified,
+        Ok(actual) => SidecarOutcome::Failed(format!(
+            "{} mismatch against {} (expected {}, actual {})",
+            expected.algorithm.label(),
+            expected.source,
+            expected.hex,
+            actual
+        )),
+        Err(e) => SidecarOutcome::Failed(format!("Cannot hash archive: {}", e)),
+    };
+    (outcome, source)
+}
+
+/// The expected checksum, or why it cannot be read along with the sidecar
+/// at fault (if any)
+fn find_expected(archive: &Path) -> Result<Option<Expected>, (String, Option<String>)> {
+    let Some(name) = archive.file_name().map(|n| n.to_string_lossy().to_string()) else {
+        return Ok(None);
+    };
+    let dir = archive.parent().unwrap_or_else(|| Path::new("."));
+
+    // 1. Dedicated per-archive sidecar
+    for (ext, algorithm) in [("sha256", Algorithm::Sha256), ("md5", Algorithm::Md5)] {
+        let sidecar_name = format!("{}.{}", name, ext);
+        let sidecar = dir.join(&sidecar_name);
+        let Ok(content) = fs::read_to_string(&sidecar) else {
+            continue;
+        };
+        // Either a bare digest or `sha256sum`-style `<hex>  <name>`
+        let hex = content.split_whitespace().next().unwrap_or("");
+        return parse_hex(hex, algorithm)
+            .map(|hex| {
+                Some(Expected {
+                    algorithm,
+                    hex,
+                    source: sidecar_name.clone(),
+                })
+            })
+            .ok_or_else(|| {
+                (
+                    format!("Malformed checksum sidecar {}", sidecar_name),
+                    Some(sidecar_name.clone()),
+                )
+            });
+    }
+
+    // 2. Per-day checksum list naming the archive
+    let mut lists: Vec<(PathBuf, String, Algorithm)> = fs::read_dir(dir)
+        .map_err(|e| (format!("Cannot list archive directory: {}", e), None))?
+        .filter_map(std::result::Result::ok)
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_file()))
+        .filter(|e| !manifest::is_manifest_file(&e.path()))
+        .filter_map(|e| {
+            let list_name = e.file_name().to_string_lossy().to_string();
+            let algorithm = Algorithm::from_file_name(&list_name)?;
+            Some((e.path(), list_name, algorithm))
+        })
+        .collect();
+    lists.sort_by(|a, b| a.1.cmp(&b.1));
+
+    for (path, list_name, algorithm) in lists {
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        if let Some(hex) = lookup_in_list(&content, &name, algorithm) {
+            return Ok(Some(Expected {
+                algorithm,
+                hex,
+                source: list_name,
+            }));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Find the digest for `name` in a `<hex>  <path>` / `<hex> *<path>` list.
+/// Paths may carry Windows or Unix directory prefixes.
+fn lookup_in_list(content: &str, name: &str, algorithm: Algorithm) -> Option<String> {
+    content.lines().find_map(|line| {
+        let (hex, rest) = line.trim().split_once(char::is_whitespace)?;
+        let listed = rest.trim_start().trim_start_matches('*');
+        let base = listed.rsplit(['/', '\\']).next()?;
+        if base == name {
+            parse_hex(hex, algorithm)
+        } else {
+            None
+        }
+    })
+}
+
+fn parse_hex(hex: &str, algorithm: Algorithm) -> Option<String> {
+    // Tolerate a UTF-8 BOM, as written by some Windows tools
+    let hex = hex.trim_start_matches('\u{feff}');
+    (hex.len() == algorithm.hex_len() && hex.chars().all(|c| c.is_ascii_hexdigit()))
+        .then(|| hex.to_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ABC_SHA256: &str = "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad";
+    const ABC_MD5: &str = "900150983cd24fb0d6963f7d28e17f72";
+
+    fn setup(files: &[(&str, &str)]) -> tempfile::TempDir {
+        let temp = tempfile::tempdir().unwrap();
+        fs::write(temp.path().join("a.zip"), "abc").unwrap();
+        for (name, content) in files {
+            fs::write(temp.path().join(name), content).unwrap();
+        }
+        temp
+    }
+
+    #[test]
+    fn test_per_archive_sidecars() {
+        let temp = setup(&[("a.zip.sha256", &format!("{}  a.zip\n", ABC_SHA256))]);
+        assert_eq!(
+            verify(&temp.path().join("a.zip")).0,
+            SidecarOutcome::Verified
+        );
+
+        // Uppercase bare digest, as written by PowerShell Get-FileHash
+        let temp = setup(&[("a.zip.md5", &ABC_MD5.to_uppercase())]);
+        assert_eq!(
+            verify(&temp.path().join("a.zip")).0,
+            SidecarOutcome::Verified
+        );
+    }
+
+    #[test]
+    fn test_per_day_list() {
+        let list = format!(
+            "{}  other.zip\n{} *C:\\Data\\Archive\\a.zip\n",
+            ABC_MD5.replace('9', "8"),
+            ABC_MD5
+        );
+        let temp = setup(&[("checksums.md5", &list)]);
+        let archive = temp.path().join("a.zip");
+        let (outcome, source) = verify(&archive);
+        assert_eq!(outcome, SidecarOutcome::Verified);
+        let source = source.unwrap();
+        assert_eq!(source.name, "checksums.md5");
+        assert!(source.is_current(&archive));
+
+        // A dedicated sidecar arriving later takes over from the list
+        fs::write(temp.path().join("a.zip.md5"), ABC_MD5).unwrap();
+        assert!(!source.is_current(&archive));
+    }
+
+    #[test]
+    fn test_mismatch_reports_both_digests() {
+        let wrong = ABC_SHA256.replace('b', "c");
+        let temp = setup(&[("a.zip.sha256", &wrong)]);
+        let (SidecarOutcome::Failed(reason), _) = verify(&temp.path().join("a.zip")) else {
+            panic!("expected mismatch");
+        };
+        assert!(reason.starts_with("SHA-256 mismatch against a.zip.sha256"));
+        assert!(reason.contains(&wrong));
+        assert!(reason.contains(ABC_SHA256));
+    }
+
+    #[test]
+    fn test_missing_and_malformed_sidecars() {
+        // Our own manifest is not a source-side checksum
+        let temp = setup(&[(
+            manifest::MANIFEST_FILE_NAME,
+            &format!("{}  a.zip\n", ABC_SHA256),
+        )]);
+        assert!(matches!(
+            verify(&temp.path().join("a.zip")),
+            (SidecarOutcome::Missing(_), None)
+        ));
+
+        let temp = setup(&[("a.zip.sha256", "not-a-digest")]);
+        assert_eq!(
+            verify(&temp.path().join("a.zip")).0,
+            SidecarOutcome::Failed("Malformed checksum sidecar a.zip.sha256".to_string())
+        );
+    }
+}
@@ -0,0 +1,245 @@
+use crate::lines;
+use chrono::NaiveDate;
+use log::debug;
+use std::fs;
+
+/// One storage server running the transfer setup
+#[derive(Debug, Clone, PartialEq)]
+pub struct Site {
+    /// `None` for the single site given by `--base-dir`
+    pub name: Option<String>,
+    pub base_dir: String,
+    pub lines: Vec<String>,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+}
+
+impl Site {
+    /// The site given on the command line, unnamed
+    #[must_use]
+    pub fn local(
+        base_dir: &str,
+        lines: Vec<String>,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Self {
+        Self {
+            name: None,
+            base_dir: base_dir.to_string(),
+            lines,
+            start_date,
+            end_date,
+        }
+    }
+}
+
+/// Load a sites config: one `[name]` section per site with `BASE_DIR`,
+/// optional `LINES` (comma separated, discovered from `Line *` folders when
+/// omitted) and optional `START_DATE` / `END_DATE` (YYYY-MM-DD, defaulting
+/// to `default_start` / `default_end`). `#` starts a comment.
+///
+/// State files live in each site's base dir, so two sites may not share one.
+pub fn load_sites(
+    config_path: &str,
+    default_start: NaiveDate,
+    default_end: NaiveDate,
+) -> Result<Vec<Site>, String> {
+    let content = fs::read_to_string(config_path)
+        .map_err(|e| format!("Cannot read sites config {}: {}", config_path, e))?;
+    parse_sites(&content, default_start, default_end).map_err(|e| format!("{}: {}", config_path, e))
+}
+
+/// Site section as written, before defaults are applied
+#[derive(Default)]
+struct RawSite {
+    name: String,
+    base_dir: Option<String>,
+    lines: Option<Vec<String>>,
+    start_date: Option<NaiveDate>,
+    end_date: Option<NaiveDate>,
+}
+
+fn parse_sites(
+    content: &str,
+    default_start: NaiveDate,
+    default_end: NaiveDate,
+) -> Result<Vec<Site>, String> {
+    let mut raw_sites: Vec<RawSite> = Vec::new();
+
+    for (number, line) in content.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            let name = name.trim();
+            if name.is_empty() {
+                return Err(format!("line {}: empty site name", number + 1));
+            }
+            if raw_sites.iter().any(|s| s.name == name) {
+                return Err(format!("line {}: duplicate site '{}'", number + 1, name));
+            }
+            raw_sites.push(RawSite {
+                name: name.to_string(),
+                ..RawSite::default()
+            });
+            continue;
+        }
+
+        let Some(site) = raw_sites.last_mut() else {
+            return Err(format!(
+                "line {}: setting outside of a [site] section",
+                number + 1
+            ));
+        };
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(format!("line {}: expected KEY=VALUE", number + 1));
+        };
+        let value = value.trim().trim_matches('"');
+        let parse_date = |v: &str| {
+            NaiveDate::parse_from_str(v, "%Y-%m-%d")
+                .map_err(|_| format!("line {}: invalid date '{}'", number + 1, v))
+        };
+        match key.trim() {
+            "BASE_DIR" => site.base_dir = Some(value.trim_end_matches('/').to_string()),
+            "LINES" => {
+                site.lines = Some(
+                    value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|l| !l.is_empty())
+                        .map(str::to_string)
+                        .collect(),
+                );
+            }
+            "START_DATE" => site.start_date = Some(parse_date(value)?),
+            "END_DATE" => site.end_date = Some(parse_date(value)?),
+            other => return Err(format!("line {}: unknown key '{}'", number + 1, other)),
+        }
+    }
+
+    if raw_sites.is_empty() {
+        return Err("no [site] sections".to_string());
+    }
+
+    let mut sites: Vec<Site> = Vec::new();
+    for raw in raw_sites {
+        let base_dir = raw
+            .base_dir
+            .ok_or_else(|| format!("site '{}' has no BASE_DIR", raw.name))?;
+        if let Some(other) = sites.iter().find(|s| s.base_dir == base_dir) {
+            return Err(format!(
+                "sites '{}' and '{}' share BASE_DIR {} (state files would collide)",
+                other.name.as_deref().unwrap_or_default(),
+                raw.name,
+                base_dir
+            ));
+        }
+        let lines = match raw.lines {
+            Some(lines) if !lines.is_empty() => lines,
+            _ => lines::resolve_lines(&base_dir),
+        };
+        if lines.is_empty() {
+            return Err(format!(
+                "site '{}' has no lines (no LINES and no 'Line *' folders in {})",
+                raw.name, base_dir
+            ));
+        }
+        debug!("Site {}: {} with lines {:?}", raw.name, base_dir, lines);
+        sites.push(Site {
+            name: Some(raw.name),
+            base_dir,
+            lines,
+            start_date: raw.start_date.unwrap_or(default_start),
+            end_date: raw.end_date.unwrap_or(default_end),
+        });
+    }
+
+    Ok(sites)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_parse_sites() {
+        let temp = tempfile::tempdir().unwrap();
+        fs::create_dir_all(temp.path().join("north/Line C")).unwrap();
+        let north = temp.path().join("north");
+        let config = format!(
+            "# storage servers\n\
+             [cluster]\n\
+             BASE_DIR=/data/storage/samba_share_cluster/\n\
+             LINES=B, A\n\
+             START_DATE=2024-07-29\n\
+             \n\
+             [north]\n\
+             BASE_DIR=\"{}\"\n\
+             END_DATE=2025-06-30  # decommissioned\n",
+            north.display()
+        );
+
+        let sites = parse_sites(&config, date(2024, 1, 1), date(2025, 12, 12)).unwrap();
+        assert_eq!(
+            sites,
+            vec![
+                Site {
+                    name: Some("cluster".to_string()),
+                    base_dir: "/data/storage/samba_share_cluster".to_string(),
+                    lines: vec!["B".to_string(), "A".to_string()],
+                    start_date: date(2024, 7, 29),
+                    end_date: date(2025, 12, 12),
+                },
+                Site {
+                    name: Some("north".to_string()),
+                    base_dir: north.to_string_lossy().to_string(),
+                    lines: vec!["C".to_string()],
+                    start_date: date(2024, 1, 1),
+                    end_date: date(2025, 6, 30),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_sites_config() {
+        let (start, end) = (date(2024, 1, 1), date(2025, 12, 12));
+        for (config, error) in [
+            ("", "no [site] sections"),
+            (
+                "BASE_DIR=/data",
+                "line 1: setting outside of a [site] section",
+            ),
+            ("[a]\nLINES=A", "site 'a' has no BASE_DIR"),
+            (
+                "[a]\nBASE_DIR=/x\nSTART=2024-01-01",
+                "line 3: unknown key 'START'",
+            ),
+            (
+                "[a]\nBASE_DIR=/x\nEND_DATE=2024-13-01",
+                "line 3: invalid date '2024-13-01'",
+            ),
+            (
+                "[a]\nBASE_DIR=/x\nLINES=A\n[a]",
+                "line 4: duplicate site 'a'",
+            ),
+            (
+                "[a]\nBASE_DIR=/x\nLINES=A\n[b]\nBASE_DIR=/x/\nLINES=B",
+                "sites 'a' and 'b' share BASE_DIR /x (state files would collide)",
+            ),
+        ] {
+            assert_eq!(
+                parse_sites(config, start, end),
+                Err(error.to_string()),
+                "{}",
+                config
+            );
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::types::FileEntry;
+use crate::types::{ArchiveFormat, FileEntry};
 use colored::Colorize;
 use comfy_table::{Attribute, Cell, Color, Table};
 use std::collections::HashMap;
@@ -6,6 +6,7 @@ use std::collections::HashMap;
 #[derive(Debug)]
 pub struct IntegrityRow {
     pub name: String,
+    pub format: ArchiveFormat,
     pub total: usize,
     pub empty: usize,
     pub bad: usize,
@@ -44,6 +45,7 @@ pub struct AnomalyReport {
 #[derive(Debug, Clone)]
 pub struct BadFile {
     pub relative_path: String,
+    pub format: ArchiveFormat,
     pub size: u64,
     pub reason: String,
 }
@@ -88,6 +90,7 @@ pub fn calculate_integrity_stats(
         let total = entries.len();
         let empty = entries.iter().filter(|e| e.size < tiny_threshold).count();
         let bad = entries.iter().filter(|e| !e.is_valid).count();
+        let format = entries[0].format;
 
         let mut sizes: Vec<f64> = entries
             .iter()
@@ -103,6 +106,7 @@ pub fn calculate_integrity_stats(
         let row = if sizes.is_empty() {
             IntegrityRow {
                 name,
+                format,
                 total,
                 empty,
                 bad,
@@ -142,6 +146,7 @@ pub fn calculate_integrity_stats(
 
             IntegrityRow {
                 name,
+                format,
                 total,
                 empty,
                 bad,
@@ -202,14 +207,14 @@ pub fn print_integrity_table(stats_opt: &Option<IntegrityStats>) {
     let stats = if let Some(s) = stats_opt {
         s
     } else {
-        println!("No zip files found.");
+        println!("No archive files found.");
         return;
     };
 
     let mut table = Table::new();
     table.load_preset(comfy_table::presets::UTF8_HORIZONTAL_ONLY);
     table.set_header(vec![
-        "Filename", "Total", "Empty", "Bad", "Min", "Median", "StdDev", "Max",
+        "Filename", "Format", "Total", "Empty", "Bad", "Min", "Median", "StdDev", "Max",
     ]);
 
     for row in &stats.rows {
@@ -231,6 +236,7 @@ pub fn print_integrity_table(stats_opt: &Option<IntegrityStats>) {
 
         let t_row = vec![
             Cell::new(&row.name),
+            Cell::new(row.format),
             Cell::new(row.total),
             Cell::new(row.empty).fg(if row.empty > 0 {
                 Color::Yellow
@@ -253,6 +259,7 @@ pub fn print_integrity_table(stats_opt: &Option<IntegrityStats>) {
     // Summary Row
     table.add_row(vec![
         Cell::new("TOTALS / SUMMARY").add_attribute(Attribute::Bold),
+        Cell::new(""),
         Cell::new(stats.grand_total).add_attribute(Attribute::Bold),
         Cell::new(stats.grand_empty)
             .fg(if stats.grand_empty > 0 {
@@ -282,6 +289,7 @@ pub fn print_integrity_table(stats_opt: &Option<IntegrityStats>) {
     // Repeat header at the bottom
     table.add_row(vec![
         Cell::new("Filename"),
+        Cell::new("Format"),
         Cell::new("Total"),
         Cell::new("Empty"),
         Cell::new("Bad"),
@@ -419,6 +427,7 @@ pub fn collect_bad_files(
 
         let bad_file = BadFile {
             relative_path,
+            format: file.format,
             size: file.size,
             reason,
         };
@@ -452,11 +461,11 @@ pub fn collect_bad_files(
 
 pub fn print_bad_files(report: &Option<BadFilesReport>, threshold: usize) {
     if let Some(r) = report {
-        println!("\n{}", "=== Bad ZIP Files ===".cyan());
+        println!("\n{}", "=== Bad Archive Files ===".cyan());
 
         let archive_count = r.files_by_folder.len();
         println!(
-            "Found {} bad ZIP files across {} archives (showing archives with >{} bad files):",
+            "Found {} bad archive files across {} archives (showing archives with >{} bad files):",
             r.total_count, archive_count, threshold
         );
 
@@ -480,6 +489,7 @@ pub fn print_bad_files(report: &Option<BadFilesReport>, threshold: usize) {
 
             for file in files {
                 println!("  {} {}", "⚠️".yellow(), file.relative_path);
+                println!("     Format: {}", file.format);
                 println!("     Size: {}", human_bytes::human_bytes(file.size as f64));
                 println!("     Reason: {}", file.reason.red());
             }
@@ -9,7 +9,7 @@ pub struct IoSample {
     pub net_recv_bytes: u64,
 }
 
-pub fn check_redundancy(line_id: &str) {
+pub fn check_redundancy(base_dir: &str, line_id: &str, lines: &[String]) {
     println!(
         "\n{}",
         "=== Redundancy Check (System-Wide Activity) ===".cyan()
@@ -30,9 +30,9 @@ pub fn check_redundancy(line_id: &str) {
 
     if disk_bps > threshold {
         let mb = disk_bps as f64 / 1_024.0 / 1_024.0;
-        let other_line = if line_id == "A" { "B" } else { "A" };
+        let active_lines = other_active_lines(base_dir, line_id, lines);
 
-        if is_other_line_active(other_line) {
+        if !active_lines.is_empty() {
             println!(
                 "{} High Disk Activity ({:.1} MB/s) detected.",
                 "INFO:".green(),
@@ -40,7 +40,7 @@ pub fn check_redundancy(line_id: &str) {
             );
             println!(
                 "Activity attributed to concurrent transfer on {}.",
-                format!("Line {other_line}").cyan()
+                describe_lines(&active_lines).cyan()
             );
         } else {
             println!(
@@ -54,9 +54,9 @@ pub fn check_redundancy(line_id: &str) {
         }
     } else if net_bps > threshold {
         let mb = net_bps as f64 / 1_024.0 / 1_024.0;
-        let other_line = if line_id == "A" { "B" } else { "A" };
+        let active_lines = other_active_lines(base_dir, line_id, lines);
 
-        if is_other_line_active(other_line) {
+        if !active_lines.is_empty() {
             println!(
                 "{} High Network Activity ({:.1} MB/s) detected.",
                 "INFO:".green(),
@@ -64,7 +64,7 @@ pub fn check_redundancy(line_id: &str) {
             );
             println!(
                 "Activity attributed to concurrent transfer on {}.",
-                format!("Line {other_line}").cyan()
+                describe_lines(&active_lines).cyan()
             );
         } else {
             println!(
@@ -115,8 +115,20 @@ fn get_sample() -> IoSample {
     sample
 }
 
-fn is_other_line_active(other_line: &str) -> bool {
-    let path = format!("./Line {other_line}");
-    // Check if any file modified in the last 1 minute
-    crate::scanner::has_recent_activity(&path, 1)
+/// Lines other than `line_id` with a file modified in the last minute
+fn other_active_lines<'a>(base_dir: &str, line_id: &str, lines: &'a [String]) -> Vec<&'a str> {
+    lines
+        .iter()
+        .filter(|l| *l != line_id)
+        .filter(|l| crate::scanner::has_recent_activity(&format!("{base_dir}/Line {l}"), 1))
+        .map(String::as_str)
+        .collect()
+}
+
+fn describe_lines(lines: &[&str]) -> String {
+    lines
+        .iter()
+        .map(|l| format!("Line {l}"))
+        .collect::<Vec<_>>()
+        .join(", ")
 }
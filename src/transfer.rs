@@ -0,0 +1,417 @@
+use crate::naming::DirPattern;
+use crate::scanner;
+use crate::types::FileWrite;
+use chrono::{DateTime, Local};
+use log::{debug, warn};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::thread;
+use std::time::Duration;
+
+/// Gap between the two walks of a one-off run
+pub const SAMPLE_GAP: Duration = Duration::from_secs(10);
+
+/// A snapshot older than this (missed cron runs) no longer gives a useful
+/// average, so the run samples for `SAMPLE_GAP` instead
+pub const MAX_SNAPSHOT_AGE: Duration = Duration::from_secs(15 * 60);
+
+/// Which kind of run an audit is. Single-line reports and dashboards are
+/// usually scheduled apart, so each keeps its own size snapshot and speed
+/// is always averaged over the time between two runs of the same kind.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunKind {
+    /// Single-line report (text, `--html`, `--format json`)
+    Line,
+    /// `--dashboard`, `--daemon` and `serve`
+    Dashboard,
+}
+
+impl RunKind {
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Line => "line",
+            Self::Dashboard => "dashboard",
+        }
+    }
+
+    #[must_use]
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "line" => Some(Self::Line),
+            "dashboard" => Some(Self::Dashboard),
+            _ => None,
+        }
+    }
+}
+
+/// `.size_snapshot_<line>` for single-line runs,
+/// `.size_snapshot_dashboard_<line>` for dashboard runs
+#[must_use]
+pub fn snapshot_file_path(base_dir: &str, line_id: &str, kind: RunKind) -> String {
+    match kind {
+        RunKind::Line => format!("{}/.size_snapshot_{}", base_dir, line_id),
+        RunKind::Dashboard => format!("{}/.size_snapshot_dashboard_{}", base_dir, line_id),
+    }
+}
+
+/// Sizes of one line's archive directories at one point in time
+#[derive(Debug, Clone)]
+pub struct SizeWalk {
+    pub taken_at: DateTime<Local>,
+    pub total: u64,
+    pub dirs: HashMap<String, u64>,
+}
+
+impl SizeWalk {
+    #[must_use]
+    pub fn take(search_dir: &str, pattern: &DirPattern) -> Self {
+        let taken_at = Local::now();
+        let (total, dirs) = scanner::get_total_and_per_dir_sizes(search_dir, pattern);
+        Self {
+            taken_at,
+            total,
+            dirs,
+        }
+    }
+
+    /// Load the walk saved by a previous run. `None` when it is missing,
+    /// unreadable or was keyed by another directory pattern.
+    ///
+    /// Stored as a tab-separated text file in the base directory: a
+    /// `# taken_at  total  pattern` header, then `dir  bytes` per line.
+    #[must_use]
+    pub fn load(snapshot_file: &str, pattern: &DirPattern) -> Option<Self> {
+        let content = fs::read_to_string(snapshot_file).ok()?;
+        let mut lines = content.lines();
+        let header = lines.next()?.strip_prefix("# ")?;
+        let mut fields = header.split('\t');
+        let taken_at = DateTime::parse_from_rfc3339(fields.next()?)
+            .ok()?
+            .with_timezone(&Local);
+        let total = fields.next()?.parse().ok()?;
+        if fields.next()? != pattern.to_string() {
+            debug!("Size snapshot {} uses another dir pattern", snapshot_file);
+            return None;
+        }
+        let dirs = lines
+            .filter_map(|line| {
+                let (dir, bytes) = line.rsplit_once('\t')?;
+                Some((dir.to_string(), bytes.parse().ok()?))
+            })
+            .collect();
+        Some(Self {
+            taken_at,
+            total,
+            dirs,
+        })
+    }
+
+    /// Write the walk atomically (temp file + rename)
+    pub fn save(&self, snapshot_file: &str, pattern: &DirPattern) {
+        let mut dirs: Vec<_> = self.dirs.iter().collect();
+        dirs.sort();
+
+        let mut content = format!(
+            "# {}\t{}\t{}\n",
+            self.taken_at.to_rfc3339(),
+            self.total,
+            pattern
+        );
+        for (dir, bytes) in dirs {
+            content.push_str(&format!("{}\t{}\n", dir, bytes));
+        }
+
+        let tmp_file = format!("{}.tmp", snapshot_file);
+        if let Err(e) =
+            fs::write(&tmp_file, content).and_then(|()| fs::rename(&tmp_file, snapshot_file))
+        {
+            warn!("Failed to write size snapshot {}: {}", snapshot_file, e);
+            fs::remove_file(&tmp_file).ok();
+        }
+    }
+
+    /// Directory sizes without the growing ones, for anomaly detection
+    #[must_use]
+    pub fn stable_dirs(&self, growing_dirs: &HashSet<String>) -> HashMap<String, u64> {
+        self.dirs
+            .iter()
+            .filter(|(dir, _)| !growing_dirs.contains(*dir))
+            .map(|(k, v)| (k.clone(), *v))
+            .collect()
+    }
+}
+
+/// How one line is being written to: speed, growing directories and the
+/// files behind them
+#[derive(Debug, Clone, Default)]
+pub struct TransferActivity {
+    pub speed_bps: u64,
+    pub growing_dirs: HashSet<String>,
+    /// As listed by `scanner::get_recent_files`
+    pub recent_files: Vec<FileWrite>,
+    /// `recent_files` are the files open for writing right now rather than
+    /// every file modified in the last 5 minutes
+    pub live: bool,
+}
+
+/// Two walks of the same line, used for speed and growing directories
+#[derive(Debug, Clone)]
+pub struct TransferSample {
+    pub before: SizeWalk,
+    pub after: SizeWalk,
+}
+
+impl TransferSample {
+    /// Walk the line twice, `gap` apart
+    #[must_use]
+    pub fn measure(search_dir: &str, pattern: &DirPattern, gap: Duration) -> Self {
+        let before = SizeWalk::take(search_dir, pattern);
+        thread::sleep(gap);
+        let after = SizeWalk::take(search_dir, pattern);
+        Self { before, after }
+    }
+
+    /// Compare a fresh walk with the snapshot saved by the previous run,
+    /// which averages over the whole time between runs with a single walk.
+    /// Without a usable snapshot, walks twice `SAMPLE_GAP` apart instead.
+    /// The fresh walk is saved for the next run either way.
+    #[must_use]
+    pub fn since_snapshot(search_dir: &str, pattern: &DirPattern, snapshot_file: &str) -> Self {
+        let sample = Self::against_snapshot(search_dir, pattern, snapshot_file);
+        sample.after.save(snapshot_file, pattern);
+        sample
+    }
+
+    /// Like `since_snapshot`, but leaves the snapshot untouched, for one-off
+    /// runs that must not reset the window the regular runs measure over
+    #[must_use]
+    pub fn against_snapshot(search_dir: &str, pattern: &DirPattern, snapshot_file: &str) -> Self {
+        let previous = SizeWalk::load(snapshot_file, pattern)
+            .filter(|walk| snapshot_is_usable(walk.taken_at, Local::now()));
+        match previous {
+            Some(before) => {
+                debug!(
+                    "Using size snapshot from {} for {}",
+                    before.taken_at.format("%Y-%m-%d %H:%M:%S"),
+                    search_dir
+                );
+                Self {
+                    before,
+                    after: SizeWalk::take(search_dir, pattern),
+                }
+            }
+            None => {
+                debug!("No usable size snapshot for {}, sampling", search_dir);
+                Self::measure(search_dir, pattern, SAMPLE_GAP)
+            }
+        }
+    }
+
+    /// Average bytes per second between the two walks
+    #[must_use]
+    pub fn speed_bps(&self) -> u64 {
+        let elapsed_ms = self
+            .after
+            .taken_at
+            .signed_duration_since(self.before.taken_at)
+            .num_milliseconds()
+            .max(1) as u64;
+        self.after.total.saturating_sub(self.before.total) * 1000 / elapsed_ms
+    }
+
+    /// Directories that grew between the two walks (actively being copied)
+    #[must_use]
+    pub fn growing_dirs(&self, line_id: &str) -> HashSet<String> {
+        let growing_dirs: HashSet<String> = self
+            .after
+            .dirs
+            .iter()
+            .filter(|(dir, size_t2)| {
+                let size_t1 = self.before.dirs.get(*dir).copied().unwrap_or(0);
+                let is_growing = **size_t2 > size_t1;
+                if is_growing {
+                    debug!(
+                        "Line {}: Growing directory detected: {} ({} -> {} bytes, delta: {} bytes)",
+                        line_id,
+                        dir,
+                        size_t1,
+                        size_t2,
+                        size_t2.saturating_sub(size_t1)
+                    );
+                }
+                is_growing
+            })
+            .map(|(dir, _)| dir.clone())
+            .collect();
+
+        debug!(
+            "Line {}: Total directories tracked: {}",
+            line_id,
+            self.after.dirs.len()
+        );
+        debug!(
+            "Line {}: Growing directories: {}",
+            line_id,
+            growing_dirs.len()
+        );
+        growing_dirs
+    }
+
+    /// Speed and growing directories from the two walks, with the files
+    /// modified in the last 5 minutes found by walking `search_dir`
+    #[must_use]
+    pub fn activity(&self, line_id: &str, search_dir: &str) -> TransferActivity {
+        TransferActivity {
+            speed_bps: self.speed_bps(),
+            growing_dirs: self.growing_dirs(line_id),
+            recent_files: scanner::get_recent_files(search_dir, 5),
+            live: false,
+        }
+    }
+}
+
+/// A snapshot is only worth comparing against when at least `SAMPLE_GAP`
+/// and at most `MAX_SNAPSHOT_AGE` old (a clock jump makes it unusable)
+fn snapshot_is_usable(taken_at: DateTime<Local>, now: DateTime<Local>) -> bool {
+    now.signed_duration_since(taken_at)
+        .to_std()
+        .is_ok_and(|age| (SAMPLE_GAP..=MAX_SNAPSHOT_AGE).contains(&age))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn walk(seconds: i64, dirs: &[(&str, u64)]) -> SizeWalk {
+        let start = DateTime::parse_from_rfc3339("2025-03-14T12:00:00+00:00")
+            .unwrap()
+            .with_timezone(&Local);
+        SizeWalk {
+            taken_at: start + chrono::Duration::seconds(seconds),
+            total: dirs.iter().map(|(_, size)| size).sum(),
+            dirs: dirs.iter().map(|(d, s)| ((*d).to_string(), *s)).collect(),
+        }
+    }
+
+    #[test]
+    fn test_speed_and_growing_dirs() {
+        let sample = TransferSample {
+            before: walk(0, &[("day1", 4000), ("day2", 1000)]),
+            after: walk(20, &[("day1", 4000), ("day2", 5000), ("day3", 2000)]),
+        };
+        assert_eq!(sample.speed_bps(), 300);
+
+        let growing = sample.growing_dirs("B");
+        assert_eq!(
+            growing,
+            HashSet::from(["day2".to_string(), "day3".to_string()])
+        );
+        assert_eq!(
+            sample.after.stable_dirs(&growing),
+            HashMap::from([("day1".to_string(), 4000)])
+        );
+    }
+
+    #[test]
+    fn test_shrinking_line_is_not_negative_speed() {
+        let sample = TransferSample {
+            before: walk(0, &[("day1", 4000)]),
+            after: walk(10, &[("day1", 1000)]),
+        };
+        assert_eq!(sample.speed_bps(), 0);
+        assert!(sample.growing_dirs("B").is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let temp = tempfile::tempdir().unwrap();
+        let snapshot_file = temp.path().join(".size_snapshot_B");
+        let snapshot_file = snapshot_file.to_str().unwrap();
+        let pattern = DirPattern::default();
+        let saved = walk(0, &[("Archive_Beam_B_2025-03-14", 4096), ("day 2", 512)]);
+
+        assert!(SizeWalk::load(snapshot_file, &pattern).is_none());
+        saved.save(snapshot_file, &pattern);
+        let loaded = SizeWalk::load(snapshot_file, &pattern).unwrap();
+        assert_eq!(loaded.taken_at, saved.taken_at);
+        assert_eq!(loaded.total, 4608);
+        assert_eq!(loaded.dirs, saved.dirs);
+
+        let nested: DirPattern = "Beam{line}/{yyyy}/{mm}/{dd}".parse().unwrap();
+        assert!(SizeWalk::load(snapshot_file, &nested).is_none());
+    }
+
+    #[test]
+    fn test_snapshot_age_limits() {
+        let now = Local::now();
+        let ago = |seconds| now - chrono::Duration::seconds(seconds);
+        assert!(snapshot_is_usable(ago(300), now));
+        assert!(snapshot_is_usable(ago(10), now));
+        assert!(!snapshot_is_usable(ago(5), now));
+        assert!(!snapshot_is_usable(ago(16 * 60), now));
+        assert!(!snapshot_is_usable(ago(-60), now));
+    }
+
+    #[test]
+    fn test_since_snapshot_uses_previous_walk() {
+        let temp = tempfile::tempdir().unwrap();
+        let search_dir = temp.path().join("Line B");
+        fs::create_dir_all(search_dir.join("Archive_Beam_B_2025-03-14")).unwrap();
+        fs::write(
+            search_dir.join("Archive_Beam_B_2025-03-14/Cam1.zip"),
+            vec![1u8; 8192],
+        )
+        .unwrap();
+        let search_dir = search_dir.to_str().unwrap();
+        let snapshot_file = temp.path().join(".size_snapshot_B");
+        let snapshot_file = snapshot_file.to_str().unwrap();
+        let pattern = DirPattern::default();
+
+        let mut previous = SizeWalk::take(search_dir, &pattern);
+        previous.taken_at = Local::now() - chrono::Duration::seconds(300);
+        previous.total = 0;
+        previous.dirs.clear();
+        previous.save(snapshot_file, &pattern);
+
+        let sample = TransferSample::since_snapshot(search_dir, &pattern, snapshot_file);
+        assert_eq!(sample.before.total, 0);
+        assert!(sample.speed_bps() > 0);
+        assert_eq!(
+            sample.growing_dirs("B"),
+            HashSet::from(["Archive_Beam_B_2025-03-14".to_string()])
+        );
+
+        // The fresh walk replaced the snapshot for the next run
+        let saved = SizeWalk::load(snapshot_file, &pattern).unwrap();
+        assert_eq!(saved.total, sample.after.total);
+    }
+
+    #[test]
+    fn test_against_snapshot_leaves_snapshot_untouched() {
+        let temp = tempfile::tempdir().unwrap();
+        let search_dir = temp.path().join("Line B");
+        fs::create_dir_all(search_dir.join("Archive_Beam_B_2025-03-14")).unwrap();
+        fs::write(
+            search_dir.join("Archive_Beam_B_2025-03-14/Cam1.zip"),
+            vec![1u8; 8192],
+        )
+        .unwrap();
+        let search_dir = search_dir.to_str().unwrap();
+        let snapshot_file = temp.path().join(".size_snapshot_B");
+        let snapshot_file = snapshot_file.to_str().unwrap();
+        let pattern = DirPattern::default();
+
+        let mut previous = SizeWalk::take(search_dir, &pattern);
+        previous.taken_at = Local::now() - chrono::Duration::seconds(300);
+        previous.total = 0;
+        previous.dirs.clear();
+        previous.save(snapshot_file, &pattern);
+
+        let sample = TransferSample::against_snapshot(search_dir, &pattern, snapshot_file);
+        assert!(sample.speed_bps() > 0);
+
+        let saved = SizeWalk::load(snapshot_file, &pattern).unwrap();
+        assert_eq!(saved.total, 0);
+    }
+}
@@ -1,12 +1,74 @@
 use chrono::{DateTime, Local};
+use std::fmt;
+use std::path::Path;
+
+/// Archive container format, determined from the file extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ArchiveFormat {
+    Zip,
+    Tar,
+    TarGz,
+    SevenZip,
+}
+
+impl fmt::Display for ArchiveFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            ArchiveFormat::Zip => "ZIP",
+            ArchiveFormat::Tar => "TAR",
+            ArchiveFormat::TarGz => "TAR.GZ",
+            ArchiveFormat::SevenZip => "7Z",
+        };
+        f.write_str(label)
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct FileEntry {
     pub name: String,
     pub size: u64,
+    pub format: ArchiveFormat,
     pub is_valid: bool,
     pub invalid_reason: Option<String>,
-    #[allow(dead_code)]
     pub modified: DateTime<Local>,
     pub parent_dir: String,
 }
+
+/// A file written in the last minutes or being copied right now
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileWrite {
+    /// Path from the `Line <ID>` folder on, or the full path outside one
+    pub path: String,
+    pub size: u64,
+    pub modified: DateTime<Local>,
+}
+
+impl FileWrite {
+    #[must_use]
+    pub fn new(path: &Path, size: u64, modified: DateTime<Local>) -> Self {
+        let full_path = path.to_string_lossy();
+        // Extract path starting from "Line " onwards
+        let path = match full_path.find("Line ") {
+            Some(idx) => full_path[idx..].to_string(),
+            None => full_path.into_owned(),
+        };
+        Self {
+            path,
+            size,
+            modified,
+        }
+    }
+}
+
+/// One entry of the active/recent file writes list
+impl fmt::Display for FileWrite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "  - {} ({}) at {}",
+            self.path,
+            human_bytes::human_bytes(self.size as f64),
+            self.modified.format("%Y-%m-%d %H:%M")
+        )
+    }
+}
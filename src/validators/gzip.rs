@@ -0,0 +1,136 @@
+use super::ArchiveValidator;
+use super::tar::check_tar_stream;
+use crate::types::ArchiveFormat;
+use flate2::read::MultiGzDecoder;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+/// 10-byte header + empty deflate block + 8-byte trailer
+const GZIP_MIN_SIZE: u64 = 18;
+
+/// Gzip-compressed tar archives: the whole stream is decompressed so the
+/// tar headers are walked and the trailer CRC32 and ISIZE of every gzip
+/// member are checked. Other `.gz` files are not archives and not scanned.
+pub struct TarGzValidator;
+
+impl ArchiveValidator for TarGzValidator {
+    fn format(&self) -> ArchiveFormat {
+        ArchiveFormat::TarGz
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &[".tar.gz", ".tgz"]
+    }
+
+    fn reads_all_data(&self) -> bool {
+        true
+    }
+
+    fn validate(&self, path: &Path, _deep_verify: bool) -> Result<(), String> {
+        let mut decoder = open_gzip(path)?;
+        check_tar_stream(
+            &mut decoder,
+            |r, n| io::copy(&mut r.take(n), &mut io::sink()),
+            describe_gzip_error,
+        )?;
+
+        // Drain the record padding so the gzip trailer is reached and checked
+        io::copy(&mut decoder, &mut io::sink()).map_err(describe_gzip_error)?;
+        Ok(())
+    }
+}
+
+fn open_gzip(path: &Path) -> Result<MultiGzDecoder<BufReader<File>>, String> {
+    let mut file = File::open(path).map_err(|_| "Cannot open file".to_string())?;
+    let len = file
+        .metadata()
+        .map_err(|_| "Cannot read file metadata".to_string())?
+        .len();
+    if len < GZIP_MIN_SIZE {
+        return Err(format!(
+            "File too small ({} bytes, minimum {} bytes required)",
+            len, GZIP_MIN_SIZE
+        ));
+    }
+
+    let mut magic = [0u8; 2];
+    file.read_exact(&mut magic)
+        .map_err(|_| "Cannot read file contents".to_string())?;
+    if magic != GZIP_MAGIC {
+        return Err("Missing gzip signature (corrupted or incomplete transfer)".to_string());
+    }
+
+    let file = File::open(path).map_err(|_| "Cannot open file".to_string())?;
+    Ok(MultiGzDecoder::new(BufReader::new(file)))
+}
+
+fn describe_gzip_error(e: io::Error) -> String {
+    if e.kind() == io::ErrorKind::UnexpectedEof {
+        "Gzip stream truncated (missing trailer)".to_string()
+    } else if e.to_string().contains("checksum") {
+        format!("Gzip trailer CRC32/ISIZE mismatch ({})", e)
+    } else {
+        format!("Gzip stream corrupt: {}", e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tar::tests::build_tar;
+    use super::*;
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    fn check_bytes(validator: &dyn ArchiveValidator, bytes: &[u8]) -> Result<(), String> {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(bytes).unwrap();
+        file.flush().unwrap();
+        validator.validate(file.path(), false)
+    }
+
+    fn tar_gz() -> Vec<u8> {
+        let data = b"beam data ".repeat(1000);
+        gzip(&build_tar(&[("a.txt", data.as_slice())]))
+    }
+
+    #[test]
+    fn test_gzip_trailer_crc_mismatch() {
+        let mut gz = tar_gz();
+        let crc_pos = gz.len() - 8;
+        gz[crc_pos] ^= 0xFF;
+        let reason = check_bytes(&TarGzValidator, &gz).unwrap_err();
+        assert!(reason.contains("CRC32/ISIZE mismatch"), "{}", reason);
+    }
+
+    #[test]
+    fn test_gzip_truncated() {
+        let gz = tar_gz();
+        let reason = check_bytes(&TarGzValidator, &gz[..gz.len() - 4]).unwrap_err();
+        assert!(reason.contains("truncated"), "{}", reason);
+    }
+
+    #[test]
+    fn test_gzip_bad_magic() {
+        let reason = check_bytes(&TarGzValidator, &[0u8; 64]).unwrap_err();
+        assert!(reason.contains("Missing gzip signature"));
+    }
+
+    #[test]
+    fn test_tar_gz_checks_inner_tar() {
+        let tar = build_tar(&[("a.txt", b"hello")]);
+        assert_eq!(check_bytes(&TarGzValidator, &gzip(&tar)), Ok(()));
+
+        // Valid gzip wrapping a tar without end-of-archive blocks
+        let reason = check_bytes(&TarGzValidator, &gzip(&tar[..1024])).unwrap_err();
+        assert!(reason.contains("Missing end-of-archive marker"));
+    }
+}
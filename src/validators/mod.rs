@@ -0,0 +1,74 @@
+//! Per-format archive validators.
+//!
+//! Each supported format implements [`ArchiveValidator`]; the scanner picks
+//! the validator from the file name via [`validator_for`].
+
+mod gzip;
+mod seven_zip;
+mod tar;
+pub mod zip;
+
+use crate::types::ArchiveFormat;
+use std::path::Path;
+
+pub trait ArchiveValidator: Sync {
+    /// Format handled by this validator
+    fn format(&self) -> ArchiveFormat;
+
+    /// Lower-case file name suffixes (with leading dot) handled by this validator
+    fn extensions(&self) -> &'static [&'static str];
+
+    /// Check the archive structure. The error names the check that failed.
+    /// `deep_verify` enables checks that need to read all archive data.
+    fn validate(&self, path: &Path, deep_verify: bool) -> Result<(), String>;
+
+    /// Whether `validate` reads all archive data even without `deep_verify`,
+    /// so its cached result also serves `--deep-verify` scans
+    fn reads_all_data(&self) -> bool {
+        false
+    }
+}
+
+static VALIDATORS: &[&dyn ArchiveValidator] = &[
+    &zip::ZipValidator,
+    &tar::TarValidator,
+    &gzip::TarGzValidator,
+    &seven_zip::SevenZipValidator,
+];
+
+/// Find the validator for a file, matching the longest extension so that
+/// `.tar.gz` is not treated as plain gzip
+#[must_use]
+pub fn validator_for(path: &Path) -> Option<&'static dyn ArchiveValidator> {
+    let file_name = path.file_name()?.to_str()?.to_ascii_lowercase();
+
+    VALIDATORS
+        .iter()
+        .flat_map(|v| v.extensions().iter().map(move |ext| (*v, *ext)))
+        .filter(|(_, ext)| file_name.ends_with(ext) && file_name.len() > ext.len())
+        .max_by_key(|(_, ext)| ext.len())
+        .map(|(v, _)| v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format_of(name: &str) -> Option<ArchiveFormat> {
+        validator_for(Path::new(name)).map(|v| v.format())
+    }
+
+    #[test]
+    fn test_validator_for_extensions() {
+        assert_eq!(format_of("/x/Device1.zip"), Some(ArchiveFormat::Zip));
+        assert_eq!(format_of("/x/Device1.ZIP"), Some(ArchiveFormat::Zip));
+        assert_eq!(format_of("/x/data.tar"), Some(ArchiveFormat::Tar));
+        assert_eq!(format_of("/x/data.tar.gz"), Some(ArchiveFormat::TarGz));
+        assert_eq!(format_of("/x/data.tgz"), Some(ArchiveFormat::TarGz));
+        // Plain gzip files are not archives
+        assert_eq!(format_of("/x/log.gz"), None);
+        assert_eq!(format_of("/x/data.7z"), Some(ArchiveFormat::SevenZip));
+        assert_eq!(format_of("/x/notes.txt"), None);
+        assert_eq!(format_of("/x/.zip"), None);
+    }
+}
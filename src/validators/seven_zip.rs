@@ -0,0 +1,207 @@
+use super::ArchiveValidator;
+use crate::types::ArchiveFormat;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+const SIGNATURE: [u8; 6] = [b'7', b'z', 0xBC, 0xAF, 0x27, 0x1C];
+const SIGNATURE_HEADER_SIZE: u64 = 32;
+/// Larger next headers are only CRC-checked in deep mode
+const MAX_NEXT_HEADER_CHECK: u64 = 16 * 1024 * 1024;
+/// Sanity limit on the next header size to avoid huge allocations
+const MAX_NEXT_HEADER_SIZE: u64 = 256 * 1024 * 1024;
+
+/// 7z archives: signature header, its start-header CRC, and the location
+/// and CRC of the next (end) header it points to
+pub struct SevenZipValidator;
+
+impl ArchiveValidator for SevenZipValidator {
+    fn format(&self) -> ArchiveFormat {
+        ArchiveFormat::SevenZip
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &[".7z"]
+    }
+
+    fn validate(&self, path: &Path, deep_verify: bool) -> Result<(), String> {
+        let mut file = File::open(path).map_err(|_| "Cannot open file".to_string())?;
+        let len = file
+            .metadata()
+            .map_err(|_| "Cannot read file metadata".to_string())?
+            .len();
+        if len < SIGNATURE_HEADER_SIZE {
+            return Err(format!(
+                "File too small ({} bytes, minimum 32 bytes required)",
+                len
+            ));
+        }
+
+        let mut header = [0u8; SIGNATURE_HEADER_SIZE as usize];
+        file.read_exact(&mut header)
+            .map_err(|_| "Cannot read file contents".to_string())?;
+
+        if header[..6] != SIGNATURE {
+            return Err("Missing 7z signature (corrupted or incomplete transfer)".to_string());
+        }
+        if header[6] != 0 {
+            return Err(format!(
+                "Unsupported 7z format version {}.{}",
+                header[6], header[7]
+            ));
+        }
+
+        let start_header_crc = u32::from_le_bytes([header[8], header[9], header[10], header[11]]);
+        let actual_crc = crc32fast::hash(&header[12..32]);
+        if start_header_crc != actual_crc {
+            return Err(format!(
+                "Start header CRC mismatch (expected {:08x}, actual {:08x})",
+                start_header_crc, actual_crc
+            ));
+        }
+
+        let next_header_offset = u64::from_le_bytes(header[12..20].try_into().unwrap());
+        let next_header_size = u64::from_le_bytes(header[20..28].try_into().unwrap());
+        let next_header_crc = u32::from_le_bytes(header[28..32].try_into().unwrap());
+
+        let next_header_end = SIGNATURE_HEADER_SIZE
+            .checked_add(next_header_offset)
+            .and_then(|v| v.checked_add(next_header_size));
+        match next_header_end {
+            Some(end) if end <= len => {}
+            _ => {
+                return Err(format!(
+                    "Next header beyond EOF (offset {}, size {}, file length {})",
+                    next_header_offset, next_header_size, len
+                ));
+            }
+        }
+
+        // Empty archive
+        if next_header_size == 0 {
+            return Ok(());
+        }
+        if next_header_size > MAX_NEXT_HEADER_SIZE {
+            return Err(format!(
+                "Next header too large ({} bytes, limit {})",
+                next_header_size, MAX_NEXT_HEADER_SIZE
+            ));
+        }
+        if next_header_size > MAX_NEXT_HEADER_CHECK && !deep_verify {
+            return Ok(());
+        }
+
+        let mut next_header = vec![0u8; next_header_size as usize];
+        file.seek(SeekFrom::Start(SIGNATURE_HEADER_SIZE + next_header_offset))
+            .and_then(|_| file.read_exact(&mut next_header))
+            .map_err(|_| "Cannot read 7z next header".to_string())?;
+        let actual_crc = crc32fast::hash(&next_header);
+        if next_header_crc != actual_crc {
+            return Err(format!(
+                "Next header CRC mismatch (expected {:08x}, actual {:08x})",
+                next_header_crc, actual_crc
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    /// Signature header followed by `body` and a next header at the end
+    fn build_7z(body: &[u8], next_header: &[u8]) -> Vec<u8> {
+        let mut start_header = Vec::new();
+        start_header.extend_from_slice(&(body.len() as u64).to_le_bytes());
+        start_header.extend_from_slice(&(next_header.len() as u64).to_le_bytes());
+        start_header.extend_from_slice(&crc32fast::hash(next_header).to_le_bytes());
+
+        let mut data = SIGNATURE.to_vec();
+        data.extend_from_slice(&[0, 4]);
+        data.extend_from_slice(&crc32fast::hash(&start_header).to_le_bytes());
+        data.extend_from_slice(&start_header);
+        data.extend_from_slice(body);
+        data.extend_from_slice(next_header);
+        data
+    }
+
+    /// Start header claiming a next header of `size` bytes right after it
+    fn start_header_claiming(size: u64) -> Vec<u8> {
+        let mut start_header = Vec::new();
+        start_header.extend_from_slice(&0u64.to_le_bytes());
+        start_header.extend_from_slice(&size.to_le_bytes());
+        start_header.extend_from_slice(&0u32.to_le_bytes());
+
+        let mut data = SIGNATURE.to_vec();
+        data.extend_from_slice(&[0, 4]);
+        data.extend_from_slice(&crc32fast::hash(&start_header).to_le_bytes());
+        data.extend_from_slice(&start_header);
+        data
+    }
+
+    fn check_bytes(bytes: &[u8]) -> Result<(), String> {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(bytes).unwrap();
+        file.flush().unwrap();
+        SevenZipValidator.validate(file.path(), false)
+    }
+
+    #[test]
+    fn test_valid_7z_passes() {
+        assert_eq!(
+            check_bytes(&build_7z(b"packed streams", b"\x01\x04\x06")),
+            Ok(())
+        );
+        assert_eq!(check_bytes(&build_7z(b"", b"")), Ok(()));
+    }
+
+    #[test]
+    fn test_7z_bad_signature() {
+        let reason = check_bytes(&[0u8; 64]).unwrap_err();
+        assert!(reason.contains("Missing 7z signature"));
+    }
+
+    #[test]
+    fn test_7z_start_header_crc_mismatch() {
+        let mut data = build_7z(b"packed streams", b"\x01\x04\x06");
+        data[8] ^= 0xFF;
+        let reason = check_bytes(&data).unwrap_err();
+        assert!(reason.contains("Start header CRC mismatch"));
+    }
+
+    #[test]
+    fn test_7z_truncated() {
+        let data = build_7z(b"packed streams", b"\x01\x04\x06");
+        let reason = check_bytes(&data[..data.len() - 2]).unwrap_err();
+        assert!(reason.contains("Next header beyond EOF"));
+    }
+
+    #[test]
+    fn test_7z_next_header_crc_mismatch() {
+        let mut data = build_7z(b"packed streams", b"\x01\x04\x06");
+        let last = data.len() - 1;
+        data[last] ^= 0xFF;
+        let reason = check_bytes(&data).unwrap_err();
+        assert!(reason.contains("Next header CRC mismatch"));
+    }
+
+    #[test]
+    fn test_7z_oversized_next_header() {
+        let reason = check_bytes(&start_header_claiming(u64::MAX - 16)).unwrap_err();
+        assert!(reason.contains("Next header beyond EOF"));
+
+        // Within the (sparse) file, but over the limit: rejected before
+        // anything is allocated, even in deep mode
+        let size = MAX_NEXT_HEADER_SIZE + 1;
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(&start_header_claiming(size)).unwrap();
+        file.as_file()
+            .set_len(SIGNATURE_HEADER_SIZE + size)
+            .unwrap();
+        let reason = SevenZipValidator.validate(file.path(), true).unwrap_err();
+        assert!(reason.contains("Next header too large"));
+    }
+}
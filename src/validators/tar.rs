@@ -0,0 +1,269 @@
+use super::ArchiveValidator;
+use crate::types::ArchiveFormat;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek};
+use std::path::Path;
+
+const BLOCK_SIZE: usize = 512;
+
+/// Plain tar archives: header checksums, member sizes against the file
+/// length, and the two zero blocks that mark the end of the archive
+pub struct TarValidator;
+
+impl ArchiveValidator for TarValidator {
+    fn format(&self) -> ArchiveFormat {
+        ArchiveFormat::Tar
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &[".tar"]
+    }
+
+    fn validate(&self, path: &Path, _deep_verify: bool) -> Result<(), String> {
+        let file = File::open(path).map_err(|_| "Cannot open file".to_string())?;
+        let len = file
+            .metadata()
+            .map_err(|_| "Cannot read file metadata".to_string())?
+            .len();
+        let mut reader = BufReader::new(file);
+
+        // Member data is skipped with a seek, never read
+        check_tar_stream(
+            &mut reader,
+            |r, n| {
+                let pos = r.stream_position()?;
+                let available = len.saturating_sub(pos).min(n);
+                r.seek_relative(available as i64)?;
+                Ok(available)
+            },
+            |e| format!("Cannot read tar data: {}", e),
+        )
+    }
+}
+
+/// Walk the tar headers in `reader`.
+///
+/// `skip` advances past `n` bytes of member data and returns how many bytes
+/// were actually available; `read_error` describes I/O errors of the
+/// underlying stream (e.g. a corrupt gzip layer).
+pub(super) fn check_tar_stream<R: Read>(
+    reader: &mut R,
+    mut skip: impl FnMut(&mut R, u64) -> io::Result<u64>,
+    read_error: impl Fn(io::Error) -> String,
+) -> Result<(), String> {
+    let mut block_index = 0u64;
+
+    loop {
+        let Some(header) = read_block(reader).map_err(&read_error)? else {
+            return Err("Missing end-of-archive marker (truncated transfer)".to_string());
+        };
+        block_index += 1;
+
+        if header.iter().all(|b| *b == 0) {
+            return match read_block(reader).map_err(&read_error)? {
+                Some(next) if next.iter().all(|b| *b == 0) => Ok(()),
+                Some(_) => Err(format!(
+                    "Unexpected data after end-of-archive block {}",
+                    block_index
+                )),
+                None => Err("Second end-of-archive block missing (truncated transfer)".to_string()),
+            };
+        }
+
+        let name = header_name(&header);
+
+        let stored = parse_octal(&header[148..156]).ok_or_else(|| {
+            format!(
+                "Invalid header checksum field at block {} ('{}')",
+                block_index, name
+            )
+        })?;
+        let computed: u64 = header
+            .iter()
+            .enumerate()
+            .map(|(i, b)| {
+                if (148..156).contains(&i) {
+                    0x20
+                } else {
+                    u64::from(*b)
+                }
+            })
+            .sum();
+        if stored != computed {
+            return Err(format!(
+                "Header checksum mismatch at block {} ('{}', expected {}, actual {})",
+                block_index, name, stored, computed
+            ));
+        }
+
+        let size = parse_size(&header[124..136])
+            .ok_or_else(|| format!("Invalid size field for '{}'", name))?;
+
+        // Links, devices, directories and FIFOs carry no data blocks
+        let data_len = match header[156] {
+            b'1'..=b'6' => 0,
+            _ => size
+                .checked_next_multiple_of(BLOCK_SIZE as u64)
+                .ok_or_else(|| format!("Invalid size field for '{}' ({} bytes)", name, size))?,
+        };
+        if data_len > 0 {
+            let skipped = skip(reader, data_len).map_err(&read_error)?;
+            if skipped < data_len {
+                return Err(format!(
+                    "Member '{}' truncated ({} of {} data bytes present)",
+                    name, skipped, data_len
+                ));
+            }
+            block_index += data_len / BLOCK_SIZE as u64;
+        }
+    }
+}
+
+/// Read one full 512-byte block; `None` at a clean EOF
+fn read_block<R: Read>(reader: &mut R) -> io::Result<Option<[u8; BLOCK_SIZE]>> {
+    let mut block = [0u8; BLOCK_SIZE];
+    let mut filled = 0;
+    while filled < BLOCK_SIZE {
+        match reader.read(&mut block[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+
+    match filled {
+        0 => Ok(None),
+        BLOCK_SIZE => Ok(Some(block)),
+        _ => Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!("partial {}-byte block at end of archive", filled),
+        )),
+    }
+}
+
+fn header_name(header: &[u8]) -> String {
+    let end = header[..100].iter().position(|b| *b == 0).unwrap_or(100);
+    String::from_utf8_lossy(&header[..end]).to_string()
+}
+
+/// Parse a NUL/space terminated octal number field
+fn parse_octal(field: &[u8]) -> Option<u64> {
+    let text = std::str::from_utf8(field).ok()?;
+    let digits = text.trim_matches(|c: char| c == '\0' || c == ' ');
+    if digits.is_empty() {
+        return Some(0);
+    }
+    u64::from_str_radix(digits, 8).ok()
+}
+
+/// Size field: octal, or GNU base-256 when the high bit of the first byte is set
+fn parse_size(field: &[u8]) -> Option<u64> {
+    if field[0] & 0x80 != 0 {
+        let mut value = u64::from(field[0] & 0x7F);
+        for b in &field[1..] {
+            value = value.checked_mul(256)?.checked_add(u64::from(*b))?;
+        }
+        Some(value)
+    } else {
+        parse_octal(field)
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+    use std::io::Write;
+
+    /// Build a ustar archive with regular file members and two end blocks
+    pub(in crate::validators) fn build_tar(members: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut data = Vec::new();
+        for (name, content) in members {
+            let mut header = [0u8; BLOCK_SIZE];
+            header[..name.len()].copy_from_slice(name.as_bytes());
+            header[100..107].copy_from_slice(b"0000644");
+            header[124..135].copy_from_slice(format!("{:011o}", content.len()).as_bytes());
+            header[156] = b'0';
+            header[257..263].copy_from_slice(b"ustar\0");
+            header[148..156].fill(b' ');
+            let sum: u64 = header.iter().map(|b| u64::from(*b)).sum();
+            header[148..155].copy_from_slice(format!("{:06o}\0", sum).as_bytes());
+
+            data.extend_from_slice(&header);
+            data.extend_from_slice(content);
+            let padding = content.len().div_ceil(BLOCK_SIZE) * BLOCK_SIZE - content.len();
+            data.extend(std::iter::repeat_n(0u8, padding));
+        }
+        data.extend_from_slice(&[0u8; BLOCK_SIZE * 2]);
+        data
+    }
+
+    fn check_bytes(bytes: &[u8]) -> Result<(), String> {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(bytes).unwrap();
+        file.flush().unwrap();
+        TarValidator.validate(file.path(), false)
+    }
+
+    #[test]
+    fn test_valid_tar_passes() {
+        let tar = build_tar(&[("a.txt", b"hello"), ("b.bin", &[7u8; 1500])]);
+        assert_eq!(check_bytes(&tar), Ok(()));
+    }
+
+    #[test]
+    fn test_header_checksum_mismatch() {
+        let mut tar = build_tar(&[("a.txt", b"hello")]);
+        tar[0] = b'x';
+        let reason = check_bytes(&tar).unwrap_err();
+        assert!(reason.contains("Header checksum mismatch at block 1"));
+    }
+
+    #[test]
+    fn test_truncated_member_data() {
+        let tar = build_tar(&[("a.bin", &[7u8; 4000])]);
+        let reason = check_bytes(&tar[..2048]).unwrap_err();
+        assert!(reason.contains("Member 'a.bin' truncated"));
+    }
+
+    #[test]
+    fn test_missing_end_of_archive_blocks() {
+        let tar = build_tar(&[("a.txt", b"hello")]);
+        let reason = check_bytes(&tar[..1024]).unwrap_err();
+        assert!(reason.contains("Missing end-of-archive marker"));
+
+        let reason = check_bytes(&tar[..1536]).unwrap_err();
+        assert!(reason.contains("Second end-of-archive block missing"));
+    }
+
+    #[test]
+    fn test_partial_block() {
+        let tar = build_tar(&[("a.txt", b"hello")]);
+        let reason = check_bytes(&tar[..1100]).unwrap_err();
+        assert!(reason.contains("partial"));
+    }
+
+    #[test]
+    fn test_parse_size_base256() {
+        let mut field = [0u8; 12];
+        field[0] = 0x80;
+        field[11] = 0x01;
+        field[10] = 0x02;
+        assert_eq!(parse_size(&field), Some(0x0201));
+        assert_eq!(parse_size(b"00000000017\0"), Some(15));
+    }
+
+    #[test]
+    fn test_size_overflowing_block_padding() {
+        let mut tar = build_tar(&[("a.bin", b"")]);
+        tar[124] = 0x80;
+        tar[125..128].fill(0);
+        tar[128..136].fill(0xFF);
+        tar[148..156].fill(b' ');
+        let sum: u64 = tar[..BLOCK_SIZE].iter().map(|b| u64::from(*b)).sum();
+        tar[148..155].copy_from_slice(format!("{:06o}\0", sum).as_bytes());
+
+        let reason = check_bytes(&tar).unwrap_err();
+        assert!(reason.contains("Invalid size field for 'a.bin' (18446744073709551615 bytes)"));
+    }
+}
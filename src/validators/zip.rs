@@ -0,0 +1,850 @@
+use super::ArchiveValidator;
+use crate::types::ArchiveFormat;
+use flate2::read::DeflateDecoder;
+use log::debug;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// ZIP archives: EOCD, ZIP64 and central directory checks, plus per-member
+/// CRC32 verification in deep mode
+pub struct ZipValidator;
+
+impl ArchiveValidator for ZipValidator {
+    fn format(&self) -> ArchiveFormat {
+        ArchiveFormat::Zip
+    }
+
+    fn extensions(&self) -> &'static [&'static str] {
+        &[".zip"]
+    }
+
+    fn validate(&self, path: &Path, deep_verify: bool) -> Result<(), String> {
+        check_zip_structure(path, deep_verify)
+    }
+}
+
+const EOCD_SIGNATURE: u32 = 0x0605_4b50;
+const EOCD_SIZE: u64 = 22;
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x0201_4b50;
+const CENTRAL_HEADER_SIZE: usize = 46;
+const LOCAL_HEADER_SIGNATURE: u32 = 0x0403_4b50;
+const LOCAL_HEADER_SIZE: u64 = 30;
+const ZIP64_LOCATOR_SIGNATURE: u32 = 0x0706_4b50;
+const ZIP64_LOCATOR_SIZE: u64 = 20;
+const ZIP64_EOCD_SIGNATURE: u32 = 0x0606_4b50;
+const ZIP64_EOCD_SIZE: u64 = 56;
+const ZIP64_EXTRA_FIELD_ID: u16 = 0x0001;
+/// Largest central directory read into memory (about a million entries
+/// with long names); anything bigger is treated as corrupt
+const MAX_CD_SIZE: u64 = 256 * 1024 * 1024;
+
+/// Parsed End Of Central Directory record (values taken from the ZIP64
+/// record when the archive has one)
+#[derive(Debug)]
+struct EndOfCentralDirectory {
+    /// Absolute offset of the record that must directly follow the central
+    /// directory (the ZIP64 EOCD record if present, otherwise the EOCD)
+    position: u64,
+    disk_number: u32,
+    cd_start_disk: u32,
+    entries_on_disk: u64,
+    total_entries: u64,
+    cd_size: u64,
+    cd_offset: u64,
+}
+
+/// Central directory entry fields needed to locate and verify a member
+#[derive(Debug)]
+struct CentralEntry {
+    name: String,
+    flags: u16,
+    method: u16,
+    crc32: u32,
+    compressed_size: u64,
+    uncompressed_size: u64,
+    local_offset: u64,
+}
+
+/// Validate the ZIP structure: EOCD record, central directory and the
+/// local-header offsets it declares, all cross-checked against the file length.
+/// With `deep_verify`, every member is also decompressed and CRC-checked.
+fn check_zip_structure(path: &Path, deep_verify: bool) -> Result<(), String> {
+    let mut file = File::open(path).map_err(|_| "Cannot open file".to_string())?;
+
+    // Quick check: valid zip must be at least 22 bytes (EOCD size)
+    let len = file
+        .metadata()
+        .map_err(|_| "Cannot read file metadata".to_string())?
+        .len();
+    if len < EOCD_SIZE {
+        return Err(format!(
+            "File too small ({} bytes, minimum 22 bytes required)",
+            len
+        ));
+    }
+
+    let eocd = find_eocd(&mut file, len)?;
+    let eocd = resolve_zip64(&mut file, eocd)?;
+
+    if eocd.disk_number != 0 || eocd.cd_start_disk != 0 {
+        return Err("Multi-disk (spanned) archive not supported".to_string());
+    }
+    if eocd.entries_on_disk != eocd.total_entries {
+        return Err(format!(
+            "Entry count mismatch (EOCD declares {} entries on disk, {} in total)",
+            eocd.entries_on_disk, eocd.total_entries
+        ));
+    }
+    if eocd.cd_offset > len {
+        return Err(format!(
+            "Central directory offset beyond EOF (offset {}, file length {})",
+            eocd.cd_offset, len
+        ));
+    }
+    let cd_end = offset_end(eocd.cd_offset, eocd.cd_size, "Central directory offset")?;
+    if cd_end > eocd.position {
+        return Err(format!(
+            "Central directory size exceeds available bytes (offset {} + size {} > end record at {})",
+            eocd.cd_offset, eocd.cd_size, eocd.position
+        ));
+    }
+    if eocd.cd_size > MAX_CD_SIZE {
+        return Err(format!(
+            "Central directory too large ({} bytes, limit {})",
+            eocd.cd_size, MAX_CD_SIZE
+        ));
+    }
+
+    let entries = read_central_directory(&mut file, &eocd)?;
+
+    if deep_verify {
+        for entry in &entries {
+            verify_member(&mut file, entry, eocd.cd_offset)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Locate and parse the EOCD record in the last 64 KiB + 22 bytes of the file.
+///
+/// A candidate signature is only accepted when its comment length ends exactly
+/// at EOF, so stray `PK\x05\x06` bytes inside a truncated archive do not pass.
+fn find_eocd(file: &mut File, len: u64) -> Result<EndOfCentralDirectory, String> {
+    let search_len = std::cmp::min(len, 65535 + EOCD_SIZE);
+    let tail_start = len - search_len;
+
+    if file.seek(SeekFrom::Start(tail_start)).is_err() {
+        return Err("Cannot seek to end of file".to_string());
+    }
+
+    let mut buffer = Vec::with_capacity(search_len as usize);
+    if file.read_to_end(&mut buffer).is_err() {
+        return Err("Cannot read file contents".to_string());
+    }
+
+    let mut signature_seen = false;
+    for i in (0..=buffer.len() - EOCD_SIZE as usize).rev() {
+        if read_u32(&buffer, i) != EOCD_SIGNATURE {
+            continue;
+        }
+        signature_seen = true;
+
+        let comment_len = read_u16(&buffer, i + 20) as usize;
+        if i + EOCD_SIZE as usize + comment_len != buffer.len() {
+            continue;
+        }
+
+        return Ok(EndOfCentralDirectory {
+            position: tail_start + i as u64,
+            disk_number: u32::from(read_u16(&buffer, i + 4)),
+            cd_start_disk: u32::from(read_u16(&buffer, i + 6)),
+            entries_on_disk: u64::from(read_u16(&buffer, i + 8)),
+            total_entries: u64::from(read_u16(&buffer, i + 10)),
+            cd_size: u64::from(read_u32(&buffer, i + 12)),
+            cd_offset: u64::from(read_u32(&buffer, i + 16)),
+        });
+    }
+
+    if signature_seen {
+        Err("EOCD record does not end at EOF (truncated or trailing data)".to_string())
+    } else {
+        Err("Missing ZIP signature (corrupted or incomplete transfer)".to_string())
+    }
+}
+
+/// Replace the EOCD values with those of the ZIP64 EOCD record when a ZIP64
+/// locator directly precedes the EOCD, cross-checking offsets on the way.
+fn resolve_zip64(
+    file: &mut File,
+    eocd: EndOfCentralDirectory,
+) -> Result<EndOfCentralDirectory, String> {
+    let saturated = eocd.entries_on_disk == 0xFFFF
+        || eocd.total_entries == 0xFFFF
+        || eocd.cd_size == 0xFFFF_FFFF
+        || eocd.cd_offset == 0xFFFF_FFFF;
+
+    let mut locator = [0u8; ZIP64_LOCATOR_SIZE as usize];
+    let has_locator = eocd.position >= ZIP64_LOCATOR_SIZE
+        && file
+            .seek(SeekFrom::Start(eocd.position - ZIP64_LOCATOR_SIZE))
+            .is_ok()
+        && file.read_exact(&mut locator).is_ok()
+        && read_u32(&locator, 0) == ZIP64_LOCATOR_SIGNATURE;
+
+    if !has_locator {
+        if saturated {
+            return Err("ZIP64 locator missing (EOCD fields require ZIP64)".to_string());
+        }
+        return Ok(eocd);
+    }
+
+    let locator_position = eocd.position - ZIP64_LOCATOR_SIZE;
+    let zip64_disk = read_u32(&locator, 4);
+    let zip64_offset = read_u64(&locator, 8);
+    let total_disks = read_u32(&locator, 16);
+
+    if zip64_disk != 0 || total_disks > 1 {
+        return Err("Multi-disk (spanned) archive not supported".to_string());
+    }
+    if offset_end(zip64_offset, ZIP64_EOCD_SIZE, "ZIP64 EOCD offset")? > locator_position {
+        return Err(format!(
+            "ZIP64 EOCD offset beyond EOF (offset {}, locator at {})",
+            zip64_offset, locator_position
+        ));
+    }
+
+    let mut record = [0u8; ZIP64_EOCD_SIZE as usize];
+    if file.seek(SeekFrom::Start(zip64_offset)).is_err() || file.read_exact(&mut record).is_err() {
+        return Err("Cannot read ZIP64 EOCD record".to_string());
+    }
+    if read_u32(&record, 0) != ZIP64_EOCD_SIGNATURE {
+        return Err(format!(
+            "Invalid ZIP64 EOCD record signature at offset {}",
+            zip64_offset
+        ));
+    }
+
+    // Record size excludes the leading signature and size fields (12 bytes)
+    let record_end = offset_end(
+        zip64_offset + 12,
+        read_u64(&record, 4),
+        "ZIP64 EOCD record size",
+    )?;
+    if record_end != locator_position {
+        return Err(format!(
+            "ZIP64 EOCD record size inconsistent with locator position ({} != {})",
+            record_end, locator_position
+        ));
+    }
+
+    Ok(EndOfCentralDirectory {
+        position: zip64_offset,
+        disk_number: read_u32(&record, 16),
+        cd_start_disk: read_u32(&record, 20),
+        entries_on_disk: read_u64(&record, 24),
+        total_entries: read_u64(&record, 32),
+        cd_size: read_u64(&record, 40),
+        cd_offset: read_u64(&record, 48),
+    })
+}
+
+/// Walk every central directory header and check it against the EOCD record.
+fn read_central_directory(
+    file: &mut File,
+    eocd: &EndOfCentralDirectory,
+) -> Result<Vec<CentralEntry>, String> {
+    if file.seek(SeekFrom::Start(eocd.cd_offset)).is_err() {
+        return Err("Cannot seek to central directory".to_string());
+    }
+
+    let mut cd = vec![0u8; eocd.cd_size as usize];
+    if file.read_exact(&mut cd).is_err() {
+        return Err("Cannot read central directory".to_string());
+    }
+
+    let mut pos = 0usize;
+    let mut count = 0u64;
+    let mut entries = Vec::new();
+
+    while pos < cd.len() {
+        if pos + CENTRAL_HEADER_SIZE > cd.len() {
+            return Err(format!("Central directory entry {} truncated", count + 1));
+        }
+        if read_u32(&cd, pos) != CENTRAL_HEADER_SIGNATURE {
+            return Err(format!(
+                "Invalid central directory header signature at entry {}",
+                count + 1
+            ));
+        }
+
+        let flags = read_u16(&cd, pos + 8);
+        let method = read_u16(&cd, pos + 10);
+        let crc32 = read_u32(&cd, pos + 16);
+        let uncompressed_size = u64::from(read_u32(&cd, pos + 24));
+        let compressed_size = u64::from(read_u32(&cd, pos + 20));
+        let name_len = read_u16(&cd, pos + 28) as usize;
+        let extra_len = read_u16(&cd, pos + 30) as usize;
+        let comment_len = read_u16(&cd, pos + 32) as usize;
+        let local_offset = u64::from(read_u32(&cd, pos + 42));
+
+        let header_len = CENTRAL_HEADER_SIZE + name_len + extra_len + comment_len;
+        if pos + header_len > cd.len() {
+            return Err(format!("Central directory entry {} truncated", count + 1));
+        }
+        let name_start = pos + CENTRAL_HEADER_SIZE;
+        let name = String::from_utf8_lossy(&cd[name_start..name_start + name_len]);
+        let extra = &cd[name_start + name_len..name_start + name_len + extra_len];
+
+        let (uncompressed_size, compressed_size, local_offset) =
+            apply_zip64_extra(extra, uncompressed_size, compressed_size, local_offset)
+                .ok_or_else(|| format!("ZIP64 extra field missing or truncated for '{}'", name))?;
+
+        // Member data lives between its local header and the central directory
+        let data_start = offset_end(local_offset, LOCAL_HEADER_SIZE, "Local header offset")?;
+        if data_start > eocd.cd_offset {
+            return Err(format!(
+                "Local header offset beyond central directory for '{}' (offset {})",
+                name, local_offset
+            ));
+        }
+        if offset_end(data_start, compressed_size, "Compressed size")? > eocd.cd_offset {
+            return Err(format!(
+                "Compressed size of '{}' exceeds archive data ({} bytes at offset {})",
+                name, compressed_size, local_offset
+            ));
+        }
+
+        entries.push(CentralEntry {
+            name: name.to_string(),
+            flags,
+            method,
+            crc32,
+            compressed_size,
+            uncompressed_size,
+            local_offset,
+        });
+
+        pos += header_len;
+        count += 1;
+    }
+
+    if count != eocd.total_entries {
+        return Err(format!(
+            "Entry count mismatch (EOCD declares {}, central directory has {})",
+            eocd.total_entries, count
+        ));
+    }
+
+    Ok(entries)
+}
+
+/// Decompress a single member and compare its CRC32 and size with the
+/// central directory. Encrypted members and compression methods other than
+/// stored (0) and deflate (8) are skipped.
+fn verify_member(file: &mut File, entry: &CentralEntry, cd_offset: u64) -> Result<(), String> {
+    if entry.flags & 0x0001 != 0 {
+        debug!(
+            "Skipping deep verification of encrypted member '{}'",
+            entry.name
+        );
+        return Ok(());
+    }
+    if entry.method != 0 && entry.method != 8 {
+        debug!(
+            "Skipping deep verification of '{}' (compression method {})",
+            entry.name, entry.method
+        );
+        return Ok(());
+    }
+
+    let mut header = [0u8; LOCAL_HEADER_SIZE as usize];
+    if file.seek(SeekFrom::Start(entry.local_offset)).is_err()
+        || file.read_exact(&mut header).is_err()
+    {
+        return Err(format!(
+            "Cannot read local header of member '{}'",
+            entry.name
+        ));
+    }
+    if read_u32(&header, 0) != LOCAL_HEADER_SIGNATURE {
+        return Err(format!(
+            "Invalid local header signature for member '{}'",
+            entry.name
+        ));
+    }
+
+    // Local name/extra lengths may differ from the central directory copy
+    let data_offset = entry.local_offset
+        + LOCAL_HEADER_SIZE
+        + u64::from(read_u16(&header, 26))
+        + u64::from(read_u16(&header, 28));
+    if offset_end(data_offset, entry.compressed_size, "Member data offset")? > cd_offset {
+        return Err(format!(
+            "Data of member '{}' extends into central directory",
+            entry.name
+        ));
+    }
+
+    if file.seek(SeekFrom::Start(data_offset)).is_err() {
+        return Err(format!("Cannot seek to data of member '{}'", entry.name));
+    }
+    let compressed = BufReader::new(&mut *file).take(entry.compressed_size);
+    let mut reader: Box<dyn Read + '_> = if entry.method == 8 {
+        Box::new(DeflateDecoder::new(compressed))
+    } else {
+        Box::new(compressed)
+    };
+
+    let mut hasher = crc32fast::Hasher::new();
+    let mut actual_size = 0u64;
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let n = reader
+            .read(&mut buffer)
+            .map_err(|e| format!("Decompression failed for member '{}': {}", entry.name, e))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+        actual_size += n as u64;
+    }
+
+    let actual_crc = hasher.finalize();
+    if actual_crc != entry.crc32 {
+        return Err(format!(
+            "CRC mismatch in member '{}' (expected {:08x}, actual {:08x})",
+            entry.name, entry.crc32, actual_crc
+        ));
+    }
+    if actual_size != entry.uncompressed_size {
+        return Err(format!(
+            "Size mismatch in member '{}' (expected {} bytes, actual {} bytes)",
+            entry.name, entry.uncompressed_size, actual_size
+        ));
+    }
+
+    Ok(())
+}
+
+/// Substitute 0xFFFFFFFF placeholders with the 64-bit values from the ZIP64
+/// extended information extra field. Returns `None` if a placeholder has no
+/// matching value in the extra field.
+fn apply_zip64_extra(
+    extra: &[u8],
+    uncompressed_size: u64,
+    compressed_size: u64,
+    local_offset: u64,
+) -> Option<(u64, u64, u64)> {
+    let needs_uncompressed = uncompressed_size == 0xFFFF_FFFF;
+    let needs_compressed = compressed_size == 0xFFFF_FFFF;
+    let needs_offset = local_offset == 0xFFFF_FFFF;
+
+    if !needs_uncompressed && !needs_compressed && !needs_offset {
+        return Some((uncompressed_size, compressed_size, local_offset));
+    }
+
+    // Extra field is a sequence of (header id, data size, data) blocks
+    let mut pos = 0usize;
+    while pos + 4 <= extra.len() {
+        let id = read_u16(extra, pos);
+        let size = read_u16(extra, pos + 2) as usize;
+        let data_start = pos + 4;
+        if data_start + size > extra.len() {
+            return None;
+        }
+
+        if id == ZIP64_EXTRA_FIELD_ID {
+            let data = &extra[data_start..data_start + size];
+            let mut field = 0usize;
+            let mut next = |present: bool, fallback: u64| -> Option<u64> {
+                if !present {
+                    return Some(fallback);
+                }
+                if field + 8 > data.len() {
+                    return None;
+                }
+                let value = read_u64(data, field);
+                field += 8;
+                Some(value)
+            };
+            // Values appear in this fixed order, only when their field is saturated
+            let uncompressed = next(needs_uncompressed, uncompressed_size)?;
+            let compressed = next(needs_compressed, compressed_size)?;
+            let offset = next(needs_offset, local_offset)?;
+            return Some((uncompressed, compressed, offset));
+        }
+
+        pos = data_start + size;
+    }
+
+    None
+}
+
+/// `offset + size` for values read from the archive, which a corrupt or
+/// hostile file can set anywhere up to `u64::MAX`
+fn offset_end(offset: u64, size: u64, what: &str) -> Result<u64, String> {
+    offset
+        .checked_add(size)
+        .ok_or_else(|| format!("{what} overflows (offset {offset}, size {size})"))
+}
+
+fn read_u16(buf: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([buf[offset], buf[offset + 1]])
+}
+
+fn read_u32(buf: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        buf[offset],
+        buf[offset + 1],
+        buf[offset + 2],
+        buf[offset + 3],
+    ])
+}
+
+fn read_u64(buf: &[u8], offset: usize) -> u64 {
+    let mut bytes = [0u8; 8];
+    bytes.copy_from_slice(&buf[offset..offset + 8]);
+    u64::from_le_bytes(bytes)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::io::Write;
+
+    /// Build a minimal stored (uncompressed) ZIP archive in memory
+    pub(crate) fn build_zip(members: &[(&str, &[u8])]) -> Vec<u8> {
+        build_archive(members, false, false)
+    }
+
+    /// Same as `build_zip`, but with ZIP64 structures: saturated EOCD fields,
+    /// a ZIP64 EOCD record + locator, and local offsets in ZIP64 extra fields
+    fn build_zip64(members: &[(&str, &[u8])]) -> Vec<u8> {
+        build_archive(members, true, false)
+    }
+
+    /// Same as `build_zip`, but with deflate-compressed members
+    fn build_deflate_zip(members: &[(&str, &[u8])]) -> Vec<u8> {
+        build_archive(members, false, true)
+    }
+
+    fn build_archive(members: &[(&str, &[u8])], zip64: bool, deflate: bool) -> Vec<u8> {
+        let mut data = Vec::new();
+        let mut central = Vec::new();
+
+        for (name, content) in members {
+            let crc = crc32fast::hash(content);
+            let (method, stored) = if deflate {
+                let mut encoder =
+                    flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+                encoder.write_all(content).unwrap();
+                (8u8, encoder.finish().unwrap())
+            } else {
+                (0u8, content.to_vec())
+            };
+
+            let offset = data.len() as u32;
+            data.extend_from_slice(&LOCAL_HEADER_SIGNATURE.to_le_bytes());
+            data.extend_from_slice(&[20, 0, 0, 0, method, 0, 0, 0, 0, 0]);
+            data.extend_from_slice(&crc.to_le_bytes());
+            data.extend_from_slice(&(stored.len() as u32).to_le_bytes());
+            data.extend_from_slice(&(content.len() as u32).to_le_bytes());
+            data.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            data.extend_from_slice(&0u16.to_le_bytes());
+            data.extend_from_slice(name.as_bytes());
+            data.extend_from_slice(&stored);
+
+            central.extend_from_slice(&CENTRAL_HEADER_SIGNATURE.to_le_bytes());
+            central.extend_from_slice(&[20, 0, 20, 0, 0, 0, method, 0, 0, 0, 0, 0]);
+            central.extend_from_slice(&crc.to_le_bytes());
+            central.extend_from_slice(&(stored.len() as u32).to_le_bytes());
+            central.extend_from_slice(&(content.len() as u32).to_le_bytes());
+            central.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            central.extend_from_slice(&(if zip64 { 12u16 } else { 0 }).to_le_bytes());
+            central.extend_from_slice(&[0; 10]);
+            if zip64 {
+                central.extend_from_slice(&0xFFFF_FFFFu32.to_le_bytes());
+                central.extend_from_slice(name.as_bytes());
+                central.extend_from_slice(&ZIP64_EXTRA_FIELD_ID.to_le_bytes());
+                central.extend_from_slice(&8u16.to_le_bytes());
+                central.extend_from_slice(&u64::from(offset).to_le_bytes());
+            } else {
+                central.extend_from_slice(&offset.to_le_bytes());
+                central.extend_from_slice(name.as_bytes());
+            }
+        }
+
+        let cd_offset = data.len() as u64;
+        let count = members.len() as u64;
+        data.extend_from_slice(&central);
+
+        if zip64 {
+            let record_offset = data.len() as u64;
+            data.extend_from_slice(&ZIP64_EOCD_SIGNATURE.to_le_bytes());
+            data.extend_from_slice(&(ZIP64_EOCD_SIZE - 12).to_le_bytes());
+            data.extend_from_slice(&[45, 0, 45, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+            data.extend_from_slice(&count.to_le_bytes());
+            data.extend_from_slice(&count.to_le_bytes());
+            data.extend_from_slice(&(central.len() as u64).to_le_bytes());
+            data.extend_from_slice(&cd_offset.to_le_bytes());
+
+            data.extend_from_slice(&ZIP64_LOCATOR_SIGNATURE.to_le_bytes());
+            data.extend_from_slice(&0u32.to_le_bytes());
+            data.extend_from_slice(&record_offset.to_le_bytes());
+            data.extend_from_slice(&1u32.to_le_bytes());
+        }
+
+        let (eocd_count, eocd_size, eocd_offset) = if zip64 {
+            (0xFFFF, 0xFFFF_FFFF, 0xFFFF_FFFF)
+        } else {
+            (count as u16, central.len() as u32, cd_offset as u32)
+        };
+        data.extend_from_slice(&EOCD_SIGNATURE.to_le_bytes());
+        data.extend_from_slice(&[0, 0, 0, 0]);
+        data.extend_from_slice(&eocd_count.to_le_bytes());
+        data.extend_from_slice(&eocd_count.to_le_bytes());
+        data.extend_from_slice(&eocd_size.to_le_bytes());
+        data.extend_from_slice(&eocd_offset.to_le_bytes());
+        data.extend_from_slice(&0u16.to_le_bytes());
+        data
+    }
+
+    fn check_bytes(bytes: &[u8]) -> Result<(), String> {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(bytes).unwrap();
+        file.flush().unwrap();
+        check_zip_structure(file.path(), false)
+    }
+
+    fn deep_check_bytes(bytes: &[u8]) -> Result<(), String> {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(bytes).unwrap();
+        file.flush().unwrap();
+        check_zip_structure(file.path(), true)
+    }
+
+    fn eocd_offset(bytes: &[u8]) -> usize {
+        bytes.len() - EOCD_SIZE as usize
+    }
+
+    #[test]
+    fn test_valid_zip_passes() {
+        let zip = build_zip(&[("a.txt", b"hello"), ("b.txt", b"world!")]);
+        assert_eq!(check_bytes(&zip), Ok(()));
+    }
+
+    #[test]
+    fn test_empty_zip_passes() {
+        let zip = build_zip(&[]);
+        assert_eq!(check_bytes(&zip), Ok(()));
+    }
+
+    #[test]
+    fn test_missing_signature() {
+        let reason = check_bytes(&[0u8; 100]).unwrap_err();
+        assert!(reason.contains("Missing ZIP signature"));
+    }
+
+    #[test]
+    fn test_truncated_archive_with_stray_signature() {
+        // Stray EOCD signature inside member data, real EOCD cut off
+        let zip = build_zip(&[("a.bin", b"xxPK\x05\x06yyyyyyyyyyyyyyyyyyyyyyyyyyyy")]);
+        let truncated = &zip[..zip.len() - 30];
+        let reason = check_bytes(truncated).unwrap_err();
+        assert!(reason.contains("does not end at EOF"));
+    }
+
+    #[test]
+    fn test_central_directory_offset_beyond_eof() {
+        let mut zip = build_zip(&[("a.txt", b"hello")]);
+        let eocd = eocd_offset(&zip);
+        zip[eocd + 16..eocd + 20].copy_from_slice(&1_000_000u32.to_le_bytes());
+        let reason = check_bytes(&zip).unwrap_err();
+        assert!(reason.contains("Central directory offset beyond EOF"));
+    }
+
+    #[test]
+    fn test_missing_central_directory() {
+        // EOCD is intact but the central directory bytes are gone
+        let zip = build_zip(&[("a.txt", b"hello")]);
+        let eocd = eocd_offset(&zip);
+        let mut broken = zip[..20].to_vec();
+        broken.extend_from_slice(&zip[eocd..]);
+        let reason = check_bytes(&broken).unwrap_err();
+        assert!(reason.contains("Central directory size exceeds"));
+    }
+
+    #[test]
+    fn test_entry_count_mismatch() {
+        let mut zip = build_zip(&[("a.txt", b"hello"), ("b.txt", b"world")]);
+        let eocd = eocd_offset(&zip);
+        zip[eocd + 8..eocd + 10].copy_from_slice(&3u16.to_le_bytes());
+        zip[eocd + 10..eocd + 12].copy_from_slice(&3u16.to_le_bytes());
+        let reason = check_bytes(&zip).unwrap_err();
+        assert!(reason.contains("Entry count mismatch"));
+    }
+
+    #[test]
+    fn test_local_header_offset_beyond_central_directory() {
+        let mut zip = build_zip(&[("a.txt", b"hello")]);
+        let eocd = eocd_offset(&zip);
+        let cd_offset = read_u32(&zip, eocd + 16) as usize;
+        zip[cd_offset + 42..cd_offset + 46].copy_from_slice(&500u32.to_le_bytes());
+        let reason = check_bytes(&zip).unwrap_err();
+        assert!(reason.contains("Local header offset beyond central directory"));
+    }
+
+    #[test]
+    fn test_zip64_archive_passes() {
+        let zip = build_zip64(&[("a.txt", b"hello"), ("b.txt", b"world!")]);
+        assert_eq!(check_bytes(&zip), Ok(()));
+    }
+
+    #[test]
+    fn test_zip64_locator_missing() {
+        // Saturated EOCD fields without a ZIP64 locator in front of the EOCD
+        let zip = build_zip64(&[("a.txt", b"hello")]);
+        let eocd = eocd_offset(&zip);
+        let locator = eocd - ZIP64_LOCATOR_SIZE as usize;
+        let mut broken = zip[..locator].to_vec();
+        broken.extend_from_slice(&zip[eocd..]);
+        let reason = check_bytes(&broken).unwrap_err();
+        assert!(reason.contains("ZIP64 locator missing"));
+    }
+
+    #[test]
+    fn test_zip64_record_offset_beyond_eof() {
+        let mut zip = build_zip64(&[("a.txt", b"hello")]);
+        let locator = eocd_offset(&zip) - ZIP64_LOCATOR_SIZE as usize;
+        zip[locator + 8..locator + 16].copy_from_slice(&(1u64 << 40).to_le_bytes());
+        let reason = check_bytes(&zip).unwrap_err();
+        assert!(reason.contains("ZIP64 EOCD offset beyond EOF"));
+    }
+
+    #[test]
+    fn test_zip64_offsets_near_u64_max() {
+        // Offsets and sizes close to u64::MAX must fail cleanly instead of
+        // overflowing (a panic in debug builds, a wrapped bounds check in release)
+        let zip = build_zip64(&[("a.txt", b"hello")]);
+        let locator = eocd_offset(&zip) - ZIP64_LOCATOR_SIZE as usize;
+        let record = read_u64(&zip, locator + 8) as usize;
+
+        let mut broken = zip.clone();
+        broken[record + 48..record + 56].copy_from_slice(&(u64::MAX - 8).to_le_bytes());
+        let reason = check_bytes(&broken).unwrap_err();
+        assert!(reason.contains("Central directory offset beyond EOF"));
+
+        let mut broken = zip.clone();
+        broken[record + 40..record + 48].copy_from_slice(&(u64::MAX - 8).to_le_bytes());
+        let reason = check_bytes(&broken).unwrap_err();
+        assert!(reason.contains("Central directory offset overflows"));
+
+        let mut broken = zip.clone();
+        broken[locator + 8..locator + 16].copy_from_slice(&(u64::MAX - 8).to_le_bytes());
+        let reason = check_bytes(&broken).unwrap_err();
+        assert!(reason.contains("ZIP64 EOCD offset overflows"));
+
+        let mut broken = zip;
+        broken[record + 4..record + 12].copy_from_slice(&u64::MAX.to_le_bytes());
+        let reason = check_bytes(&broken).unwrap_err();
+        assert!(reason.contains("ZIP64 EOCD record size overflows"));
+    }
+
+    #[test]
+    fn test_central_directory_size_limit() {
+        // Sparse file: a declared central directory just over the limit at
+        // offset 0, followed by the ZIP64 records and the EOCD
+        let cd_size = MAX_CD_SIZE + 1;
+        let mut tail = Vec::new();
+        tail.extend_from_slice(&ZIP64_EOCD_SIGNATURE.to_le_bytes());
+        tail.extend_from_slice(&(ZIP64_EOCD_SIZE - 12).to_le_bytes());
+        tail.extend_from_slice(&[45, 0, 45, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        tail.extend_from_slice(&1u64.to_le_bytes());
+        tail.extend_from_slice(&1u64.to_le_bytes());
+        tail.extend_from_slice(&cd_size.to_le_bytes());
+        tail.extend_from_slice(&0u64.to_le_bytes());
+        tail.extend_from_slice(&ZIP64_LOCATOR_SIGNATURE.to_le_bytes());
+        tail.extend_from_slice(&0u32.to_le_bytes());
+        tail.extend_from_slice(&cd_size.to_le_bytes());
+        tail.extend_from_slice(&1u32.to_le_bytes());
+        tail.extend_from_slice(&EOCD_SIGNATURE.to_le_bytes());
+        tail.extend_from_slice(&[0, 0, 0, 0, 0xFF, 0xFF, 0xFF, 0xFF]);
+        tail.extend_from_slice(&[0xFF; 8]);
+        tail.extend_from_slice(&0u16.to_le_bytes());
+
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.as_file().set_len(cd_size).unwrap();
+        file.seek(SeekFrom::Start(cd_size)).unwrap();
+        file.write_all(&tail).unwrap();
+        file.flush().unwrap();
+
+        let reason = check_zip_structure(file.path(), false).unwrap_err();
+        assert!(reason.contains("Central directory too large"));
+    }
+
+    #[test]
+    fn test_zip64_entry_count_mismatch() {
+        let mut zip = build_zip64(&[("a.txt", b"hello")]);
+        let locator = eocd_offset(&zip) - ZIP64_LOCATOR_SIZE as usize;
+        let record = read_u64(&zip, locator + 8) as usize;
+        zip[record + 24..record + 32].copy_from_slice(&70_000u64.to_le_bytes());
+        zip[record + 32..record + 40].copy_from_slice(&70_000u64.to_le_bytes());
+        let reason = check_bytes(&zip).unwrap_err();
+        assert!(reason.contains("Entry count mismatch"));
+    }
+
+    #[test]
+    fn test_zip64_extra_field_missing() {
+        let mut zip = build_zip64(&[("a.txt", b"hello")]);
+        let locator = eocd_offset(&zip) - ZIP64_LOCATOR_SIZE as usize;
+        let record = read_u64(&zip, locator + 8) as usize;
+        let cd_offset = read_u64(&zip, record + 48) as usize;
+        // Rename the extra field so the ZIP64 block can no longer be found
+        zip[cd_offset + 46 + 5..cd_offset + 46 + 7].copy_from_slice(&0x9999u16.to_le_bytes());
+        let reason = check_bytes(&zip).unwrap_err();
+        assert!(reason.contains("ZIP64 extra field missing"));
+    }
+
+    #[test]
+    fn test_deep_verify_stored_and_deflate() {
+        let content = b"beam data beam data beam data beam data".repeat(100);
+        let stored = build_zip(&[("a.dat", &content), ("b.dat", b"")]);
+        assert_eq!(deep_check_bytes(&stored), Ok(()));
+
+        let deflated = build_deflate_zip(&[("a.dat", &content)]);
+        assert_eq!(deep_check_bytes(&deflated), Ok(()));
+    }
+
+    #[test]
+    fn test_deep_verify_detects_bit_rot() {
+        let mut zip = build_zip(&[("a.dat", b"hello world")]);
+        // Flip a byte inside the member data (after 30-byte header + 5-byte name)
+        zip[30 + 5 + 2] ^= 0xFF;
+
+        // Structural check alone cannot see this
+        assert_eq!(check_bytes(&zip), Ok(()));
+
+        let reason = deep_check_bytes(&zip).unwrap_err();
+        assert!(reason.contains("CRC mismatch in member 'a.dat'"));
+        assert!(reason.contains(&format!("expected {:08x}", crc32fast::hash(b"hello world"))));
+    }
+
+    #[test]
+    fn test_deep_verify_detects_corrupt_deflate_stream() {
+        let content = b"0123456789".repeat(500);
+        let mut zip = build_deflate_zip(&[("a.dat", &content)]);
+        let data_start = 30 + 5;
+        for byte in &mut zip[data_start..data_start + 8] {
+            *byte = 0xFF;
+        }
+
+        let reason = deep_check_bytes(&zip).unwrap_err();
+        assert!(
+            reason.contains("member 'a.dat'"),
+            "unexpected reason: {}",
+            reason
+        );
+    }
+}
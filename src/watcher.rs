@@ -0,0 +1,442 @@
+use crate::manifest;
+use crate::naming::DirPattern;
+use crate::transfer::{SizeWalk, TransferActivity, TransferSample};
+use crate::types::FileWrite;
+use chrono::{DateTime, Local};
+use inotify::{EventMask, Inotify, WatchDescriptor, WatchMask};
+use log::{debug, warn};
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// A file open for writing that has not written for this long is dropped
+/// from the copying list (the client went away without closing it)
+const STALE_MINUTES: i64 = 10;
+
+/// A file written to since it was created or first seen
+#[derive(Debug)]
+struct WrittenFile {
+    dir_key: String,
+    /// Disk usage already counted as transferred, in bytes
+    counted: u64,
+    len: u64,
+    last_write: DateTime<Local>,
+    /// Closed after being renamed away, waiting for the rename's events
+    closed: bool,
+}
+
+/// Follows the writes to one `Line <X>` tree as they happen, so short
+/// bursts between two samples are not missed and no walk is needed to
+/// tell which directories are growing.
+pub struct TransferWatcher {
+    inotify: Inotify,
+    root: PathBuf,
+    pattern: DirPattern,
+    dirs: HashMap<WatchDescriptor, PathBuf>,
+    /// Files with writes and no close-write yet
+    open: HashMap<PathBuf, WrittenFile>,
+    /// Bytes written per directory key since the last `activity` call
+    written: HashMap<String, u64>,
+    /// Files renamed away from a watched directory by rename cookie, to
+    /// tell a rename within the tree from a file moved in from outside
+    moved_from: HashMap<u32, Option<WrittenFile>>,
+    /// The kernel queue overflowed: writes were lost, so the next
+    /// `activity` call walks the tree instead of trusting the counts
+    overflowed: bool,
+    since: DateTime<Local>,
+}
+
+impl TransferWatcher {
+    /// Watch every directory under `search_dir`. Fails when inotify is not
+    /// available or the watch limit (`fs.inotify.max_user_watches`) is hit.
+    pub fn new(search_dir: &str, pattern: &DirPattern) -> io::Result<Self> {
+        let mut watcher = Self {
+            inotify: Inotify::init()?,
+            root: PathBuf::from(search_dir),
+            pattern: pattern.clone(),
+            dirs: HashMap::new(),
+            open: HashMap::new(),
+            written: HashMap::new(),
+            moved_from: HashMap::new(),
+            overflowed: false,
+            since: Local::now(),
+        };
+        watcher.watch_tree(Path::new(search_dir), false)?;
+        debug!(
+            "Watching {} directories under {}",
+            watcher.dirs.len(),
+            search_dir
+        );
+        Ok(watcher)
+    }
+
+    /// Add a watch for `dir` and every directory below it. Files already in
+    /// a directory that was just created were copied before its watch was
+    /// in place, so they are counted when `count_files` is set.
+    fn watch_tree(&mut self, dir: &Path, count_files: bool) -> io::Result<()> {
+        let mask = WatchMask::CREATE
+            | WatchMask::MODIFY
+            | WatchMask::CLOSE_WRITE
+            | WatchMask::MOVED_FROM
+            | WatchMask::MOVED_TO
+            | WatchMask::DELETE_SELF;
+        for entry in WalkDir::new(dir)
+            .into_iter()
+            .filter_map(std::result::Result::ok)
+        {
+            if entry.file_type().is_dir() {
+                let wd = self.inotify.watches().add(entry.path(), mask)?;
+                self.dirs.insert(wd, entry.path().to_path_buf());
+            } else if count_files && entry.file_type().is_file() {
+                self.record_write(entry.path(), true);
+                self.open.remove(entry.path());
+            }
+        }
+        Ok(())
+    }
+
+    /// Drain the pending inotify events. Cheap; call it often so the kernel
+    /// queue does not overflow.
+    pub fn poll(&mut self) {
+        let mut buffer = [0u8; 4096];
+        loop {
+            let events: Vec<(EventMask, PathBuf, Option<WatchDescriptor>, u32)> =
+                match self.inotify.read_events(&mut buffer) {
+                    Ok(events) => events
+                        .filter_map(|event| {
+                            if event.mask.contains(EventMask::Q_OVERFLOW) {
+                                return Some((event.mask, self.root.clone(), None, 0));
+                            }
+                            let dir = self.dirs.get(&event.wd)?;
+                            let path = event.name.map_or_else(|| dir.clone(), |n| dir.join(n));
+                            Some((event.mask, path, Some(event.wd), event.cookie))
+                        })
+                        .collect(),
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => return,
+                    Err(e) => {
+                        warn!(
+                            "Cannot read inotify events for {}: {}",
+                            self.root.display(),
+                            e
+                        );
+                        return;
+                    }
+                };
+            if events.is_empty() {
+                return;
+            }
+            for (mask, path, wd, cookie) in events {
+                self.handle_event(mask, &path, wd, cookie);
+            }
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        mask: EventMask,
+        path: &Path,
+        wd: Option<WatchDescriptor>,
+        cookie: u32,
+    ) {
+        if mask.contains(EventMask::Q_OVERFLOW) {
+            warn!(
+                "inotify queue overflowed for {}, walking it on the next sample",
+                self.root.display()
+            );
+            self.overflowed = true;
+            // Directories created while events were dropped have no watch yet
+            let root = self.root.clone();
+            if let Err(e) = self.watch_tree(&root, false) {
+                warn!("Cannot watch {}: {}", root.display(), e);
+            }
+        } else if mask.contains(EventMask::DELETE_SELF) || mask.contains(EventMask::IGNORED) {
+            if let Some(wd) = wd {
+                self.dirs.remove(&wd);
+            }
+        } else if mask.contains(EventMask::ISDIR) {
+            if (mask.contains(EventMask::CREATE) || mask.contains(EventMask::MOVED_TO))
+                && let Err(e) = self.watch_tree(path, true)
+            {
+                warn!("Cannot watch {}: {}", path.display(), e);
+            }
+        } else if mask.contains(EventMask::CLOSE_WRITE) {
+            if self.record_write(path, false) {
+                self.open.remove(path);
+            } else if let Some(file) = self.open.get_mut(path) {
+                // Renamed before this event was read; the rename follows
+                file.closed = true;
+            }
+        } else if mask.contains(EventMask::CREATE) {
+            self.record_write(path, true);
+        } else if mask.contains(EventMask::MODIFY) {
+            self.record_write(path, false);
+        } else if mask.contains(EventMask::MOVED_FROM) {
+            let file = self.open.remove(path);
+            self.moved_from.insert(cookie, file);
+        } else if mask.contains(EventMask::MOVED_TO) {
+            match self.moved_from.remove(&cookie) {
+                // Renamed within the tree (e.g. rsync temp files): count
+                // what was written under the old name but not seen yet
+                Some(Some(mut file)) => {
+                    let closed = std::mem::take(&mut file.closed);
+                    file.dir_key = self.pattern.dir_key(&self.root, path);
+                    self.open.insert(path.to_path_buf(), file);
+                    self.record_write(path, false);
+                    if closed {
+                        self.open.remove(path);
+                    }
+                }
+                // Renamed within the tree after it was fully counted
+                Some(None) => {
+                    let dir_key = self.pattern.dir_key(&self.root, path);
+                    self.written.entry(dir_key).or_insert(0);
+                }
+                // Moved in from outside the tree: all of it is new here
+                None => {
+                    self.record_write(path, true);
+                    self.open.remove(path);
+                }
+            }
+        }
+    }
+
+    /// Count the growth of `path` since it was last seen. A file first seen
+    /// mid-copy (written to before the watcher started) is counted from its
+    /// current size on, not from zero. False when `path` is gone.
+    fn record_write(&mut self, path: &Path, created: bool) -> bool {
+        if manifest::is_manifest_file(path) {
+            return true;
+        }
+        let Ok(metadata) = path.metadata() else {
+            // Already renamed: remember a new file so that a rename within
+            // the tree still counts it from zero
+            if created {
+                self.open
+                    .entry(path.to_path_buf())
+                    .or_insert_with(|| WrittenFile {
+                        dir_key: self.pattern.dir_key(&self.root, path),
+                        counted: 0,
+                        len: 0,
+                        last_write: Local::now(),
+                        closed: false,
+                    });
+            }
+            return false;
+        };
+        let usage = metadata.blocks() * 512;
+        let file = self
+            .open
+            .entry(path.to_path_buf())
+            .or_insert_with(|| WrittenFile {
+                dir_key: self.pattern.dir_key(&self.root, path),
+                counted: if created { 0 } else { usage },
+                len: 0,
+                last_write: Local::now(),
+                closed: false,
+            });
+        let grown = usage.saturating_sub(file.counted);
+        file.counted = file.counted.max(usage);
+        file.len = metadata.len();
+        file.last_write = Local::now();
+        if grown > 0 {
+            *self.written.entry(file.dir_key.clone()).or_insert(0) += grown;
+        }
+        true
+    }
+
+    /// Activity since the previous call. `walk` is moved forward by the
+    /// bytes written, so it stays current between full walks. After a queue
+    /// overflow `walk` is compared with a fresh walk instead.
+    pub fn activity(&mut self, line_id: &str, walk: &mut SizeWalk) -> TransferActivity {
+        self.poll();
+        // A rename's two events arrive together; older cookies were moved
+        // out of the tree
+        self.moved_from.clear();
+        if std::mem::take(&mut self.overflowed) {
+            return self.walk_activity(line_id, walk);
+        }
+        let now = Local::now();
+        let elapsed_ms = now
+            .signed_duration_since(self.since)
+            .num_milliseconds()
+            .max(1) as u64;
+        self.since = now;
+
+        self.open.retain(|path, file| {
+            let stale = now.signed_duration_since(file.last_write).num_minutes() >= STALE_MINUTES;
+            if stale {
+                debug!(
+                    "No writes to {} for {} minutes, dropping it",
+                    path.display(),
+                    STALE_MINUTES
+                );
+            }
+            // Closed entries left over were renamed out of the tree
+            !stale && !file.closed
+        });
+
+        let mut bytes_written = 0;
+        let mut growing_dirs: HashSet<String> = HashSet::new();
+        for (dir_key, bytes) in self.written.drain() {
+            bytes_written += bytes;
+            *walk.dirs.entry(dir_key.clone()).or_insert(0) += bytes;
+            growing_dirs.insert(dir_key);
+        }
+        walk.total += bytes_written;
+        walk.taken_at = now;
+        growing_dirs.extend(self.open.values().map(|f| f.dir_key.clone()));
+
+        let mut copying: Vec<(&PathBuf, &WrittenFile)> = self.open.iter().collect();
+        copying.sort_by_key(|(_, file)| std::cmp::Reverse(file.last_write));
+
+        TransferActivity {
+            speed_bps: bytes_written * 1000 / elapsed_ms,
+            growing_dirs,
+            recent_files: copying
+                .into_iter()
+                .map(|(path, file)| FileWrite::new(path, file.len, file.last_write))
+                .collect(),
+            live: true,
+        }
+    }
+
+    /// Activity from comparing `walk` with a fresh walk of the tree. The
+    /// files still open are counted from their current size on, which the
+    /// fresh walk already includes.
+    fn walk_activity(&mut self, line_id: &str, walk: &mut SizeWalk) -> TransferActivity {
+        let search_dir = self.root.to_string_lossy();
+        let after = SizeWalk::take(&search_dir, &self.pattern);
+        let sample = TransferSample {
+            before: std::mem::replace(walk, after.clone()),
+            after,
+        };
+        self.since = sample.after.taken_at;
+        self.written.clear();
+        self.open.retain(|path, file| {
+            let Ok(metadata) = path.metadata() else {
+                return false;
+            };
+            file.counted = metadata.blocks() * 512;
+            file.len = metadata.len();
+            true
+        });
+        sample.activity(line_id, &search_dir)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::io::Write;
+
+    #[test]
+    fn test_watcher_follows_copies() {
+        let temp = tempfile::tempdir().unwrap();
+        let search_dir = temp.path().join("Line B");
+        fs::create_dir_all(search_dir.join("Archive_Beam_B_2025-03-13")).unwrap();
+        fs::write(
+            search_dir.join("Archive_Beam_B_2025-03-13/Cam1.zip"),
+            vec![1u8; 8192],
+        )
+        .unwrap();
+
+        let search_dir = search_dir.to_str().unwrap();
+        let pattern = DirPattern::default();
+        let mut watcher = TransferWatcher::new(search_dir, &pattern).unwrap();
+        let mut walk = SizeWalk::take(search_dir, &pattern);
+        let total_before = walk.total;
+
+        // A new day folder appears and one archive is still being written
+        let day = format!("{}/Archive_Beam_B_2025-03-14", search_dir);
+        fs::create_dir(&day).unwrap();
+        watcher.poll();
+        fs::write(format!("{}/Cam1.zip", day), vec![1u8; 16384]).unwrap();
+        let mut copying = fs::File::create(format!("{}/Cam2.zip", day)).unwrap();
+        copying.write_all(&[2u8; 16384]).unwrap();
+        copying.flush().unwrap();
+
+        let activity = watcher.activity("B", &mut walk);
+        assert!(activity.live);
+        assert!(activity.speed_bps > 0);
+        assert_eq!(
+            activity.growing_dirs,
+            HashSet::from(["Archive_Beam_B_2025-03-14".to_string()])
+        );
+        assert_eq!(activity.recent_files.len(), 1);
+        assert!(activity.recent_files[0].path.ends_with("Cam2.zip"));
+        assert_eq!(walk.total, SizeWalk::take(search_dir, &pattern).total);
+        assert!(walk.total > total_before);
+
+        // Closing the file ends the copy; nothing was written since
+        drop(copying);
+        let activity = watcher.activity("B", &mut walk);
+        assert_eq!(activity.speed_bps, 0);
+        assert!(activity.recent_files.is_empty());
+        assert!(activity.growing_dirs.is_empty());
+    }
+
+    #[test]
+    fn test_watcher_counts_files_moved_in() {
+        let temp = tempfile::tempdir().unwrap();
+        let staging = temp.path().join("staging");
+        let search_dir = temp.path().join("Line B");
+        let day = search_dir.join("Archive_Beam_B_2025-03-14");
+        fs::create_dir_all(&staging).unwrap();
+        fs::create_dir_all(&day).unwrap();
+
+        let search_dir = search_dir.to_str().unwrap();
+        let pattern = DirPattern::default();
+        let mut watcher = TransferWatcher::new(search_dir, &pattern).unwrap();
+        let mut walk = SizeWalk::take(search_dir, &pattern);
+
+        // Written outside the tree, then moved into the day folder
+        fs::write(staging.join("Cam1.zip"), vec![1u8; 16384]).unwrap();
+        fs::rename(staging.join("Cam1.zip"), day.join("Cam1.zip")).unwrap();
+        let activity = watcher.activity("B", &mut walk);
+        assert!(activity.speed_bps > 0);
+        assert_eq!(walk.total, SizeWalk::take(search_dir, &pattern).total);
+
+        // Written in the tree under a temporary name, then renamed: counted once
+        fs::write(day.join(".Cam2.zip.tmp"), vec![2u8; 16384]).unwrap();
+        fs::rename(day.join(".Cam2.zip.tmp"), day.join("Cam2.zip")).unwrap();
+        watcher.activity("B", &mut walk);
+        assert_eq!(walk.total, SizeWalk::take(search_dir, &pattern).total);
+    }
+
+    #[test]
+    fn test_watcher_walks_after_queue_overflow() {
+        let temp = tempfile::tempdir().unwrap();
+        let search_dir = temp.path().join("Line B");
+        fs::create_dir_all(&search_dir).unwrap();
+
+        let search_dir_str = search_dir.to_str().unwrap();
+        let pattern = DirPattern::default();
+        let mut watcher = TransferWatcher::new(search_dir_str, &pattern).unwrap();
+        let mut walk = SizeWalk::take(search_dir_str, &pattern);
+
+        // The queue overflows while this folder is copied
+        let day = search_dir.join("Archive_Beam_B_2025-03-14");
+        fs::create_dir(&day).unwrap();
+        fs::write(day.join("Cam1.zip"), vec![1u8; 16384]).unwrap();
+        watcher.handle_event(EventMask::Q_OVERFLOW, &search_dir, None, 0);
+        assert!(watcher.dirs.values().any(|dir| *dir == day));
+
+        let activity = watcher.activity("B", &mut walk);
+        assert!(!activity.live);
+        assert!(activity.speed_bps > 0);
+        assert_eq!(
+            activity.growing_dirs,
+            HashSet::from(["Archive_Beam_B_2025-03-14".to_string()])
+        );
+        assert_eq!(walk.total, SizeWalk::take(search_dir_str, &pattern).total);
+
+        // Back to following events once the tree was walked
+        fs::write(day.join("Cam2.zip"), vec![2u8; 16384]).unwrap();
+        let activity = watcher.activity("B", &mut walk);
+        assert!(activity.live);
+        assert_eq!(walk.total, SizeWalk::take(search_dir_str, &pattern).total);
+    }
+}